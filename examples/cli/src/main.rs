@@ -1,4 +1,4 @@
-use routes::Match;
+use routes::{Match, ToPath};
 
 include!(concat!(env!("OUT_DIR"), "/routes.rs"));
 
@@ -16,7 +16,7 @@ fn main() {
     match result {
         Err(e) => println!("Error: {}", e),
         Ok(Match::NotFound) => println!("No matching route."),
-        Ok(Match::NotAllowed) => println!("Route does not support method."),
+        Ok(Match::NotAllowed(_)) => println!("Route does not support method."),
         Ok(Match::Route(p)) => println!("Route to {}", p.to_path()),
         Ok(Match::Redirect(p)) => println!("Redirect to {}", p.to_path()),
     }
@@ -260,7 +260,7 @@ mod tests {
             (
                 "/people/12345678901234567890123456789012",
                 wayfinder::Method::Post,
-                Match::NotAllowed,
+                Match::NotAllowed(&["GET", "PUT", "DELETE"]),
             ),
         ]
     }
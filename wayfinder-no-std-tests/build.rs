@@ -0,0 +1,55 @@
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use wayfinder::build::{Builder, Job};
+
+fn main() {
+    let cases_path = "../wayfinder-tests/cases";
+    println!("cargo:rerun-if-changed={}", cases_path);
+
+    let mut cases = std::fs::read_dir(cases_path)
+        .expect("cases path")
+        .map(|entry| {
+            let entry = entry.expect("case entry");
+            entry.file_name().into_string().expect("file name")
+        })
+        .collect::<Vec<_>>();
+    cases.sort();
+
+    let mut builder = Builder::from_env();
+    for case in cases.iter() {
+        let input = format!("{}/{}/routes.routes", cases_path, case);
+        let output = format!("{}.rs", case);
+        builder = builder.job(Job::from_file(input, output, case).no_std(true));
+    }
+
+    let example = format!("{}/example/routes.routes", cases_path);
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let mut out_file = File::create(out_path.join("cases.rs")).expect("create file");
+    for case in cases.iter() {
+        writeln!(
+            out_file,
+            "include!(concat!(env!(\"OUT_DIR\"), \"/{}.rs\"));",
+            case
+        )
+        .expect("write cases");
+    }
+
+    builder
+        .job(
+            Job::from_file(&example, "async_normalized.rs", "async_normalized")
+                .no_std(true)
+                .async_handlers(true)
+                .collapse_slashes(true)
+                .resolve_dot_segments(true)
+                .ignore_case(true),
+        )
+        .job(
+            Job::from_file(&example, "borrowed.rs", "borrowed")
+                .no_std(true)
+                .borrowed(true)
+                .handlers(true),
+        )
+        .build();
+}
@@ -0,0 +1,60 @@
+//! The route matchers for every test case, generated for `core` and
+//! `alloc` only, so this crate checks they build without `std`.
+
+#![no_std]
+
+extern crate alloc;
+
+include!(concat!(env!("OUT_DIR"), "/cases.rs"));
+include!(concat!(env!("OUT_DIR"), "/async_normalized.rs"));
+include!(concat!(env!("OUT_DIR"), "/borrowed.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::borrow::Cow;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_match_route() {
+        use example::{people, Match, Route, ToPath};
+
+        let id = uuid::Uuid::nil();
+        let path = Route::from(people::Edit { id, lang: None }).to_path();
+        match example::match_route(&path, b"GET") {
+            Ok(Match::Route(Route::People(people::Route::Edit(edit)))) => {
+                assert_eq!(edit.id, id)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let error = example::match_route("/people/not-a-uuid", b"GET").unwrap_err();
+        assert_eq!(error.param(), "id");
+        assert!(error.to_string().contains("not-a-uuid"));
+    }
+
+    #[test]
+    fn test_normalized() {
+        use async_normalized::{people, Match, Route};
+
+        assert_eq!(
+            async_normalized::match_route("//People/./new", b"GET").unwrap(),
+            Match::Route(Route::People(people::Route::New(people::New {
+                lang: None
+            })))
+        );
+    }
+
+    #[test]
+    fn test_borrowed() {
+        use borrowed::{files, Match, Route};
+
+        match borrowed::match_request("/files/a%20b?version=1", b"GET") {
+            Ok(Match::Route(Route::Files(files::Route::Show(show)))) => {
+                assert!(matches!(show.file, Cow::Borrowed("a%20b")));
+                assert!(matches!(show.version, Some(Cow::Borrowed("1"))));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
@@ -2,8 +2,75 @@ use itertools::process_results;
 use std::env;
 use std::fs::{DirEntry, File};
 use std::path::PathBuf;
+use wayfinder::build::{Builder, Job};
+use wayfinder::TrailingSlash;
 
 fn main() {
+    println!("cargo:rerun-if-changed=cases");
+
+    Builder::from_env()
+        .format(true)
+        .job(
+            Job::from_file("cases/example/routes.routes", "public.rs", "public_api")
+                .http(true)
+                .tower(true)
+                .async_handlers(true),
+        )
+        .job(
+            Job::from_file("cases/nesting/routes.routes", "admin.rs", "admin_api")
+                .visibility("pub(crate)")
+                .handlers(true),
+        )
+        .job(Job::from_file(
+            "cases/commons/routes.routes",
+            "commons.rs",
+            "commons_api",
+        ))
+        .job(
+            Job::from_file("cases/slashes/routes.routes", "slashes.rs", "slashes_api")
+                .trailing_slash(TrailingSlash::Strict),
+        )
+        .job(
+            Job::from_file(
+                "cases/siblings/routes.routes",
+                "siblings.rs",
+                "siblings_api",
+            )
+            .async_handlers(true),
+        )
+        .job(Job::from_file(
+            "cases/locals/routes.routes",
+            "locals.rs",
+            "locals_api",
+        ))
+        .job(
+            Job::from_file(
+                "cases/example/routes.routes",
+                "normalized.rs",
+                "normalized_api",
+            )
+            .collapse_slashes(true)
+            .resolve_dot_segments(true)
+            .ignore_case(true),
+        )
+        .job(
+            Job::from_file("cases/example/routes.routes", "borrowed.rs", "borrowed_api")
+                .borrowed(true)
+                .http(true)
+                .async_handlers(true),
+        )
+        .job(
+            Job::from_file(
+                "cases/siblings/routes.routes",
+                "borrowed_siblings.rs",
+                "borrowed_siblings_api",
+            )
+            .borrowed(true)
+            .handlers(true),
+        )
+        .job(Job::from_file("cases/example/routes.routes", "no_std.rs", "no_std_api").no_std(true))
+        .build();
+
     let root_path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
     let cases_path = root_path.join("cases");
 
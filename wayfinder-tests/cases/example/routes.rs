@@ -5,6 +5,18 @@ pub mod routes {
     //! Of note is the function [`match_route`] as well as request structs
     //! specific to each named resource.
     //!
+    //! Parameters are percent-decoded before they're parsed, and `+` in
+    //! a query string is a space.  Parameters declared `raw` are parsed
+    //! from the text exactly as it appears in the request instead.
+    //! Static path segments are matched literally, without decoding, so
+    //! `/peop%6Ce` doesn't match `/people`, and `%2F` is never a
+    //! separator.  Links are encoded the same way: parameters are
+    //! percent-encoded unless they're `raw`.
+    //!
+    //! A path parameter that isn't valid UTF-8 once it's decoded doesn't
+    //! match, since a sibling route might.  A query parameter that isn't
+    //! is reported as `Error::NotUtf8`, as the path has already matched.
+    //!
     //! Route configuration:
     //!
     //! ```ignore
@@ -27,6 +39,7 @@ pub mod routes {
     //!   books
     //!     GET Books::Index
     //!     POST Books::Create
+    //!       <title: String>
     //!     new
     //!       GET Books::New
     //!     {id: Uuid}
@@ -35,6 +48,10 @@ pub mod routes {
     //!       DELETE Books::Destroy
     //!       edit
     //!         GET Books::Edit
+    //!   files
+    //!     {file: raw String}
+    //!       GET Files::Show
+    //!         [version: raw String]
     //! ```
     //!
     //! [`match_route`]: fn.match_route.html
@@ -53,13 +70,18 @@ pub mod routes {
         #[derive(Debug, PartialEq, Eq)]
         pub struct Create {
             pub lang: Option<String>,
+            /// Read from a form body by the async `dispatch`, and `None`
+            /// when the route is matched otherwise.
+            pub title: Option<String>,
         }
 
-        impl Create {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Create { ref lang, } = self;
-                format!("/books")
+        impl super::ToPath for Create {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Create { lang: ref param_lang, .. } = self;
+                w.write_str("/books")?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -70,11 +92,14 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Destroy {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Destroy { ref id, ref lang, } = self;
-                format!("/books/{}", id)
+        impl super::ToPath for Destroy {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Destroy { id: ref param_id, lang: ref param_lang, } = self;
+                w.write_str("/books/")?;
+                super::write_encoded(w, param_id)?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -85,11 +110,15 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Edit {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Edit { ref id, ref lang, } = self;
-                format!("/books/{}/edit", id)
+        impl super::ToPath for Edit {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Edit { id: ref param_id, lang: ref param_lang, } = self;
+                w.write_str("/books/")?;
+                super::write_encoded(w, param_id)?;
+                w.write_str("/edit")?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -99,11 +128,13 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Index {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Index { ref lang, } = self;
-                format!("/books")
+        impl super::ToPath for Index {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Index { lang: ref param_lang, } = self;
+                w.write_str("/books")?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -113,11 +144,13 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl New {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let New { ref lang, } = self;
-                format!("/books/new")
+        impl super::ToPath for New {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let New { lang: ref param_lang, } = self;
+                w.write_str("/books/new")?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -128,11 +161,14 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Show {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Show { ref id, ref lang, } = self;
-                format!("/books/{}", id)
+        impl super::ToPath for Show {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Show { id: ref param_id, lang: ref param_lang, } = self;
+                w.write_str("/books/")?;
+                super::write_encoded(w, param_id)?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -143,11 +179,14 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Update {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Update { ref id, ref lang, } = self;
-                format!("/books/{}", id)
+        impl super::ToPath for Update {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Update { id: ref param_id, lang: ref param_lang, } = self;
+                w.write_str("/books/")?;
+                super::write_encoded(w, param_id)?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -163,20 +202,105 @@ pub mod routes {
             Update(Update),
         }
 
-        impl Route {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
+        impl super::ToPath for Route {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
                 match self {
-                    Route::Create(ref route) => route.to_path(),
-                    Route::Destroy(ref route) => route.to_path(),
-                    Route::Edit(ref route) => route.to_path(),
-                    Route::Index(ref route) => route.to_path(),
-                    Route::New(ref route) => route.to_path(),
-                    Route::Show(ref route) => route.to_path(),
-                    Route::Update(ref route) => route.to_path(),
+                    Route::Create(ref route) => super::ToPath::write_path(route, w),
+                    Route::Destroy(ref route) => super::ToPath::write_path(route, w),
+                    Route::Edit(ref route) => super::ToPath::write_path(route, w),
+                    Route::Index(ref route) => super::ToPath::write_path(route, w),
+                    Route::New(ref route) => super::ToPath::write_path(route, w),
+                    Route::Show(ref route) => super::ToPath::write_path(route, w),
+                    Route::Update(ref route) => super::ToPath::write_path(route, w),
                 }
             }
         }
+
+        impl From<Create> for Route {
+            fn from(route: Create) -> Route {
+                Route::Create(route)
+            }
+        }
+
+        impl From<Destroy> for Route {
+            fn from(route: Destroy) -> Route {
+                Route::Destroy(route)
+            }
+        }
+
+        impl From<Edit> for Route {
+            fn from(route: Edit) -> Route {
+                Route::Edit(route)
+            }
+        }
+
+        impl From<Index> for Route {
+            fn from(route: Index) -> Route {
+                Route::Index(route)
+            }
+        }
+
+        impl From<New> for Route {
+            fn from(route: New) -> Route {
+                Route::New(route)
+            }
+        }
+
+        impl From<Show> for Route {
+            fn from(route: Show) -> Route {
+                Route::Show(route)
+            }
+        }
+
+        impl From<Update> for Route {
+            fn from(route: Update) -> Route {
+                Route::Update(route)
+            }
+        }
+    }
+
+    pub mod files {
+        use uuid::Uuid;
+
+        /// Renders for `GET /files/{file}`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Show {
+            pub file: String,
+            pub lang: Option<String>,
+            pub version: Option<String>,
+        }
+
+        impl super::ToPath for Show {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Show { file: ref param_file, lang: ref param_lang, version: ref param_version, } = self;
+                w.write_str("/files/")?;
+                write!(w, "{}", param_file)?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                super::write_query(w, &mut first, "version", param_version, true)?;
+                Ok(())
+            }
+        }
+
+        /// Parameters for requests to the files controller.
+        #[derive(Debug, PartialEq, Eq)]
+        pub enum Route {
+            Show(Show),
+        }
+
+        impl super::ToPath for Route {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                match self {
+                    Route::Show(ref route) => super::ToPath::write_path(route, w),
+                }
+            }
+        }
+
+        impl From<Show> for Route {
+            fn from(route: Show) -> Route {
+                Route::Show(route)
+            }
+        }
     }
 
     pub mod people {
@@ -188,11 +312,13 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Create {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Create { ref lang, } = self;
-                format!("/people")
+        impl super::ToPath for Create {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Create { lang: ref param_lang, } = self;
+                w.write_str("/people")?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -203,11 +329,14 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Destroy {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Destroy { ref id, ref lang, } = self;
-                format!("/people/{}", id)
+        impl super::ToPath for Destroy {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Destroy { id: ref param_id, lang: ref param_lang, } = self;
+                w.write_str("/people/")?;
+                super::write_encoded(w, param_id)?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -218,11 +347,15 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Edit {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Edit { ref id, ref lang, } = self;
-                format!("/people/{}/edit", id)
+        impl super::ToPath for Edit {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Edit { id: ref param_id, lang: ref param_lang, } = self;
+                w.write_str("/people/")?;
+                super::write_encoded(w, param_id)?;
+                w.write_str("/edit")?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -232,11 +365,13 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Index {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Index { ref lang, } = self;
-                format!("/people")
+        impl super::ToPath for Index {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Index { lang: ref param_lang, } = self;
+                w.write_str("/people")?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -246,11 +381,13 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl New {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let New { ref lang, } = self;
-                format!("/people/new")
+        impl super::ToPath for New {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let New { lang: ref param_lang, } = self;
+                w.write_str("/people/new")?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -261,11 +398,14 @@ pub mod routes {
             pub lang: Option<String>,
         }
 
-        impl Show {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Show { ref id, ref lang, } = self;
-                format!("/people/{}", id)
+        impl super::ToPath for Show {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Show { id: ref param_id, lang: ref param_lang, } = self;
+                w.write_str("/people/")?;
+                super::write_encoded(w, param_id)?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                Ok(())
             }
         }
 
@@ -277,11 +417,15 @@ pub mod routes {
             pub name: Option<String>,
         }
 
-        impl Update {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
-                let Update { ref id, ref lang, ref name, } = self;
-                format!("/people/{}", id)
+        impl super::ToPath for Update {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Update { id: ref param_id, lang: ref param_lang, name: ref param_name, } = self;
+                w.write_str("/people/")?;
+                super::write_encoded(w, param_id)?;
+                let mut first = true;
+                super::write_query(w, &mut first, "lang", param_lang, false)?;
+                super::write_query(w, &mut first, "name", param_name, false)?;
+                Ok(())
             }
         }
 
@@ -297,43 +441,294 @@ pub mod routes {
             Update(Update),
         }
 
-        impl Route {
-            /// Make a path to this route with the given parameters.
-            pub fn to_path(&self) -> String {
+        impl super::ToPath for Route {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
                 match self {
-                    Route::Create(ref route) => route.to_path(),
-                    Route::Destroy(ref route) => route.to_path(),
-                    Route::Edit(ref route) => route.to_path(),
-                    Route::Index(ref route) => route.to_path(),
-                    Route::New(ref route) => route.to_path(),
-                    Route::Show(ref route) => route.to_path(),
-                    Route::Update(ref route) => route.to_path(),
+                    Route::Create(ref route) => super::ToPath::write_path(route, w),
+                    Route::Destroy(ref route) => super::ToPath::write_path(route, w),
+                    Route::Edit(ref route) => super::ToPath::write_path(route, w),
+                    Route::Index(ref route) => super::ToPath::write_path(route, w),
+                    Route::New(ref route) => super::ToPath::write_path(route, w),
+                    Route::Show(ref route) => super::ToPath::write_path(route, w),
+                    Route::Update(ref route) => super::ToPath::write_path(route, w),
                 }
             }
         }
+
+        impl From<Create> for Route {
+            fn from(route: Create) -> Route {
+                Route::Create(route)
+            }
+        }
+
+        impl From<Destroy> for Route {
+            fn from(route: Destroy) -> Route {
+                Route::Destroy(route)
+            }
+        }
+
+        impl From<Edit> for Route {
+            fn from(route: Edit) -> Route {
+                Route::Edit(route)
+            }
+        }
+
+        impl From<Index> for Route {
+            fn from(route: Index) -> Route {
+                Route::Index(route)
+            }
+        }
+
+        impl From<New> for Route {
+            fn from(route: New) -> Route {
+                Route::New(route)
+            }
+        }
+
+        impl From<Show> for Route {
+            fn from(route: Show) -> Route {
+                Route::Show(route)
+            }
+        }
+
+        impl From<Update> for Route {
+            fn from(route: Update) -> Route {
+                Route::Update(route)
+            }
+        }
     }
 
     /// An active route in the application -- match against this.
     #[derive(Debug, PartialEq, Eq)]
     pub enum Route {
         Books(books::Route),
+        Files(files::Route),
         People(people::Route),
     }
 
-    impl Route {
-        /// Make a path to this route with the given parameters.
-        pub fn to_path(&self) -> String {
+    impl ToPath for Route {
+        fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
             match self {
-                Route::Books(ref route) => route.to_path(),
-                Route::People(ref route) => route.to_path(),
+                Route::Books(ref route) => ToPath::write_path(route, w),
+                Route::Files(ref route) => ToPath::write_path(route, w),
+                Route::People(ref route) => ToPath::write_path(route, w),
             }
         }
     }
 
+    impl From<books::Route> for Route {
+        fn from(route: books::Route) -> Route {
+            Route::Books(route)
+        }
+    }
+
+    impl From<books::Create> for Route {
+        fn from(route: books::Create) -> Route {
+            Route::Books(route.into())
+        }
+    }
+
+    impl From<books::Destroy> for Route {
+        fn from(route: books::Destroy) -> Route {
+            Route::Books(route.into())
+        }
+    }
+
+    impl From<books::Edit> for Route {
+        fn from(route: books::Edit) -> Route {
+            Route::Books(route.into())
+        }
+    }
+
+    impl From<books::Index> for Route {
+        fn from(route: books::Index) -> Route {
+            Route::Books(route.into())
+        }
+    }
+
+    impl From<books::New> for Route {
+        fn from(route: books::New) -> Route {
+            Route::Books(route.into())
+        }
+    }
+
+    impl From<books::Show> for Route {
+        fn from(route: books::Show) -> Route {
+            Route::Books(route.into())
+        }
+    }
+
+    impl From<books::Update> for Route {
+        fn from(route: books::Update) -> Route {
+            Route::Books(route.into())
+        }
+    }
+
+    impl From<files::Route> for Route {
+        fn from(route: files::Route) -> Route {
+            Route::Files(route)
+        }
+    }
+
+    impl From<files::Show> for Route {
+        fn from(route: files::Show) -> Route {
+            Route::Files(route.into())
+        }
+    }
+
+    impl From<people::Route> for Route {
+        fn from(route: people::Route) -> Route {
+            Route::People(route)
+        }
+    }
+
+    impl From<people::Create> for Route {
+        fn from(route: people::Create) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    impl From<people::Destroy> for Route {
+        fn from(route: people::Destroy) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    impl From<people::Edit> for Route {
+        fn from(route: people::Edit) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    impl From<people::Index> for Route {
+        fn from(route: people::Index) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    impl From<people::New> for Route {
+        fn from(route: people::New) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    impl From<people::Show> for Route {
+        fn from(route: people::Show) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    impl From<people::Update> for Route {
+        fn from(route: people::Update) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    /// Routes and request structs that can be rendered as a path.
+    pub trait ToPath {
+        /// Write the path to this route, with its parameters filled in.
+        fn write_path<W: fmt::Write>(&self, w: &mut W) -> fmt::Result;
+
+        /// Make a path to this route with the given parameters.
+        fn to_path(&self) -> String {
+            let mut path = String::new();
+            self.write_path(&mut path)
+                .expect("writing to a String can't fail");
+            path
+        }
+    }
+
+    /// Makes links to routes.
+    ///
+    /// Configure it once with the base URL of the app and the prefix
+    /// it's mounted at, if any, then use it to link to any route.
+    ///
+    /// ```ignore
+    /// let urls = Urls::new()
+    ///     .base("https://example.com")
+    ///     .mount("/app");
+    ///
+    /// urls.link_to(&route, None); // /app/people
+    /// urls.url_for(&route, Some("top")); // https://example.com/app/people#top
+    /// ```
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct Urls {
+        base: String,
+        mount: String,
+    }
+
+    impl Urls {
+        /// Links with no base URL or mount prefix, that is, just paths.
+        pub fn new() -> Urls {
+            Urls::default()
+        }
+
+        /// Set the scheme and host for absolute URLs, such as
+        /// `https://example.com`.
+        pub fn base<S: Into<String>>(mut self, base: S) -> Urls {
+            let mut base = base.into();
+            while base.ends_with('/') {
+                base.pop();
+            }
+            self.base = base;
+            self
+        }
+
+        /// Set the prefix the app is mounted at, such as `/app`.  It's
+        /// added to the start of every link.
+        pub fn mount<S: AsRef<str>>(mut self, prefix: S) -> Urls {
+            let prefix = prefix.as_ref().trim_matches('/');
+            self.mount = if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("/{}", prefix)
+            };
+            self
+        }
+
+        /// Write a link to the route, relative to the host, with an
+        /// optional fragment.
+        pub fn write_link<T: ToPath, W: fmt::Write>(
+            &self,
+            w: &mut W,
+            route: &T,
+            fragment: Option<&str>,
+        ) -> fmt::Result {
+            w.write_str(&self.mount)?;
+            route.write_path(w)?;
+            if let Some(fragment) = fragment {
+                w.write_char('#')?;
+                write_encoded(w, &fragment)?;
+            }
+            Ok(())
+        }
+
+        /// Make a link to the route, relative to the host, such as
+        /// `/app/people/new`.
+        pub fn link_to<T: ToPath>(&self, route: &T, fragment: Option<&str>) -> String {
+            let mut link = String::new();
+            self.write_link(&mut link, route, fragment)
+                .expect("writing to a String can't fail");
+            link
+        }
+
+        /// Make an absolute URL for the route, such as
+        /// `https://example.com/app/people/new`.  Without a base URL
+        /// this is the same as `link_to`.
+        pub fn url_for<T: ToPath>(&self, route: &T, fragment: Option<&str>) -> String {
+            let mut url = self.base.clone();
+            self.write_link(&mut url, route, fragment)
+                .expect("writing to a String can't fail");
+            url
+        }
+    }
+
     #[derive(PartialEq, Eq)]
     pub enum Match<T> {
         NotFound,
-        NotAllowed,
+        /// The path matches, but not the method.  Holds the methods
+        /// the route does allow.
+        NotAllowed(&'static [&'static str]),
         Route(T),
         Redirect(T),
     }
@@ -343,24 +738,123 @@ pub mod routes {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             match self {
                 Match::NotFound => write!(f, "Match::NotFound"),
-                Match::NotAllowed => write!(f, "Match::NotAllowed"),
+                Match::NotAllowed(m) => write!(f, "Match::NotAllowed({:?})", m),
                 Match::Route(t) => write!(f, "Match::Route({:?})", t),
                 Match::Redirect(t) => write!(f, "Match::Redirect({:?})", t),
             }
         }
     }
 
-    pub struct Error {
-        param: String,
-        what: Box<dyn fmt::Debug>,
+    /// Which part of the request a parameter came from.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ParamKind {
+        Path,
+        Query,
+        Body,
     }
 
-    impl fmt::Debug for Error {
+    impl fmt::Display for ParamKind {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            f.debug_struct("wayfinder::Error")
-                .field("param", &self.param)
-                .field("what", &self.what)
-                .finish()
+            f.write_str(match self {
+                ParamKind::Path => "path",
+                ParamKind::Query => "query",
+                ParamKind::Body => "body",
+            })
+        }
+    }
+
+    /// A parameter that failed to parse.
+    ///
+    /// There is a variant for each parameter, holding the error from
+    /// its type's `FromStr` implementation, the text that failed to
+    /// parse, and where in the request it came from.  A query or body
+    /// parameter that isn't valid UTF-8 once it's decoded is `NotUtf8`
+    /// instead, while a path parameter that isn't is not a match.
+    #[derive(Debug)]
+    pub enum Error {
+        /// The `lang` parameter is not a valid `String`.
+        Lang {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `id` parameter is not a valid `Uuid`.
+        Id {
+            kind: ParamKind,
+            text: String,
+            error: <Uuid as std::str::FromStr>::Err,
+        },
+        /// The `name` parameter is not a valid `String`.
+        Name {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `title` parameter is not a valid `String`.
+        Title {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `file` parameter is not a valid `String`.
+        File {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `version` parameter is not a valid `String`.
+        Version {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The query or body parameter named by `param` is not valid
+        /// UTF-8.  The text has each invalid sequence replaced with `U+FFFD`.
+        NotUtf8 {
+            param: &'static str,
+            kind: ParamKind,
+            text: String,
+        },
+    }
+
+    impl Error {
+        /// The name of the parameter that failed to parse.
+        pub fn param(&self) -> &'static str {
+            match *self {
+                Error::Lang { .. } => "lang",
+                Error::Id { .. } => "id",
+                Error::Name { .. } => "name",
+                Error::Title { .. } => "title",
+                Error::File { .. } => "file",
+                Error::Version { .. } => "version",
+                Error::NotUtf8 { param, .. } => param,
+            }
+        }
+
+        /// Whether the parameter was part of the path, the query or the body.
+        pub fn kind(&self) -> ParamKind {
+            match *self {
+                Error::Lang { kind, .. } => kind,
+                Error::Id { kind, .. } => kind,
+                Error::Name { kind, .. } => kind,
+                Error::Title { kind, .. } => kind,
+                Error::File { kind, .. } => kind,
+                Error::Version { kind, .. } => kind,
+                Error::NotUtf8 { kind, .. } => kind,
+            }
+        }
+
+        /// The text that failed to parse.
+        pub fn text(&self) -> &str {
+            match *self {
+                Error::Lang { ref text, .. } => text,
+                Error::Id { ref text, .. } => text,
+                Error::Name { ref text, .. } => text,
+                Error::Title { ref text, .. } => text,
+                Error::File { ref text, .. } => text,
+                Error::Version { ref text, .. } => text,
+                Error::NotUtf8 { ref text, .. } => text,
+            }
         }
     }
 
@@ -368,22 +862,501 @@ pub mod routes {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(
                 f,
-                "Error parsing '{}' parameter {:?}",
-                self.param, self.what
-            )
+                "Error parsing {} parameter '{}' from {:?}: ",
+                self.kind(),
+                self.param(),
+                self.text()
+            )?;
+            match *self {
+                Error::Lang { ref error, .. } => write!(f, "{:?}", error),
+                Error::Id { ref error, .. } => write!(f, "{:?}", error),
+                Error::Name { ref error, .. } => write!(f, "{:?}", error),
+                Error::Title { ref error, .. } => write!(f, "{:?}", error),
+                Error::File { ref error, .. } => write!(f, "{:?}", error),
+                Error::Version { ref error, .. } => write!(f, "{:?}", error),
+                Error::NotUtf8 { .. } => f.write_str("not valid UTF-8"),
+            }
         }
     }
 
     impl std::error::Error for Error {}
 
-    impl Error {
-        pub fn fail<S: AsRef<str>, T: fmt::Debug + 'static>(param: S, what: T) -> Error {
-            Error {
-                param: param.as_ref().to_string(),
-                what: Box::new(what),
+    /// Percent-encodes everything written to it except unreserved
+    /// characters, so it's safe in a path segment or query.
+    struct Encode<'a, W: fmt::Write>(&'a mut W);
+
+    impl<'a, W: fmt::Write> fmt::Write for Encode<'a, W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for &b in s.as_bytes() {
+                if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                    self.0.write_char(b as char)?;
+                } else {
+                    write!(self.0, "%{:02X}", b)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn write_encoded<W: fmt::Write, T: fmt::Display>(w: &mut W, value: &T) -> fmt::Result {
+        use std::fmt::Write;
+        write!(Encode(w), "{}", value)
+    }
+
+    fn write_query<W: fmt::Write, T: fmt::Display>(
+        w: &mut W,
+        first: &mut bool,
+        name: &str,
+        value: &Option<T>,
+        raw: bool,
+    ) -> fmt::Result {
+        if let Some(ref value) = *value {
+            w.write_char(if *first { '?' } else { '&' })?;
+            *first = false;
+            w.write_str(name)?;
+            w.write_char('=')?;
+            if raw {
+                write!(w, "{}", value)?;
+            } else {
+                write_encoded(w, value)?;
             }
         }
+        Ok(())
     }
+
+    fn from_hex(b: u8) -> Option<u8> {
+        match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' => Some(b - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    /// Decode percent-escapes, and in a query string, `+` as a space.
+    /// Invalid escapes are left as they are.  Only allocates if there
+    /// is something to decode.
+    fn decode(bytes: &[u8], is_query: bool) -> std::borrow::Cow<'_, [u8]> {
+        if !bytes.iter().any(|&b| b == b'%' || (is_query && b == b'+')) {
+            return std::borrow::Cow::Borrowed(bytes);
+        }
+
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'%' if i + 2 < bytes.len() => {
+                    match (from_hex(bytes[i + 1]), from_hex(bytes[i + 2])) {
+                        (Some(hi), Some(lo)) => {
+                            decoded.push(hi * 16 + lo);
+                            i += 3;
+                            continue;
+                        }
+                        _ => decoded.push(b'%'),
+                    }
+                }
+                b'+' if is_query => decoded.push(b' '),
+                b => decoded.push(b),
+            }
+            i += 1;
+        }
+        std::borrow::Cow::Owned(decoded)
+    }
+
+    /// Parse a path parameter, decoding it unless it's raw.  If it isn't
+    /// valid UTF-8 it's not a match, otherwise the error has the text.
+    fn parse_path<T: std::str::FromStr>(
+        text: &[u8],
+        raw: bool,
+    ) -> Option<Result<T, (String, T::Err)>> {
+        let text = if raw {
+            std::borrow::Cow::Borrowed(text)
+        } else {
+            decode(text, false)
+        };
+        let text = std::str::from_utf8(&text).ok()?;
+        Some(text.parse().map_err(|e| (text.to_string(), e)))
+    }
+
+    /// The error for a query or body parameter that isn't valid UTF-8.
+    fn not_utf8(param: &'static str, kind: ParamKind, text: &[u8]) -> Error {
+        Error::NotUtf8 {
+            param,
+            kind,
+            text: String::from_utf8_lossy(text).into_owned(),
+        }
+    }
+
+    /// Find and parse the first value for the named query parameter,
+    /// decoding it unless it's raw.  If it isn't valid UTF-8, or doesn't
+    /// parse, the error has the text.
+    fn parse_query<T: std::str::FromStr>(
+        query: &[u8],
+        name: &'static str,
+        kind: ParamKind,
+        raw: bool,
+        error: fn(ParamKind, String, T::Err) -> Error,
+    ) -> Result<Option<T>, Error> {
+        if query.is_empty() {
+            return Ok(None);
+        }
+
+        for pair in query.split(|&b| b == b'&') {
+            let (key, value) = match pair.iter().position(|&b| b == b'=') {
+                Some(i) => (&pair[..i], &pair[i + 1..]),
+                None => (pair, &b""[..]),
+            };
+            if &*decode(key, true) != name.as_bytes() {
+                continue;
+            }
+
+            let value = if raw {
+                std::borrow::Cow::Borrowed(value)
+            } else {
+                decode(value, true)
+            };
+            let text = match std::str::from_utf8(&value) {
+                Ok(text) => text,
+                Err(_) => return Err(not_utf8(name, kind, &value)),
+            };
+            return text
+                .parse()
+                .map(Some)
+                .map_err(|e| error(kind, text.to_string(), e));
+        }
+
+        Ok(None)
+    }
+
+    /// A problem finding a route by name or filling in its parameters.
+    #[derive(Debug)]
+    pub enum UrlError {
+        /// There is no route with this name.
+        UnknownRoute(String),
+        /// The route needs a parameter that wasn't given.
+        MissingParam {
+            route: &'static str,
+            param: &'static str,
+        },
+        /// A parameter was given that the route doesn't take.
+        UnknownParam {
+            route: &'static str,
+            param: String,
+        },
+        /// A parameter was given that failed to parse.
+        Param(Error),
+    }
+
+    impl fmt::Display for UrlError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                UrlError::UnknownRoute(ref name) => write!(f, "Unknown route '{}'", name),
+                UrlError::MissingParam { route, param } => {
+                    write!(f, "Missing parameter '{}' for route '{}'", param, route)
+                }
+                UrlError::UnknownParam { route, ref param } => {
+                    write!(f, "Unknown parameter '{}' for route '{}'", param, route)
+                }
+                UrlError::Param(ref error) => write!(f, "{}", error),
+            }
+        }
+    }
+
+    impl std::error::Error for UrlError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match *self {
+                UrlError::Param(ref error) => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    /// Find the action named `name` and fill it in from `params`.
+    ///
+    /// Actions are named by their module path and action name in
+    /// snake case, separated by dots, such as `people.show`.  Every
+    /// path parameter of the action must be given, query parameters are
+    /// optional, and anything else is an error.  Body parameters aren't
+    /// part of a URL, so they're left `None`.
+    pub fn route_for(name: &str, params: &[(&str, &str)]) -> Result<Route, UrlError> {
+        match name {
+            "books.create" => {
+                check_params("books.create", params, &["lang"])?;
+                let action = books::Create {
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                    title: None,
+                };
+                Ok(action.into())
+            }
+            "books.destroy" => {
+                check_params("books.destroy", params, &["id", "lang"])?;
+                let action = books::Destroy {
+                    id: required_param("books.destroy", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "books.edit" => {
+                check_params("books.edit", params, &["id", "lang"])?;
+                let action = books::Edit {
+                    id: required_param("books.edit", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "books.index" => {
+                check_params("books.index", params, &["lang"])?;
+                let action = books::Index {
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "books.new" => {
+                check_params("books.new", params, &["lang"])?;
+                let action = books::New {
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "books.show" => {
+                check_params("books.show", params, &["id", "lang"])?;
+                let action = books::Show {
+                    id: required_param("books.show", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "books.update" => {
+                check_params("books.update", params, &["id", "lang"])?;
+                let action = books::Update {
+                    id: required_param("books.update", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "files.show" => {
+                check_params("files.show", params, &["file", "lang", "version"])?;
+                let action = files::Show {
+                    file: required_param("files.show", params, "file", |text, error| Error::File {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                    version: optional_param(params, "version", |text, error| Error::Version {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "people.create" => {
+                check_params("people.create", params, &["lang"])?;
+                let action = people::Create {
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "people.destroy" => {
+                check_params("people.destroy", params, &["id", "lang"])?;
+                let action = people::Destroy {
+                    id: required_param("people.destroy", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "people.edit" => {
+                check_params("people.edit", params, &["id", "lang"])?;
+                let action = people::Edit {
+                    id: required_param("people.edit", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "people.index" => {
+                check_params("people.index", params, &["lang"])?;
+                let action = people::Index {
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "people.new" => {
+                check_params("people.new", params, &["lang"])?;
+                let action = people::New {
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "people.show" => {
+                check_params("people.show", params, &["id", "lang"])?;
+                let action = people::Show {
+                    id: required_param("people.show", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "people.update" => {
+                check_params("people.update", params, &["id", "lang", "name"])?;
+                let action = people::Update {
+                    id: required_param("people.update", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                    name: optional_param(params, "name", |text, error| Error::Name {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            _ => Err(UrlError::UnknownRoute(name.to_string())),
+        }
+    }
+
+    /// Make a path to the action named `name` with the given parameters,
+    /// for when the route isn't known until runtime, such as in a
+    /// template.  See [`route_for`] for the details.
+    ///
+    /// [`route_for`]: fn.route_for.html
+    pub fn url_for(name: &str, params: &[(&str, &str)]) -> Result<String, UrlError> {
+        route_for(name, params).map(|route| route.to_path())
+    }
+
+    fn check_params(
+        route: &'static str,
+        params: &[(&str, &str)],
+        known: &[&str],
+    ) -> Result<(), UrlError> {
+        for &(param, _) in params {
+            if !known.contains(&param) {
+                return Err(UrlError::UnknownParam {
+                    route,
+                    param: param.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn optional_param<T: std::str::FromStr>(
+        params: &[(&str, &str)],
+        name: &str,
+        error: fn(String, T::Err) -> Error,
+    ) -> Result<Option<T>, UrlError> {
+        match params.iter().find(|&&(param, _)| param == name) {
+            None => Ok(None),
+            Some(&(_, text)) => text
+                .parse()
+                .map(Some)
+                .map_err(|e| error(text.to_string(), e))
+                .map_err(UrlError::Param),
+        }
+    }
+
+    fn required_param<T: std::str::FromStr>(
+        route: &'static str,
+        params: &[(&str, &str)],
+        name: &'static str,
+        error: fn(String, T::Err) -> Error,
+    ) -> Result<T, UrlError> {
+        optional_param(params, name, error)?.ok_or(UrlError::MissingParam {
+            route,
+            param: name,
+        })
+    }
+
     /// Match a path and method against this router.
     ///
     /// Accepts a byte slice for the request path and HTTP verb.
@@ -398,11 +1371,27 @@ pub mod routes {
     /// If there is no match, this will return `Match::NotFound`
     /// if no path matches (which you could return as `404 Not Found`),
     /// or `Match::NotAllowed` if no method matches (in which case a
-    /// `405 Not Allowed` would be appropriate).
+    /// `405 Not Allowed` would be appropriate, with the methods it holds
+    /// in the `Allow` header).
+    ///
+    /// Static path segments take precedence over a parameter in the
+    /// same place.  If the rest of the path doesn't match after a static
+    /// segment, the parameter is tried instead.  A parameter matches a
+    /// whole segment, which can't be empty.  Parameters side by side are
+    /// tried in the order they're declared, and one that fails to parse
+    /// falls through to the next.
+    ///
+    /// Parameters are only parsed once the whole path matches, so a path
+    /// that doesn't match any route is `NotFound` even if a parameter in
+    /// it is invalid.  One that could fall through to the next is parsed
+    /// before the method is checked, and the rest after.  If the last
+    /// route parameter tried fails to parse, this will return `Err` with
+    /// the underlying parsing error.  Usually you'll want to send back a
+    /// `400 Bad Request` for that.
     ///
-    /// If a route parameter fails to parse correctly, this will return
-    /// `Err` with the underlying parsing error.  Usually you'll want
-    /// to send back a `400 Bad Request` for that.
+    /// Any bytes may be passed without panicking.  A path segment that
+    /// isn't valid UTF-8 once percent-decoded can't be a parameter, so
+    /// it's `Match::NotFound`.
     ///
     /// [`Match`]: enum.Match.html
     /// [`Route`]: enum.Route.html
@@ -410,8 +1399,28 @@ pub mod routes {
         path: P,
         method: M,
     ) -> Result<Match<Route>, Error> {
-        let method = method.as_ref();
-        let path = path.as_ref();
+        match_path(path.as_ref(), b"", method.as_ref())
+    }
+
+    /// Match a request target and method against this router.
+    ///
+    /// The request target is a path with an optional query string, such
+    /// as `/people?lang=en`.  This works like [`match_route`], but query
+    /// parameters are also decoded and parsed from the query string.
+    ///
+    /// [`match_route`]: fn.match_route.html
+    pub fn match_request<T: AsRef<[u8]>, M: AsRef<[u8]>>(
+        target: T,
+        method: M,
+    ) -> Result<Match<Route>, Error> {
+        let target = target.as_ref();
+        match target.iter().position(|&b| b == b'?') {
+            Some(i) => match_path(&target[..i], &target[i + 1..], method.as_ref()),
+            None => match_path(target, b"", method.as_ref()),
+        }
+    }
+
+    fn match_path(path: &[u8], query: &[u8], method: &[u8]) -> Result<Match<Route>, Error> {
         let len = path.len();
         let mut i = if len > 0 && &path[0..1] == b"/" { 1 } else { 0 };
 
@@ -433,12 +1442,21 @@ pub mod routes {
                 if i == len {
                     match method {
                         b"GET" => return Ok(Match::Route(Route::Books(books::Route::Index(books::Index {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
                         })))),
                         b"POST" => return Ok(Match::Route(Route::Books(books::Route::Create(books::Create {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
+                            title: None,
                         })))),
-                        _ => return Ok(Match::NotAllowed),
+                        _ => return Ok(Match::NotAllowed(&["GET", "POST"])),
                     }
                 }
                 match &path[i..i+1] {
@@ -450,72 +1468,136 @@ pub mod routes {
                 if i == len {
                     match method {
                         b"GET" => return Ok(Match::Route(Route::Books(books::Route::Index(books::Index {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
                         })))),
                         b"POST" => return Ok(Match::Route(Route::Books(books::Route::Create(books::Create {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
+                            title: None,
                         })))),
-                        _ => return Ok(Match::NotAllowed),
+                        _ => return Ok(Match::NotAllowed(&["GET", "POST"])),
                     }
                 }
-
                 let start = i;
-
-                if i + 3 <= len {
+                'fallback0: {
+                    if i + 3 > len {
+                        break 'fallback0;
+                    }
                     match &path[i..i+3] {
                         b"new" => {
                             i += 3;
-                            if i == len {
-                                match method {
-                                    b"GET" => return Ok(Match::Route(Route::Books(books::Route::New(books::New {
-                                        lang: None,
-                                    })))),
-                                    _ => return Ok(Match::NotAllowed),
-                                }
-                            }
-                            match &path[i..i+1] {
-                                b"/" => {
-                                    i += 1;
-                                },
-                                _ => return Ok(Match::NotFound),
-                            }
-                            if i == len {
-                                match method {
-                                    b"GET" => return Ok(Match::Route(Route::Books(books::Route::New(books::New {
-                                        lang: None,
-                                    })))),
-                                    _ => return Ok(Match::NotAllowed),
-                                }
-                            }
-                            return Ok(Match::NotFound);
                         },
-                        _ => {},
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => return Ok(Match::Route(Route::Books(books::Route::New(books::New {
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            })))),
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => return Ok(Match::Route(Route::Books(books::Route::New(books::New {
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            })))),
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
                     }
+                    break 'fallback0;
                 }
+                i = start;
 
                 while i < len && &path[i..i+1] != b"/" {
                     i += 1;
                 }
-
-                let text = std::str::from_utf8(&path[start..i]).unwrap();
-                let id = text.parse()
-                    .map_err(|e| Error::fail("id", e))?;
+                if i == start {
+                    return Ok(Match::NotFound);
+                }
+                let param_id = &path[start..i];
 
                 if i == len {
                     match method {
-                        b"GET" => return Ok(Match::Route(Route::Books(books::Route::Show(books::Show {
-                            id,
-                            lang: None,
-                        })))),
-                        b"PUT" => return Ok(Match::Route(Route::Books(books::Route::Update(books::Update {
-                            id,
-                            lang: None,
-                        })))),
-                        b"DELETE" => return Ok(Match::Route(Route::Books(books::Route::Destroy(books::Destroy {
-                            id,
-                            lang: None,
-                        })))),
-                        _ => return Ok(Match::NotAllowed),
+                        b"GET" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Books(books::Route::Show(books::Show {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        b"PUT" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Books(books::Route::Update(books::Update {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        b"DELETE" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Books(books::Route::Destroy(books::Destroy {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET", "PUT", "DELETE"])),
                     }
                 }
                 match &path[i..i+1] {
@@ -526,19 +1608,64 @@ pub mod routes {
                 }
                 if i == len {
                     match method {
-                        b"GET" => return Ok(Match::Route(Route::Books(books::Route::Show(books::Show {
-                            id,
-                            lang: None,
-                        })))),
-                        b"PUT" => return Ok(Match::Route(Route::Books(books::Route::Update(books::Update {
-                            id,
-                            lang: None,
-                        })))),
-                        b"DELETE" => return Ok(Match::Route(Route::Books(books::Route::Destroy(books::Destroy {
-                            id,
-                            lang: None,
-                        })))),
-                        _ => return Ok(Match::NotAllowed),
+                        b"GET" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Books(books::Route::Show(books::Show {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        b"PUT" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Books(books::Route::Update(books::Update {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        b"DELETE" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Books(books::Route::Destroy(books::Destroy {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET", "PUT", "DELETE"])),
                     }
                 }
                 if i + 4 > len {
@@ -552,11 +1679,26 @@ pub mod routes {
                 }
                 if i == len {
                     match method {
-                        b"GET" => return Ok(Match::Route(Route::Books(books::Route::Edit(books::Edit {
-                            id,
-                            lang: None,
-                        })))),
-                        _ => return Ok(Match::NotAllowed),
+                        b"GET" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Books(books::Route::Edit(books::Edit {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET"])),
                     }
                 }
                 match &path[i..i+1] {
@@ -567,11 +1709,122 @@ pub mod routes {
                 }
                 if i == len {
                     match method {
-                        b"GET" => return Ok(Match::Route(Route::Books(books::Route::Edit(books::Edit {
-                            id,
-                            lang: None,
-                        })))),
-                        _ => return Ok(Match::NotAllowed),
+                        b"GET" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Books(books::Route::Edit(books::Edit {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET"])),
+                    }
+                }
+                return Ok(Match::NotFound);
+            },
+            b"f" => {
+                i += 1;
+                if i + 4 > len {
+                    return Ok(Match::NotFound);
+                }
+                match &path[i..i+4] {
+                    b"iles" => {
+                        i += 4;
+                    },
+                    _ => return Ok(Match::NotFound),
+                }
+                if i == len {
+                    return Ok(Match::NotFound);
+                }
+                match &path[i..i+1] {
+                    b"/" => {
+                        i += 1;
+                    },
+                    _ => return Ok(Match::NotFound),
+                }
+                let start = i;
+
+                while i < len && &path[i..i+1] != b"/" {
+                    i += 1;
+                }
+                if i == start {
+                    return Ok(Match::NotFound);
+                }
+                let param_file = &path[start..i];
+
+                if i == len {
+                    match method {
+                        b"GET" => {
+                            let param_file = match parse_path(param_file, true) {
+                                Some(Ok(param_file)) => param_file,
+                                Some(Err((text, error))) => return Err(Error::File {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Files(files::Route::Show(files::Show {
+                                file: param_file,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                                version: parse_query(query, "version", ParamKind::Query, true, |kind, text, error| Error::Version {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET"])),
+                    }
+                }
+                match &path[i..i+1] {
+                    b"/" => {
+                        i += 1;
+                    },
+                    _ => return Ok(Match::NotFound),
+                }
+                if i == len {
+                    match method {
+                        b"GET" => {
+                            let param_file = match parse_path(param_file, true) {
+                                Some(Ok(param_file)) => param_file,
+                                Some(Err((text, error))) => return Err(Error::File {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::Files(files::Route::Show(files::Show {
+                                file: param_file,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                                version: parse_query(query, "version", ParamKind::Query, true, |kind, text, error| Error::Version {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET"])),
                     }
                 }
                 return Ok(Match::NotFound);
@@ -590,12 +1843,20 @@ pub mod routes {
                 if i == len {
                     match method {
                         b"GET" => return Ok(Match::Route(Route::People(people::Route::Index(people::Index {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
                         })))),
                         b"POST" => return Ok(Match::Route(Route::People(people::Route::Create(people::Create {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
                         })))),
-                        _ => return Ok(Match::NotAllowed),
+                        _ => return Ok(Match::NotAllowed(&["GET", "POST"])),
                     }
                 }
                 match &path[i..i+1] {
@@ -607,73 +1868,140 @@ pub mod routes {
                 if i == len {
                     match method {
                         b"GET" => return Ok(Match::Route(Route::People(people::Route::Index(people::Index {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
                         })))),
                         b"POST" => return Ok(Match::Route(Route::People(people::Route::Create(people::Create {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
                         })))),
-                        _ => return Ok(Match::NotAllowed),
+                        _ => return Ok(Match::NotAllowed(&["GET", "POST"])),
                     }
                 }
-
                 let start = i;
-
-                if i + 3 <= len {
+                'fallback0: {
+                    if i + 3 > len {
+                        break 'fallback0;
+                    }
                     match &path[i..i+3] {
                         b"new" => {
                             i += 3;
-                            if i == len {
-                                match method {
-                                    b"GET" => return Ok(Match::Route(Route::People(people::Route::New(people::New {
-                                        lang: None,
-                                    })))),
-                                    _ => return Ok(Match::NotAllowed),
-                                }
-                            }
-                            match &path[i..i+1] {
-                                b"/" => {
-                                    i += 1;
-                                },
-                                _ => return Ok(Match::NotFound),
-                            }
-                            if i == len {
-                                match method {
-                                    b"GET" => return Ok(Match::Route(Route::People(people::Route::New(people::New {
-                                        lang: None,
-                                    })))),
-                                    _ => return Ok(Match::NotAllowed),
-                                }
-                            }
-                            return Ok(Match::NotFound);
                         },
-                        _ => {},
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => return Ok(Match::Route(Route::People(people::Route::New(people::New {
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            })))),
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
                     }
+                    if i == len {
+                        match method {
+                            b"GET" => return Ok(Match::Route(Route::People(people::Route::New(people::New {
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            })))),
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    break 'fallback0;
                 }
+                i = start;
 
                 while i < len && &path[i..i+1] != b"/" {
                     i += 1;
                 }
-
-                let text = std::str::from_utf8(&path[start..i]).unwrap();
-                let id = text.parse()
-                    .map_err(|e| Error::fail("id", e))?;
+                if i == start {
+                    return Ok(Match::NotFound);
+                }
+                let param_id = &path[start..i];
 
                 if i == len {
                     match method {
-                        b"GET" => return Ok(Match::Route(Route::People(people::Route::Show(people::Show {
-                            id,
-                            lang: None,
-                        })))),
-                        b"PUT" => return Ok(Match::Route(Route::People(people::Route::Update(people::Update {
-                            id,
-                            lang: None,
-                            name: None,
-                        })))),
-                        b"DELETE" => return Ok(Match::Route(Route::People(people::Route::Destroy(people::Destroy {
-                            id,
-                            lang: None,
-                        })))),
-                        _ => return Ok(Match::NotAllowed),
+                        b"GET" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::People(people::Route::Show(people::Show {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        b"PUT" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::People(people::Route::Update(people::Update {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                                name: parse_query(query, "name", ParamKind::Query, false, |kind, text, error| Error::Name {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        b"DELETE" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::People(people::Route::Destroy(people::Destroy {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET", "PUT", "DELETE"])),
                     }
                 }
                 match &path[i..i+1] {
@@ -684,20 +2012,69 @@ pub mod routes {
                 }
                 if i == len {
                     match method {
-                        b"GET" => return Ok(Match::Route(Route::People(people::Route::Show(people::Show {
-                            id,
-                            lang: None,
-                        })))),
-                        b"PUT" => return Ok(Match::Route(Route::People(people::Route::Update(people::Update {
-                            id,
-                            lang: None,
-                            name: None,
-                        })))),
-                        b"DELETE" => return Ok(Match::Route(Route::People(people::Route::Destroy(people::Destroy {
-                            id,
-                            lang: None,
-                        })))),
-                        _ => return Ok(Match::NotAllowed),
+                        b"GET" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::People(people::Route::Show(people::Show {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        b"PUT" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::People(people::Route::Update(people::Update {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                                name: parse_query(query, "name", ParamKind::Query, false, |kind, text, error| Error::Name {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        b"DELETE" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::People(people::Route::Destroy(people::Destroy {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET", "PUT", "DELETE"])),
                     }
                 }
                 if i + 4 > len {
@@ -711,11 +2088,26 @@ pub mod routes {
                 }
                 if i == len {
                     match method {
-                        b"GET" => return Ok(Match::Route(Route::People(people::Route::Edit(people::Edit {
-                            id,
-                            lang: None,
-                        })))),
-                        _ => return Ok(Match::NotAllowed),
+                        b"GET" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::People(people::Route::Edit(people::Edit {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET"])),
                     }
                 }
                 match &path[i..i+1] {
@@ -726,11 +2118,26 @@ pub mod routes {
                 }
                 if i == len {
                     match method {
-                        b"GET" => return Ok(Match::Route(Route::People(people::Route::Edit(people::Edit {
-                            id,
-                            lang: None,
-                        })))),
-                        _ => return Ok(Match::NotAllowed),
+                        b"GET" => {
+                            let param_id = match parse_path(param_id, false) {
+                                Some(Ok(param_id)) => param_id,
+                                Some(Err((text, error))) => return Err(Error::Id {
+                                    kind: ParamKind::Path,
+                                    text,
+                                    error,
+                                }),
+                                None => return Ok(Match::NotFound),
+                            };
+                            return Ok(Match::Route(Route::People(people::Route::Edit(people::Edit {
+                                id: param_id,
+                                lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                    kind,
+                                    text,
+                                    error,
+                                })?,
+                            }))));
+                        }
+                        _ => return Ok(Match::NotAllowed(&["GET"])),
                     }
                 }
                 return Ok(Match::NotFound);
@@ -749,9 +2156,13 @@ pub mod routes {
                 if i == len {
                     match method {
                         b"GET" => return Ok(Match::Redirect(Route::People(people::Route::Index(people::Index {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
                         })))),
-                        _ => return Ok(Match::NotAllowed),
+                        _ => return Ok(Match::NotAllowed(&["GET"])),
                     }
                 }
                 match &path[i..i+1] {
@@ -763,9 +2174,13 @@ pub mod routes {
                 if i == len {
                     match method {
                         b"GET" => return Ok(Match::Redirect(Route::People(people::Route::Index(people::Index {
-                            lang: None,
+                            lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                                kind,
+                                text,
+                                error,
+                            })?,
                         })))),
-                        _ => return Ok(Match::NotAllowed),
+                        _ => return Ok(Match::NotAllowed(&["GET"])),
                     }
                 }
                 return Ok(Match::NotFound);
@@ -774,4 +2189,105 @@ pub mod routes {
         }
     }
 
+    /// The longest request line `match_request_line` accepts, in bytes.
+    pub const MAX_REQUEST_LINE_LEN: usize = 8192;
+
+    /// The longest method `match_request_line` accepts, in bytes.
+    pub const MAX_METHOD_LEN: usize = 16;
+
+    /// A request line that couldn't be matched.
+    #[derive(Debug)]
+    pub enum RequestLineError {
+        /// The line is longer than `MAX_REQUEST_LINE_LEN`.
+        TooLong,
+        /// The line isn't three parts separated by spaces.
+        Malformed,
+        /// The method is empty, too long, or not a valid token.
+        BadMethod,
+        /// The request target isn't a path with an optional query, or
+        /// has characters that aren't allowed.
+        BadTarget,
+        /// The version isn't `HTTP/1.0` or `HTTP/1.1`.
+        BadVersion,
+        /// A parameter failed to parse.
+        Param(Error),
+    }
+
+    impl fmt::Display for RequestLineError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                RequestLineError::TooLong => write!(f, "Request line is longer than {} bytes", MAX_REQUEST_LINE_LEN),
+                RequestLineError::Malformed => f.write_str("Malformed request line"),
+                RequestLineError::BadMethod => f.write_str("Invalid method in request line"),
+                RequestLineError::BadTarget => f.write_str("Invalid request target in request line"),
+                RequestLineError::BadVersion => f.write_str("Unsupported HTTP version in request line"),
+                RequestLineError::Param(ref error) => write!(f, "{}", error),
+            }
+        }
+    }
+
+    impl std::error::Error for RequestLineError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match *self {
+                RequestLineError::Param(ref error) => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    fn is_token_char(b: u8) -> bool {
+        b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+    }
+
+    /// Parse the first line of an HTTP/1.x request, such as
+    /// `GET /people?lang=en HTTP/1.1`, and match it against this router.
+    ///
+    /// The line may end with `\r\n`.  The method must be a token of at
+    /// most `MAX_METHOD_LEN` bytes, the request target must be a path
+    /// starting with `/` of visible ASCII, and the whole line may be at
+    /// most `MAX_REQUEST_LINE_LEN` bytes.  Parsing the line doesn't
+    /// allocate, though parameters may when they're decoded and parsed.
+    pub fn match_request_line<L: AsRef<[u8]>>(line: L) -> Result<Match<Route>, RequestLineError> {
+        let mut line = line.as_ref();
+        if line.ends_with(b"\r\n") {
+            line = &line[..line.len() - 2];
+        }
+        if line.len() > MAX_REQUEST_LINE_LEN {
+            return Err(RequestLineError::TooLong);
+        }
+
+        let method_end = match line.iter().position(|&b| b == b' ') {
+            Some(i) => i,
+            None => return Err(RequestLineError::Malformed),
+        };
+        let target_end = match line.iter().rposition(|&b| b == b' ') {
+            Some(i) if i > method_end => i,
+            _ => return Err(RequestLineError::Malformed),
+        };
+        let method = &line[..method_end];
+        let target = &line[method_end + 1..target_end];
+        let version = &line[target_end + 1..];
+
+        if method.is_empty()
+            || method.len() > MAX_METHOD_LEN
+            || !method.iter().all(|&b| is_token_char(b))
+        {
+            return Err(RequestLineError::BadMethod);
+        }
+        if target.first() != Some(&b'/')
+            || !target.iter().all(|&b| b.is_ascii_graphic() && b != b'#')
+        {
+            return Err(RequestLineError::BadTarget);
+        }
+        if version != b"HTTP/1.1" && version != b"HTTP/1.0" {
+            return Err(RequestLineError::BadVersion);
+        }
+
+        let (path, query) = match target.iter().position(|&b| b == b'?') {
+            Some(i) => (&target[..i], &target[i + 1..]),
+            None => (target, &b""[..]),
+        };
+        match_path(path, query, method).map_err(RequestLineError::Param)
+    }
+
 } // mod routes
@@ -0,0 +1,1171 @@
+pub mod routes {
+
+    //! Application route configuration.
+    //!
+    //! Of note is the function [`match_route`] as well as request structs
+    //! specific to each named resource.
+    //!
+    //! Parameters are percent-decoded before they're parsed, and `+` in
+    //! a query string is a space.  Parameters declared `raw` are parsed
+    //! from the text exactly as it appears in the request instead.
+    //! Static path segments are matched literally, without decoding, so
+    //! `/peop%6Ce` doesn't match `/people`, and `%2F` is never a
+    //! separator.  Links are encoded the same way: parameters are
+    //! percent-encoded unless they're `raw`.
+    //!
+    //! A path parameter that isn't valid UTF-8 once it's decoded doesn't
+    //! match, since a sibling route might.  A query parameter that isn't
+    //! is reported as `Error::NotUtf8`, as the path has already matched.
+    //!
+    //! Route configuration:
+    //!
+    //! ```ignore
+    //! /
+    //!   [lang: String]
+    //!   {path: String}
+    //!     {method: String}
+    //!       {query: String}
+    //!         GET Show
+    //!           [len: usize]
+    //!         {w: String}
+    //!           GET Write
+    //!             [first: String]
+    //! ```
+    //!
+    //! [`match_route`]: fn.match_route.html
+
+    #![allow(dead_code)]
+    #![allow(unused_imports)]
+    #![allow(unused_mut)]
+    #![allow(unused_variables)]
+
+    /// Renders for `GET /{path}/{method}/{query}`.
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct Show {
+        pub path: String,
+        pub method: String,
+        pub query: String,
+        pub lang: Option<String>,
+        pub len: Option<usize>,
+    }
+
+    impl ToPath for Show {
+        fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+            let Show { path: ref param_path, method: ref param_method, query: ref param_query, lang: ref param_lang, len: ref param_len, } = self;
+            w.write_str("/")?;
+            write_encoded(w, param_path)?;
+            w.write_str("/")?;
+            write_encoded(w, param_method)?;
+            w.write_str("/")?;
+            write_encoded(w, param_query)?;
+            let mut first = true;
+            write_query(w, &mut first, "lang", param_lang, false)?;
+            write_query(w, &mut first, "len", param_len, false)?;
+            Ok(())
+        }
+    }
+
+    /// Renders for `GET /{path}/{method}/{query}/{w}`.
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct Write {
+        pub path: String,
+        pub method: String,
+        pub query: String,
+        pub w: String,
+        pub lang: Option<String>,
+        pub first: Option<String>,
+    }
+
+    impl ToPath for Write {
+        fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+            let Write { path: ref param_path, method: ref param_method, query: ref param_query, w: ref param_w, lang: ref param_lang, first: ref param_first, } = self;
+            w.write_str("/")?;
+            write_encoded(w, param_path)?;
+            w.write_str("/")?;
+            write_encoded(w, param_method)?;
+            w.write_str("/")?;
+            write_encoded(w, param_query)?;
+            w.write_str("/")?;
+            write_encoded(w, param_w)?;
+            let mut first = true;
+            write_query(w, &mut first, "lang", param_lang, false)?;
+            write_query(w, &mut first, "first", param_first, false)?;
+            Ok(())
+        }
+    }
+
+    /// An active route in the application -- match against this.
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum Route {
+        Show(Show),
+        Write(Write),
+    }
+
+    impl ToPath for Route {
+        fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+            match self {
+                Route::Show(ref route) => ToPath::write_path(route, w),
+                Route::Write(ref route) => ToPath::write_path(route, w),
+            }
+        }
+    }
+
+    impl From<Show> for Route {
+        fn from(route: Show) -> Route {
+            Route::Show(route)
+        }
+    }
+
+    impl From<Write> for Route {
+        fn from(route: Write) -> Route {
+            Route::Write(route)
+        }
+    }
+
+    /// Routes and request structs that can be rendered as a path.
+    pub trait ToPath {
+        /// Write the path to this route, with its parameters filled in.
+        fn write_path<W: fmt::Write>(&self, w: &mut W) -> fmt::Result;
+
+        /// Make a path to this route with the given parameters.
+        fn to_path(&self) -> String {
+            let mut path = String::new();
+            self.write_path(&mut path)
+                .expect("writing to a String can't fail");
+            path
+        }
+    }
+
+    /// Makes links to routes.
+    ///
+    /// Configure it once with the base URL of the app and the prefix
+    /// it's mounted at, if any, then use it to link to any route.
+    ///
+    /// ```ignore
+    /// let urls = Urls::new()
+    ///     .base("https://example.com")
+    ///     .mount("/app");
+    ///
+    /// urls.link_to(&route, None); // /app/people
+    /// urls.url_for(&route, Some("top")); // https://example.com/app/people#top
+    /// ```
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct Urls {
+        base: String,
+        mount: String,
+    }
+
+    impl Urls {
+        /// Links with no base URL or mount prefix, that is, just paths.
+        pub fn new() -> Urls {
+            Urls::default()
+        }
+
+        /// Set the scheme and host for absolute URLs, such as
+        /// `https://example.com`.
+        pub fn base<S: Into<String>>(mut self, base: S) -> Urls {
+            let mut base = base.into();
+            while base.ends_with('/') {
+                base.pop();
+            }
+            self.base = base;
+            self
+        }
+
+        /// Set the prefix the app is mounted at, such as `/app`.  It's
+        /// added to the start of every link.
+        pub fn mount<S: AsRef<str>>(mut self, prefix: S) -> Urls {
+            let prefix = prefix.as_ref().trim_matches('/');
+            self.mount = if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("/{}", prefix)
+            };
+            self
+        }
+
+        /// Write a link to the route, relative to the host, with an
+        /// optional fragment.
+        pub fn write_link<T: ToPath, W: fmt::Write>(
+            &self,
+            w: &mut W,
+            route: &T,
+            fragment: Option<&str>,
+        ) -> fmt::Result {
+            w.write_str(&self.mount)?;
+            route.write_path(w)?;
+            if let Some(fragment) = fragment {
+                w.write_char('#')?;
+                write_encoded(w, &fragment)?;
+            }
+            Ok(())
+        }
+
+        /// Make a link to the route, relative to the host, such as
+        /// `/app/people/new`.
+        pub fn link_to<T: ToPath>(&self, route: &T, fragment: Option<&str>) -> String {
+            let mut link = String::new();
+            self.write_link(&mut link, route, fragment)
+                .expect("writing to a String can't fail");
+            link
+        }
+
+        /// Make an absolute URL for the route, such as
+        /// `https://example.com/app/people/new`.  Without a base URL
+        /// this is the same as `link_to`.
+        pub fn url_for<T: ToPath>(&self, route: &T, fragment: Option<&str>) -> String {
+            let mut url = self.base.clone();
+            self.write_link(&mut url, route, fragment)
+                .expect("writing to a String can't fail");
+            url
+        }
+    }
+
+    #[derive(PartialEq, Eq)]
+    pub enum Match<T> {
+        NotFound,
+        /// The path matches, but not the method.  Holds the methods
+        /// the route does allow.
+        NotAllowed(&'static [&'static str]),
+        Route(T),
+        Redirect(T),
+    }
+
+    use std::fmt;
+    impl<T: fmt::Debug> fmt::Debug for Match<T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Match::NotFound => write!(f, "Match::NotFound"),
+                Match::NotAllowed(m) => write!(f, "Match::NotAllowed({:?})", m),
+                Match::Route(t) => write!(f, "Match::Route({:?})", t),
+                Match::Redirect(t) => write!(f, "Match::Redirect({:?})", t),
+            }
+        }
+    }
+
+    /// Which part of the request a parameter came from.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ParamKind {
+        Path,
+        Query,
+        Body,
+    }
+
+    impl fmt::Display for ParamKind {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(match self {
+                ParamKind::Path => "path",
+                ParamKind::Query => "query",
+                ParamKind::Body => "body",
+            })
+        }
+    }
+
+    /// A parameter that failed to parse.
+    ///
+    /// There is a variant for each parameter, holding the error from
+    /// its type's `FromStr` implementation, the text that failed to
+    /// parse, and where in the request it came from.  A query or body
+    /// parameter that isn't valid UTF-8 once it's decoded is `NotUtf8`
+    /// instead, while a path parameter that isn't is not a match.
+    #[derive(Debug)]
+    pub enum Error {
+        /// The `lang` parameter is not a valid `String`.
+        Lang {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `path` parameter is not a valid `String`.
+        Path {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `method` parameter is not a valid `String`.
+        Method {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `query` parameter is not a valid `String`.
+        Query {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `len` parameter is not a valid `usize`.
+        Len {
+            kind: ParamKind,
+            text: String,
+            error: <usize as std::str::FromStr>::Err,
+        },
+        /// The `w` parameter is not a valid `String`.
+        W {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `first` parameter is not a valid `String`.
+        First {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The query or body parameter named by `param` is not valid
+        /// UTF-8.  The text has each invalid sequence replaced with `U+FFFD`.
+        NotUtf8 {
+            param: &'static str,
+            kind: ParamKind,
+            text: String,
+        },
+    }
+
+    impl Error {
+        /// The name of the parameter that failed to parse.
+        pub fn param(&self) -> &'static str {
+            match *self {
+                Error::Lang { .. } => "lang",
+                Error::Path { .. } => "path",
+                Error::Method { .. } => "method",
+                Error::Query { .. } => "query",
+                Error::Len { .. } => "len",
+                Error::W { .. } => "w",
+                Error::First { .. } => "first",
+                Error::NotUtf8 { param, .. } => param,
+            }
+        }
+
+        /// Whether the parameter was part of the path, the query or the body.
+        pub fn kind(&self) -> ParamKind {
+            match *self {
+                Error::Lang { kind, .. } => kind,
+                Error::Path { kind, .. } => kind,
+                Error::Method { kind, .. } => kind,
+                Error::Query { kind, .. } => kind,
+                Error::Len { kind, .. } => kind,
+                Error::W { kind, .. } => kind,
+                Error::First { kind, .. } => kind,
+                Error::NotUtf8 { kind, .. } => kind,
+            }
+        }
+
+        /// The text that failed to parse.
+        pub fn text(&self) -> &str {
+            match *self {
+                Error::Lang { ref text, .. } => text,
+                Error::Path { ref text, .. } => text,
+                Error::Method { ref text, .. } => text,
+                Error::Query { ref text, .. } => text,
+                Error::Len { ref text, .. } => text,
+                Error::W { ref text, .. } => text,
+                Error::First { ref text, .. } => text,
+                Error::NotUtf8 { ref text, .. } => text,
+            }
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(
+                f,
+                "Error parsing {} parameter '{}' from {:?}: ",
+                self.kind(),
+                self.param(),
+                self.text()
+            )?;
+            match *self {
+                Error::Lang { ref error, .. } => write!(f, "{:?}", error),
+                Error::Path { ref error, .. } => write!(f, "{:?}", error),
+                Error::Method { ref error, .. } => write!(f, "{:?}", error),
+                Error::Query { ref error, .. } => write!(f, "{:?}", error),
+                Error::Len { ref error, .. } => write!(f, "{:?}", error),
+                Error::W { ref error, .. } => write!(f, "{:?}", error),
+                Error::First { ref error, .. } => write!(f, "{:?}", error),
+                Error::NotUtf8 { .. } => f.write_str("not valid UTF-8"),
+            }
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    /// Percent-encodes everything written to it except unreserved
+    /// characters, so it's safe in a path segment or query.
+    struct Encode<'a, W: fmt::Write>(&'a mut W);
+
+    impl<'a, W: fmt::Write> fmt::Write for Encode<'a, W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for &b in s.as_bytes() {
+                if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                    self.0.write_char(b as char)?;
+                } else {
+                    write!(self.0, "%{:02X}", b)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn write_encoded<W: fmt::Write, T: fmt::Display>(w: &mut W, value: &T) -> fmt::Result {
+        use std::fmt::Write;
+        write!(Encode(w), "{}", value)
+    }
+
+    fn write_query<W: fmt::Write, T: fmt::Display>(
+        w: &mut W,
+        first: &mut bool,
+        name: &str,
+        value: &Option<T>,
+        raw: bool,
+    ) -> fmt::Result {
+        if let Some(ref value) = *value {
+            w.write_char(if *first { '?' } else { '&' })?;
+            *first = false;
+            w.write_str(name)?;
+            w.write_char('=')?;
+            if raw {
+                write!(w, "{}", value)?;
+            } else {
+                write_encoded(w, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn from_hex(b: u8) -> Option<u8> {
+        match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' => Some(b - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    /// Decode percent-escapes, and in a query string, `+` as a space.
+    /// Invalid escapes are left as they are.  Only allocates if there
+    /// is something to decode.
+    fn decode(bytes: &[u8], is_query: bool) -> std::borrow::Cow<'_, [u8]> {
+        if !bytes.iter().any(|&b| b == b'%' || (is_query && b == b'+')) {
+            return std::borrow::Cow::Borrowed(bytes);
+        }
+
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'%' if i + 2 < bytes.len() => {
+                    match (from_hex(bytes[i + 1]), from_hex(bytes[i + 2])) {
+                        (Some(hi), Some(lo)) => {
+                            decoded.push(hi * 16 + lo);
+                            i += 3;
+                            continue;
+                        }
+                        _ => decoded.push(b'%'),
+                    }
+                }
+                b'+' if is_query => decoded.push(b' '),
+                b => decoded.push(b),
+            }
+            i += 1;
+        }
+        std::borrow::Cow::Owned(decoded)
+    }
+
+    /// Parse a path parameter, decoding it unless it's raw.  If it isn't
+    /// valid UTF-8 it's not a match, otherwise the error has the text.
+    fn parse_path<T: std::str::FromStr>(
+        text: &[u8],
+        raw: bool,
+    ) -> Option<Result<T, (String, T::Err)>> {
+        let text = if raw {
+            std::borrow::Cow::Borrowed(text)
+        } else {
+            decode(text, false)
+        };
+        let text = std::str::from_utf8(&text).ok()?;
+        Some(text.parse().map_err(|e| (text.to_string(), e)))
+    }
+
+    /// The error for a query or body parameter that isn't valid UTF-8.
+    fn not_utf8(param: &'static str, kind: ParamKind, text: &[u8]) -> Error {
+        Error::NotUtf8 {
+            param,
+            kind,
+            text: String::from_utf8_lossy(text).into_owned(),
+        }
+    }
+
+    /// Find and parse the first value for the named query parameter,
+    /// decoding it unless it's raw.  If it isn't valid UTF-8, or doesn't
+    /// parse, the error has the text.
+    fn parse_query<T: std::str::FromStr>(
+        query: &[u8],
+        name: &'static str,
+        kind: ParamKind,
+        raw: bool,
+        error: fn(ParamKind, String, T::Err) -> Error,
+    ) -> Result<Option<T>, Error> {
+        if query.is_empty() {
+            return Ok(None);
+        }
+
+        for pair in query.split(|&b| b == b'&') {
+            let (key, value) = match pair.iter().position(|&b| b == b'=') {
+                Some(i) => (&pair[..i], &pair[i + 1..]),
+                None => (pair, &b""[..]),
+            };
+            if &*decode(key, true) != name.as_bytes() {
+                continue;
+            }
+
+            let value = if raw {
+                std::borrow::Cow::Borrowed(value)
+            } else {
+                decode(value, true)
+            };
+            let text = match std::str::from_utf8(&value) {
+                Ok(text) => text,
+                Err(_) => return Err(not_utf8(name, kind, &value)),
+            };
+            return text
+                .parse()
+                .map(Some)
+                .map_err(|e| error(kind, text.to_string(), e));
+        }
+
+        Ok(None)
+    }
+
+    /// A problem finding a route by name or filling in its parameters.
+    #[derive(Debug)]
+    pub enum UrlError {
+        /// There is no route with this name.
+        UnknownRoute(String),
+        /// The route needs a parameter that wasn't given.
+        MissingParam {
+            route: &'static str,
+            param: &'static str,
+        },
+        /// A parameter was given that the route doesn't take.
+        UnknownParam {
+            route: &'static str,
+            param: String,
+        },
+        /// A parameter was given that failed to parse.
+        Param(Error),
+    }
+
+    impl fmt::Display for UrlError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                UrlError::UnknownRoute(ref name) => write!(f, "Unknown route '{}'", name),
+                UrlError::MissingParam { route, param } => {
+                    write!(f, "Missing parameter '{}' for route '{}'", param, route)
+                }
+                UrlError::UnknownParam { route, ref param } => {
+                    write!(f, "Unknown parameter '{}' for route '{}'", param, route)
+                }
+                UrlError::Param(ref error) => write!(f, "{}", error),
+            }
+        }
+    }
+
+    impl std::error::Error for UrlError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match *self {
+                UrlError::Param(ref error) => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    /// Find the action named `name` and fill it in from `params`.
+    ///
+    /// Actions are named by their module path and action name in
+    /// snake case, separated by dots, such as `people.show`.  Every
+    /// path parameter of the action must be given, query parameters are
+    /// optional, and anything else is an error.  Body parameters aren't
+    /// part of a URL, so they're left `None`.
+    pub fn route_for(name: &str, params: &[(&str, &str)]) -> Result<Route, UrlError> {
+        match name {
+            "show" => {
+                check_params("show", params, &["path", "method", "query", "lang", "len"])?;
+                let action = Show {
+                    path: required_param("show", params, "path", |text, error| Error::Path {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    method: required_param("show", params, "method", |text, error| Error::Method {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    query: required_param("show", params, "query", |text, error| Error::Query {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                    len: optional_param(params, "len", |text, error| Error::Len {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "write" => {
+                check_params("write", params, &["path", "method", "query", "w", "lang", "first"])?;
+                let action = Write {
+                    path: required_param("write", params, "path", |text, error| Error::Path {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    method: required_param("write", params, "method", |text, error| Error::Method {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    query: required_param("write", params, "query", |text, error| Error::Query {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    w: required_param("write", params, "w", |text, error| Error::W {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    lang: optional_param(params, "lang", |text, error| Error::Lang {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                    first: optional_param(params, "first", |text, error| Error::First {
+                        kind: ParamKind::Query,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            _ => Err(UrlError::UnknownRoute(name.to_string())),
+        }
+    }
+
+    /// Make a path to the action named `name` with the given parameters,
+    /// for when the route isn't known until runtime, such as in a
+    /// template.  See [`route_for`] for the details.
+    ///
+    /// [`route_for`]: fn.route_for.html
+    pub fn url_for(name: &str, params: &[(&str, &str)]) -> Result<String, UrlError> {
+        route_for(name, params).map(|route| route.to_path())
+    }
+
+    fn check_params(
+        route: &'static str,
+        params: &[(&str, &str)],
+        known: &[&str],
+    ) -> Result<(), UrlError> {
+        for &(param, _) in params {
+            if !known.contains(&param) {
+                return Err(UrlError::UnknownParam {
+                    route,
+                    param: param.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn optional_param<T: std::str::FromStr>(
+        params: &[(&str, &str)],
+        name: &str,
+        error: fn(String, T::Err) -> Error,
+    ) -> Result<Option<T>, UrlError> {
+        match params.iter().find(|&&(param, _)| param == name) {
+            None => Ok(None),
+            Some(&(_, text)) => text
+                .parse()
+                .map(Some)
+                .map_err(|e| error(text.to_string(), e))
+                .map_err(UrlError::Param),
+        }
+    }
+
+    fn required_param<T: std::str::FromStr>(
+        route: &'static str,
+        params: &[(&str, &str)],
+        name: &'static str,
+        error: fn(String, T::Err) -> Error,
+    ) -> Result<T, UrlError> {
+        optional_param(params, name, error)?.ok_or(UrlError::MissingParam {
+            route,
+            param: name,
+        })
+    }
+
+    /// Match a path and method against this router.
+    ///
+    /// Accepts a byte slice for the request path and HTTP verb.
+    /// Returns a `Result`, usually `Ok` with the result of the
+    /// [`Match`].
+    ///
+    /// If the match was successful, it will be a `Match::Route` or
+    /// `Match::Redirect` with the parameters enclosed.  You can then
+    /// match on the [`Route`] to pass control of the request along to
+    /// a specific handler.
+    ///
+    /// If there is no match, this will return `Match::NotFound`
+    /// if no path matches (which you could return as `404 Not Found`),
+    /// or `Match::NotAllowed` if no method matches (in which case a
+    /// `405 Not Allowed` would be appropriate, with the methods it holds
+    /// in the `Allow` header).
+    ///
+    /// Static path segments take precedence over a parameter in the
+    /// same place.  If the rest of the path doesn't match after a static
+    /// segment, the parameter is tried instead.  A parameter matches a
+    /// whole segment, which can't be empty.  Parameters side by side are
+    /// tried in the order they're declared, and one that fails to parse
+    /// falls through to the next.
+    ///
+    /// Parameters are only parsed once the whole path matches, so a path
+    /// that doesn't match any route is `NotFound` even if a parameter in
+    /// it is invalid.  One that could fall through to the next is parsed
+    /// before the method is checked, and the rest after.  If the last
+    /// route parameter tried fails to parse, this will return `Err` with
+    /// the underlying parsing error.  Usually you'll want to send back a
+    /// `400 Bad Request` for that.
+    ///
+    /// Any bytes may be passed without panicking.  A path segment that
+    /// isn't valid UTF-8 once percent-decoded can't be a parameter, so
+    /// it's `Match::NotFound`.
+    ///
+    /// [`Match`]: enum.Match.html
+    /// [`Route`]: enum.Route.html
+    pub fn match_route<P: AsRef<[u8]>, M: AsRef<[u8]>>(
+        path: P,
+        method: M,
+    ) -> Result<Match<Route>, Error> {
+        match_path(path.as_ref(), b"", method.as_ref())
+    }
+
+    /// Match a request target and method against this router.
+    ///
+    /// The request target is a path with an optional query string, such
+    /// as `/people?lang=en`.  This works like [`match_route`], but query
+    /// parameters are also decoded and parsed from the query string.
+    ///
+    /// [`match_route`]: fn.match_route.html
+    pub fn match_request<T: AsRef<[u8]>, M: AsRef<[u8]>>(
+        target: T,
+        method: M,
+    ) -> Result<Match<Route>, Error> {
+        let target = target.as_ref();
+        match target.iter().position(|&b| b == b'?') {
+            Some(i) => match_path(&target[..i], &target[i + 1..], method.as_ref()),
+            None => match_path(target, b"", method.as_ref()),
+        }
+    }
+
+    fn match_path(path: &[u8], query: &[u8], method: &[u8]) -> Result<Match<Route>, Error> {
+        let len = path.len();
+        let mut i = if len > 0 && &path[0..1] == b"/" { 1 } else { 0 };
+
+        let start = i;
+
+        while i < len && &path[i..i+1] != b"/" {
+            i += 1;
+        }
+        if i == start {
+            return Ok(Match::NotFound);
+        }
+        let param_path = &path[start..i];
+
+        if i == len {
+            return Ok(Match::NotFound);
+        }
+        match &path[i..i+1] {
+            b"/" => {
+                i += 1;
+            },
+            _ => return Ok(Match::NotFound),
+        }
+        let start = i;
+
+        while i < len && &path[i..i+1] != b"/" {
+            i += 1;
+        }
+        if i == start {
+            return Ok(Match::NotFound);
+        }
+        let param_method = &path[start..i];
+
+        if i == len {
+            return Ok(Match::NotFound);
+        }
+        match &path[i..i+1] {
+            b"/" => {
+                i += 1;
+            },
+            _ => return Ok(Match::NotFound),
+        }
+        let start = i;
+
+        while i < len && &path[i..i+1] != b"/" {
+            i += 1;
+        }
+        if i == start {
+            return Ok(Match::NotFound);
+        }
+        let param_query = &path[start..i];
+
+        if i == len {
+            match method {
+                b"GET" => {
+                    let param_path = match parse_path(param_path, false) {
+                        Some(Ok(param_path)) => param_path,
+                        Some(Err((text, error))) => return Err(Error::Path {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_method = match parse_path(param_method, false) {
+                        Some(Ok(param_method)) => param_method,
+                        Some(Err((text, error))) => return Err(Error::Method {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_query = match parse_path(param_query, false) {
+                        Some(Ok(param_query)) => param_query,
+                        Some(Err((text, error))) => return Err(Error::Query {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    return Ok(Match::Route(Route::Show(Show {
+                        path: param_path,
+                        method: param_method,
+                        query: param_query,
+                        lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                            kind,
+                            text,
+                            error,
+                        })?,
+                        len: parse_query(query, "len", ParamKind::Query, false, |kind, text, error| Error::Len {
+                            kind,
+                            text,
+                            error,
+                        })?,
+                    })));
+                }
+                _ => return Ok(Match::NotAllowed(&["GET"])),
+            }
+        }
+        match &path[i..i+1] {
+            b"/" => {
+                i += 1;
+            },
+            _ => return Ok(Match::NotFound),
+        }
+        if i == len {
+            match method {
+                b"GET" => {
+                    let param_path = match parse_path(param_path, false) {
+                        Some(Ok(param_path)) => param_path,
+                        Some(Err((text, error))) => return Err(Error::Path {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_method = match parse_path(param_method, false) {
+                        Some(Ok(param_method)) => param_method,
+                        Some(Err((text, error))) => return Err(Error::Method {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_query = match parse_path(param_query, false) {
+                        Some(Ok(param_query)) => param_query,
+                        Some(Err((text, error))) => return Err(Error::Query {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    return Ok(Match::Route(Route::Show(Show {
+                        path: param_path,
+                        method: param_method,
+                        query: param_query,
+                        lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                            kind,
+                            text,
+                            error,
+                        })?,
+                        len: parse_query(query, "len", ParamKind::Query, false, |kind, text, error| Error::Len {
+                            kind,
+                            text,
+                            error,
+                        })?,
+                    })));
+                }
+                _ => return Ok(Match::NotAllowed(&["GET"])),
+            }
+        }
+        let start = i;
+
+        while i < len && &path[i..i+1] != b"/" {
+            i += 1;
+        }
+        if i == start {
+            return Ok(Match::NotFound);
+        }
+        let param_w = &path[start..i];
+
+        if i == len {
+            match method {
+                b"GET" => {
+                    let param_path = match parse_path(param_path, false) {
+                        Some(Ok(param_path)) => param_path,
+                        Some(Err((text, error))) => return Err(Error::Path {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_method = match parse_path(param_method, false) {
+                        Some(Ok(param_method)) => param_method,
+                        Some(Err((text, error))) => return Err(Error::Method {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_query = match parse_path(param_query, false) {
+                        Some(Ok(param_query)) => param_query,
+                        Some(Err((text, error))) => return Err(Error::Query {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_w = match parse_path(param_w, false) {
+                        Some(Ok(param_w)) => param_w,
+                        Some(Err((text, error))) => return Err(Error::W {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    return Ok(Match::Route(Route::Write(Write {
+                        path: param_path,
+                        method: param_method,
+                        query: param_query,
+                        w: param_w,
+                        lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                            kind,
+                            text,
+                            error,
+                        })?,
+                        first: parse_query(query, "first", ParamKind::Query, false, |kind, text, error| Error::First {
+                            kind,
+                            text,
+                            error,
+                        })?,
+                    })));
+                }
+                _ => return Ok(Match::NotAllowed(&["GET"])),
+            }
+        }
+        match &path[i..i+1] {
+            b"/" => {
+                i += 1;
+            },
+            _ => return Ok(Match::NotFound),
+        }
+        if i == len {
+            match method {
+                b"GET" => {
+                    let param_path = match parse_path(param_path, false) {
+                        Some(Ok(param_path)) => param_path,
+                        Some(Err((text, error))) => return Err(Error::Path {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_method = match parse_path(param_method, false) {
+                        Some(Ok(param_method)) => param_method,
+                        Some(Err((text, error))) => return Err(Error::Method {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_query = match parse_path(param_query, false) {
+                        Some(Ok(param_query)) => param_query,
+                        Some(Err((text, error))) => return Err(Error::Query {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    let param_w = match parse_path(param_w, false) {
+                        Some(Ok(param_w)) => param_w,
+                        Some(Err((text, error))) => return Err(Error::W {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    return Ok(Match::Route(Route::Write(Write {
+                        path: param_path,
+                        method: param_method,
+                        query: param_query,
+                        w: param_w,
+                        lang: parse_query(query, "lang", ParamKind::Query, false, |kind, text, error| Error::Lang {
+                            kind,
+                            text,
+                            error,
+                        })?,
+                        first: parse_query(query, "first", ParamKind::Query, false, |kind, text, error| Error::First {
+                            kind,
+                            text,
+                            error,
+                        })?,
+                    })));
+                }
+                _ => return Ok(Match::NotAllowed(&["GET"])),
+            }
+        }
+        return Ok(Match::NotFound);
+    }
+
+    /// The longest request line `match_request_line` accepts, in bytes.
+    pub const MAX_REQUEST_LINE_LEN: usize = 8192;
+
+    /// The longest method `match_request_line` accepts, in bytes.
+    pub const MAX_METHOD_LEN: usize = 16;
+
+    /// A request line that couldn't be matched.
+    #[derive(Debug)]
+    pub enum RequestLineError {
+        /// The line is longer than `MAX_REQUEST_LINE_LEN`.
+        TooLong,
+        /// The line isn't three parts separated by spaces.
+        Malformed,
+        /// The method is empty, too long, or not a valid token.
+        BadMethod,
+        /// The request target isn't a path with an optional query, or
+        /// has characters that aren't allowed.
+        BadTarget,
+        /// The version isn't `HTTP/1.0` or `HTTP/1.1`.
+        BadVersion,
+        /// A parameter failed to parse.
+        Param(Error),
+    }
+
+    impl fmt::Display for RequestLineError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                RequestLineError::TooLong => write!(f, "Request line is longer than {} bytes", MAX_REQUEST_LINE_LEN),
+                RequestLineError::Malformed => f.write_str("Malformed request line"),
+                RequestLineError::BadMethod => f.write_str("Invalid method in request line"),
+                RequestLineError::BadTarget => f.write_str("Invalid request target in request line"),
+                RequestLineError::BadVersion => f.write_str("Unsupported HTTP version in request line"),
+                RequestLineError::Param(ref error) => write!(f, "{}", error),
+            }
+        }
+    }
+
+    impl std::error::Error for RequestLineError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match *self {
+                RequestLineError::Param(ref error) => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    fn is_token_char(b: u8) -> bool {
+        b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+    }
+
+    /// Parse the first line of an HTTP/1.x request, such as
+    /// `GET /people?lang=en HTTP/1.1`, and match it against this router.
+    ///
+    /// The line may end with `\r\n`.  The method must be a token of at
+    /// most `MAX_METHOD_LEN` bytes, the request target must be a path
+    /// starting with `/` of visible ASCII, and the whole line may be at
+    /// most `MAX_REQUEST_LINE_LEN` bytes.  Parsing the line doesn't
+    /// allocate, though parameters may when they're decoded and parsed.
+    pub fn match_request_line<L: AsRef<[u8]>>(line: L) -> Result<Match<Route>, RequestLineError> {
+        let mut line = line.as_ref();
+        if line.ends_with(b"\r\n") {
+            line = &line[..line.len() - 2];
+        }
+        if line.len() > MAX_REQUEST_LINE_LEN {
+            return Err(RequestLineError::TooLong);
+        }
+
+        let method_end = match line.iter().position(|&b| b == b' ') {
+            Some(i) => i,
+            None => return Err(RequestLineError::Malformed),
+        };
+        let target_end = match line.iter().rposition(|&b| b == b' ') {
+            Some(i) if i > method_end => i,
+            _ => return Err(RequestLineError::Malformed),
+        };
+        let method = &line[..method_end];
+        let target = &line[method_end + 1..target_end];
+        let version = &line[target_end + 1..];
+
+        if method.is_empty()
+            || method.len() > MAX_METHOD_LEN
+            || !method.iter().all(|&b| is_token_char(b))
+        {
+            return Err(RequestLineError::BadMethod);
+        }
+        if target.first() != Some(&b'/')
+            || !target.iter().all(|&b| b.is_ascii_graphic() && b != b'#')
+        {
+            return Err(RequestLineError::BadTarget);
+        }
+        if version != b"HTTP/1.1" && version != b"HTTP/1.0" {
+            return Err(RequestLineError::BadVersion);
+        }
+
+        let (path, query) = match target.iter().position(|&b| b == b'?') {
+            Some(i) => (&target[..i], &target[i + 1..]),
+            None => (target, &b""[..]),
+        };
+        match_path(path, query, method).map_err(RequestLineError::Param)
+    }
+
+} // mod routes
@@ -0,0 +1,1983 @@
+pub mod routes {
+
+    //! Application route configuration.
+    //!
+    //! Of note is the function [`match_route`] as well as request structs
+    //! specific to each named resource.
+    //!
+    //! Parameters are percent-decoded before they're parsed, and `+` in
+    //! a query string is a space.  Parameters declared `raw` are parsed
+    //! from the text exactly as it appears in the request instead.
+    //! Static path segments are matched literally, without decoding, so
+    //! `/peop%6Ce` doesn't match `/people`, and `%2F` is never a
+    //! separator.  Links are encoded the same way: parameters are
+    //! percent-encoded unless they're `raw`.
+    //!
+    //! A path parameter that isn't valid UTF-8 once it's decoded doesn't
+    //! match, since a sibling route might.  A query parameter that isn't
+    //! is reported as `Error::NotUtf8`, as the path has already matched.
+    //!
+    //! Route configuration:
+    //!
+    //! ```ignore
+    //! /
+    //!   new
+    //!     GET New
+    //!   {slug: String}
+    //!     GET Show
+    //!     extra
+    //!       GET Extra
+    //!   people
+    //!     new
+    //!       GET People::New
+    //!     newest
+    //!       GET People::Newest
+    //!     {id: u32}
+    //!       GET People::Show
+    //!       edit
+    //!         GET People::Edit
+    //!   deep
+    //!     a
+    //!       b
+    //!         GET Deep::Ab
+    //!     {x: String}
+    //!       c
+    //!         GET Deep::C
+    //!   items
+    //!     {number: u64}
+    //!       GET Items::Show
+    //!       edit
+    //!         GET Items::Edit
+    //!     {slug: String}
+    //!       GET Items::Find
+    //!       POST Items::Update
+    //!         <note: String>
+    //!       history
+    //!         GET Items::History
+    //! ```
+    //!
+    //! [`match_route`]: fn.match_route.html
+
+    #![allow(dead_code)]
+    #![allow(unused_imports)]
+    #![allow(unused_mut)]
+    #![allow(unused_variables)]
+
+    /// Renders for `GET /{slug}/extra`.
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct Extra {
+        pub slug: String,
+    }
+
+    impl ToPath for Extra {
+        fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+            let Extra { slug: ref param_slug, } = self;
+            w.write_str("/")?;
+            write_encoded(w, param_slug)?;
+            w.write_str("/extra")?;
+            Ok(())
+        }
+    }
+
+    /// Renders for `GET /new`.
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct New;
+
+    impl ToPath for New {
+        fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+            w.write_str("/new")?;
+            Ok(())
+        }
+    }
+
+    /// Renders for `GET /{slug}`.
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct Show {
+        pub slug: String,
+    }
+
+    impl ToPath for Show {
+        fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+            let Show { slug: ref param_slug, } = self;
+            w.write_str("/")?;
+            write_encoded(w, param_slug)?;
+            Ok(())
+        }
+    }
+
+    pub mod deep {
+        /// Renders for `GET /deep/a/b`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Ab;
+
+        impl super::ToPath for Ab {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                w.write_str("/deep/a/b")?;
+                Ok(())
+            }
+        }
+
+        /// Renders for `GET /deep/{x}/c`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct C {
+            pub x: String,
+        }
+
+        impl super::ToPath for C {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let C { x: ref param_x, } = self;
+                w.write_str("/deep/")?;
+                super::write_encoded(w, param_x)?;
+                w.write_str("/c")?;
+                Ok(())
+            }
+        }
+
+        /// Parameters for requests to the deep controller.
+        #[derive(Debug, PartialEq, Eq)]
+        pub enum Route {
+            Ab(Ab),
+            C(C),
+        }
+
+        impl super::ToPath for Route {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                match self {
+                    Route::Ab(ref route) => super::ToPath::write_path(route, w),
+                    Route::C(ref route) => super::ToPath::write_path(route, w),
+                }
+            }
+        }
+
+        impl From<Ab> for Route {
+            fn from(route: Ab) -> Route {
+                Route::Ab(route)
+            }
+        }
+
+        impl From<C> for Route {
+            fn from(route: C) -> Route {
+                Route::C(route)
+            }
+        }
+    }
+
+    pub mod items {
+        /// Renders for `GET /items/{number}/edit`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Edit {
+            pub number: u64,
+        }
+
+        impl super::ToPath for Edit {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Edit { number: ref param_number, } = self;
+                w.write_str("/items/")?;
+                super::write_encoded(w, param_number)?;
+                w.write_str("/edit")?;
+                Ok(())
+            }
+        }
+
+        /// Renders for `GET /items/{slug}`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Find {
+            pub slug: String,
+        }
+
+        impl super::ToPath for Find {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Find { slug: ref param_slug, } = self;
+                w.write_str("/items/")?;
+                super::write_encoded(w, param_slug)?;
+                Ok(())
+            }
+        }
+
+        /// Renders for `GET /items/{slug}/history`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct History {
+            pub slug: String,
+        }
+
+        impl super::ToPath for History {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let History { slug: ref param_slug, } = self;
+                w.write_str("/items/")?;
+                super::write_encoded(w, param_slug)?;
+                w.write_str("/history")?;
+                Ok(())
+            }
+        }
+
+        /// Renders for `GET /items/{number}`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Show {
+            pub number: u64,
+        }
+
+        impl super::ToPath for Show {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Show { number: ref param_number, } = self;
+                w.write_str("/items/")?;
+                super::write_encoded(w, param_number)?;
+                Ok(())
+            }
+        }
+
+        /// Renders for `POST /items/{slug}`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Update {
+            pub slug: String,
+            /// Read from a form body by the async `dispatch`, and `None`
+            /// when the route is matched otherwise.
+            pub note: Option<String>,
+        }
+
+        impl super::ToPath for Update {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Update { slug: ref param_slug, .. } = self;
+                w.write_str("/items/")?;
+                super::write_encoded(w, param_slug)?;
+                Ok(())
+            }
+        }
+
+        /// Parameters for requests to the items controller.
+        #[derive(Debug, PartialEq, Eq)]
+        pub enum Route {
+            Edit(Edit),
+            Find(Find),
+            History(History),
+            Show(Show),
+            Update(Update),
+        }
+
+        impl super::ToPath for Route {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                match self {
+                    Route::Edit(ref route) => super::ToPath::write_path(route, w),
+                    Route::Find(ref route) => super::ToPath::write_path(route, w),
+                    Route::History(ref route) => super::ToPath::write_path(route, w),
+                    Route::Show(ref route) => super::ToPath::write_path(route, w),
+                    Route::Update(ref route) => super::ToPath::write_path(route, w),
+                }
+            }
+        }
+
+        impl From<Edit> for Route {
+            fn from(route: Edit) -> Route {
+                Route::Edit(route)
+            }
+        }
+
+        impl From<Find> for Route {
+            fn from(route: Find) -> Route {
+                Route::Find(route)
+            }
+        }
+
+        impl From<History> for Route {
+            fn from(route: History) -> Route {
+                Route::History(route)
+            }
+        }
+
+        impl From<Show> for Route {
+            fn from(route: Show) -> Route {
+                Route::Show(route)
+            }
+        }
+
+        impl From<Update> for Route {
+            fn from(route: Update) -> Route {
+                Route::Update(route)
+            }
+        }
+    }
+
+    pub mod people {
+        /// Renders for `GET /people/{id}/edit`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Edit {
+            pub id: u32,
+        }
+
+        impl super::ToPath for Edit {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Edit { id: ref param_id, } = self;
+                w.write_str("/people/")?;
+                super::write_encoded(w, param_id)?;
+                w.write_str("/edit")?;
+                Ok(())
+            }
+        }
+
+        /// Renders for `GET /people/new`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct New;
+
+        impl super::ToPath for New {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                w.write_str("/people/new")?;
+                Ok(())
+            }
+        }
+
+        /// Renders for `GET /people/newest`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Newest;
+
+        impl super::ToPath for Newest {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                w.write_str("/people/newest")?;
+                Ok(())
+            }
+        }
+
+        /// Renders for `GET /people/{id}`.
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Show {
+            pub id: u32,
+        }
+
+        impl super::ToPath for Show {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                let Show { id: ref param_id, } = self;
+                w.write_str("/people/")?;
+                super::write_encoded(w, param_id)?;
+                Ok(())
+            }
+        }
+
+        /// Parameters for requests to the people controller.
+        #[derive(Debug, PartialEq, Eq)]
+        pub enum Route {
+            Edit(Edit),
+            New(New),
+            Newest(Newest),
+            Show(Show),
+        }
+
+        impl super::ToPath for Route {
+            fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+                match self {
+                    Route::Edit(ref route) => super::ToPath::write_path(route, w),
+                    Route::New(ref route) => super::ToPath::write_path(route, w),
+                    Route::Newest(ref route) => super::ToPath::write_path(route, w),
+                    Route::Show(ref route) => super::ToPath::write_path(route, w),
+                }
+            }
+        }
+
+        impl From<Edit> for Route {
+            fn from(route: Edit) -> Route {
+                Route::Edit(route)
+            }
+        }
+
+        impl From<New> for Route {
+            fn from(route: New) -> Route {
+                Route::New(route)
+            }
+        }
+
+        impl From<Newest> for Route {
+            fn from(route: Newest) -> Route {
+                Route::Newest(route)
+            }
+        }
+
+        impl From<Show> for Route {
+            fn from(route: Show) -> Route {
+                Route::Show(route)
+            }
+        }
+    }
+
+    /// An active route in the application -- match against this.
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum Route {
+        Extra(Extra),
+        New(New),
+        Show(Show),
+        Deep(deep::Route),
+        Items(items::Route),
+        People(people::Route),
+    }
+
+    impl ToPath for Route {
+        fn write_path<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+            match self {
+                Route::Extra(ref route) => ToPath::write_path(route, w),
+                Route::New(ref route) => ToPath::write_path(route, w),
+                Route::Show(ref route) => ToPath::write_path(route, w),
+                Route::Deep(ref route) => ToPath::write_path(route, w),
+                Route::Items(ref route) => ToPath::write_path(route, w),
+                Route::People(ref route) => ToPath::write_path(route, w),
+            }
+        }
+    }
+
+    impl From<Extra> for Route {
+        fn from(route: Extra) -> Route {
+            Route::Extra(route)
+        }
+    }
+
+    impl From<New> for Route {
+        fn from(route: New) -> Route {
+            Route::New(route)
+        }
+    }
+
+    impl From<Show> for Route {
+        fn from(route: Show) -> Route {
+            Route::Show(route)
+        }
+    }
+
+    impl From<deep::Route> for Route {
+        fn from(route: deep::Route) -> Route {
+            Route::Deep(route)
+        }
+    }
+
+    impl From<deep::Ab> for Route {
+        fn from(route: deep::Ab) -> Route {
+            Route::Deep(route.into())
+        }
+    }
+
+    impl From<deep::C> for Route {
+        fn from(route: deep::C) -> Route {
+            Route::Deep(route.into())
+        }
+    }
+
+    impl From<items::Route> for Route {
+        fn from(route: items::Route) -> Route {
+            Route::Items(route)
+        }
+    }
+
+    impl From<items::Edit> for Route {
+        fn from(route: items::Edit) -> Route {
+            Route::Items(route.into())
+        }
+    }
+
+    impl From<items::Find> for Route {
+        fn from(route: items::Find) -> Route {
+            Route::Items(route.into())
+        }
+    }
+
+    impl From<items::History> for Route {
+        fn from(route: items::History) -> Route {
+            Route::Items(route.into())
+        }
+    }
+
+    impl From<items::Show> for Route {
+        fn from(route: items::Show) -> Route {
+            Route::Items(route.into())
+        }
+    }
+
+    impl From<items::Update> for Route {
+        fn from(route: items::Update) -> Route {
+            Route::Items(route.into())
+        }
+    }
+
+    impl From<people::Route> for Route {
+        fn from(route: people::Route) -> Route {
+            Route::People(route)
+        }
+    }
+
+    impl From<people::Edit> for Route {
+        fn from(route: people::Edit) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    impl From<people::New> for Route {
+        fn from(route: people::New) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    impl From<people::Newest> for Route {
+        fn from(route: people::Newest) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    impl From<people::Show> for Route {
+        fn from(route: people::Show) -> Route {
+            Route::People(route.into())
+        }
+    }
+
+    /// Routes and request structs that can be rendered as a path.
+    pub trait ToPath {
+        /// Write the path to this route, with its parameters filled in.
+        fn write_path<W: fmt::Write>(&self, w: &mut W) -> fmt::Result;
+
+        /// Make a path to this route with the given parameters.
+        fn to_path(&self) -> String {
+            let mut path = String::new();
+            self.write_path(&mut path)
+                .expect("writing to a String can't fail");
+            path
+        }
+    }
+
+    /// Makes links to routes.
+    ///
+    /// Configure it once with the base URL of the app and the prefix
+    /// it's mounted at, if any, then use it to link to any route.
+    ///
+    /// ```ignore
+    /// let urls = Urls::new()
+    ///     .base("https://example.com")
+    ///     .mount("/app");
+    ///
+    /// urls.link_to(&route, None); // /app/people
+    /// urls.url_for(&route, Some("top")); // https://example.com/app/people#top
+    /// ```
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct Urls {
+        base: String,
+        mount: String,
+    }
+
+    impl Urls {
+        /// Links with no base URL or mount prefix, that is, just paths.
+        pub fn new() -> Urls {
+            Urls::default()
+        }
+
+        /// Set the scheme and host for absolute URLs, such as
+        /// `https://example.com`.
+        pub fn base<S: Into<String>>(mut self, base: S) -> Urls {
+            let mut base = base.into();
+            while base.ends_with('/') {
+                base.pop();
+            }
+            self.base = base;
+            self
+        }
+
+        /// Set the prefix the app is mounted at, such as `/app`.  It's
+        /// added to the start of every link.
+        pub fn mount<S: AsRef<str>>(mut self, prefix: S) -> Urls {
+            let prefix = prefix.as_ref().trim_matches('/');
+            self.mount = if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("/{}", prefix)
+            };
+            self
+        }
+
+        /// Write a link to the route, relative to the host, with an
+        /// optional fragment.
+        pub fn write_link<T: ToPath, W: fmt::Write>(
+            &self,
+            w: &mut W,
+            route: &T,
+            fragment: Option<&str>,
+        ) -> fmt::Result {
+            w.write_str(&self.mount)?;
+            route.write_path(w)?;
+            if let Some(fragment) = fragment {
+                w.write_char('#')?;
+                write_encoded(w, &fragment)?;
+            }
+            Ok(())
+        }
+
+        /// Make a link to the route, relative to the host, such as
+        /// `/app/people/new`.
+        pub fn link_to<T: ToPath>(&self, route: &T, fragment: Option<&str>) -> String {
+            let mut link = String::new();
+            self.write_link(&mut link, route, fragment)
+                .expect("writing to a String can't fail");
+            link
+        }
+
+        /// Make an absolute URL for the route, such as
+        /// `https://example.com/app/people/new`.  Without a base URL
+        /// this is the same as `link_to`.
+        pub fn url_for<T: ToPath>(&self, route: &T, fragment: Option<&str>) -> String {
+            let mut url = self.base.clone();
+            self.write_link(&mut url, route, fragment)
+                .expect("writing to a String can't fail");
+            url
+        }
+    }
+
+    #[derive(PartialEq, Eq)]
+    pub enum Match<T> {
+        NotFound,
+        /// The path matches, but not the method.  Holds the methods
+        /// the route does allow.
+        NotAllowed(&'static [&'static str]),
+        Route(T),
+        Redirect(T),
+    }
+
+    use std::fmt;
+    impl<T: fmt::Debug> fmt::Debug for Match<T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Match::NotFound => write!(f, "Match::NotFound"),
+                Match::NotAllowed(m) => write!(f, "Match::NotAllowed({:?})", m),
+                Match::Route(t) => write!(f, "Match::Route({:?})", t),
+                Match::Redirect(t) => write!(f, "Match::Redirect({:?})", t),
+            }
+        }
+    }
+
+    /// Which part of the request a parameter came from.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ParamKind {
+        Path,
+        Query,
+        Body,
+    }
+
+    impl fmt::Display for ParamKind {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(match self {
+                ParamKind::Path => "path",
+                ParamKind::Query => "query",
+                ParamKind::Body => "body",
+            })
+        }
+    }
+
+    /// A parameter that failed to parse.
+    ///
+    /// There is a variant for each parameter, holding the error from
+    /// its type's `FromStr` implementation, the text that failed to
+    /// parse, and where in the request it came from.  A query or body
+    /// parameter that isn't valid UTF-8 once it's decoded is `NotUtf8`
+    /// instead, while a path parameter that isn't is not a match.
+    #[derive(Debug)]
+    pub enum Error {
+        /// The `slug` parameter is not a valid `String`.
+        Slug {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `id` parameter is not a valid `u32`.
+        Id {
+            kind: ParamKind,
+            text: String,
+            error: <u32 as std::str::FromStr>::Err,
+        },
+        /// The `x` parameter is not a valid `String`.
+        X {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The `number` parameter is not a valid `u64`.
+        Number {
+            kind: ParamKind,
+            text: String,
+            error: <u64 as std::str::FromStr>::Err,
+        },
+        /// The `note` parameter is not a valid `String`.
+        Note {
+            kind: ParamKind,
+            text: String,
+            error: <String as std::str::FromStr>::Err,
+        },
+        /// The query or body parameter named by `param` is not valid
+        /// UTF-8.  The text has each invalid sequence replaced with `U+FFFD`.
+        NotUtf8 {
+            param: &'static str,
+            kind: ParamKind,
+            text: String,
+        },
+    }
+
+    impl Error {
+        /// The name of the parameter that failed to parse.
+        pub fn param(&self) -> &'static str {
+            match *self {
+                Error::Slug { .. } => "slug",
+                Error::Id { .. } => "id",
+                Error::X { .. } => "x",
+                Error::Number { .. } => "number",
+                Error::Note { .. } => "note",
+                Error::NotUtf8 { param, .. } => param,
+            }
+        }
+
+        /// Whether the parameter was part of the path, the query or the body.
+        pub fn kind(&self) -> ParamKind {
+            match *self {
+                Error::Slug { kind, .. } => kind,
+                Error::Id { kind, .. } => kind,
+                Error::X { kind, .. } => kind,
+                Error::Number { kind, .. } => kind,
+                Error::Note { kind, .. } => kind,
+                Error::NotUtf8 { kind, .. } => kind,
+            }
+        }
+
+        /// The text that failed to parse.
+        pub fn text(&self) -> &str {
+            match *self {
+                Error::Slug { ref text, .. } => text,
+                Error::Id { ref text, .. } => text,
+                Error::X { ref text, .. } => text,
+                Error::Number { ref text, .. } => text,
+                Error::Note { ref text, .. } => text,
+                Error::NotUtf8 { ref text, .. } => text,
+            }
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(
+                f,
+                "Error parsing {} parameter '{}' from {:?}: ",
+                self.kind(),
+                self.param(),
+                self.text()
+            )?;
+            match *self {
+                Error::Slug { ref error, .. } => write!(f, "{:?}", error),
+                Error::Id { ref error, .. } => write!(f, "{:?}", error),
+                Error::X { ref error, .. } => write!(f, "{:?}", error),
+                Error::Number { ref error, .. } => write!(f, "{:?}", error),
+                Error::Note { ref error, .. } => write!(f, "{:?}", error),
+                Error::NotUtf8 { .. } => f.write_str("not valid UTF-8"),
+            }
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    /// Percent-encodes everything written to it except unreserved
+    /// characters, so it's safe in a path segment or query.
+    struct Encode<'a, W: fmt::Write>(&'a mut W);
+
+    impl<'a, W: fmt::Write> fmt::Write for Encode<'a, W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for &b in s.as_bytes() {
+                if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                    self.0.write_char(b as char)?;
+                } else {
+                    write!(self.0, "%{:02X}", b)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn write_encoded<W: fmt::Write, T: fmt::Display>(w: &mut W, value: &T) -> fmt::Result {
+        use std::fmt::Write;
+        write!(Encode(w), "{}", value)
+    }
+
+    fn from_hex(b: u8) -> Option<u8> {
+        match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' => Some(b - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    /// Decode percent-escapes, and in a query string, `+` as a space.
+    /// Invalid escapes are left as they are.  Only allocates if there
+    /// is something to decode.
+    fn decode(bytes: &[u8], is_query: bool) -> std::borrow::Cow<'_, [u8]> {
+        if !bytes.iter().any(|&b| b == b'%' || (is_query && b == b'+')) {
+            return std::borrow::Cow::Borrowed(bytes);
+        }
+
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'%' if i + 2 < bytes.len() => {
+                    match (from_hex(bytes[i + 1]), from_hex(bytes[i + 2])) {
+                        (Some(hi), Some(lo)) => {
+                            decoded.push(hi * 16 + lo);
+                            i += 3;
+                            continue;
+                        }
+                        _ => decoded.push(b'%'),
+                    }
+                }
+                b'+' if is_query => decoded.push(b' '),
+                b => decoded.push(b),
+            }
+            i += 1;
+        }
+        std::borrow::Cow::Owned(decoded)
+    }
+
+    /// Parse a path parameter, decoding it unless it's raw.  If it isn't
+    /// valid UTF-8 it's not a match, otherwise the error has the text.
+    fn parse_path<T: std::str::FromStr>(
+        text: &[u8],
+        raw: bool,
+    ) -> Option<Result<T, (String, T::Err)>> {
+        let text = if raw {
+            std::borrow::Cow::Borrowed(text)
+        } else {
+            decode(text, false)
+        };
+        let text = std::str::from_utf8(&text).ok()?;
+        Some(text.parse().map_err(|e| (text.to_string(), e)))
+    }
+
+    /// A problem finding a route by name or filling in its parameters.
+    #[derive(Debug)]
+    pub enum UrlError {
+        /// There is no route with this name.
+        UnknownRoute(String),
+        /// The route needs a parameter that wasn't given.
+        MissingParam {
+            route: &'static str,
+            param: &'static str,
+        },
+        /// A parameter was given that the route doesn't take.
+        UnknownParam {
+            route: &'static str,
+            param: String,
+        },
+        /// A parameter was given that failed to parse.
+        Param(Error),
+    }
+
+    impl fmt::Display for UrlError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                UrlError::UnknownRoute(ref name) => write!(f, "Unknown route '{}'", name),
+                UrlError::MissingParam { route, param } => {
+                    write!(f, "Missing parameter '{}' for route '{}'", param, route)
+                }
+                UrlError::UnknownParam { route, ref param } => {
+                    write!(f, "Unknown parameter '{}' for route '{}'", param, route)
+                }
+                UrlError::Param(ref error) => write!(f, "{}", error),
+            }
+        }
+    }
+
+    impl std::error::Error for UrlError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match *self {
+                UrlError::Param(ref error) => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    /// Find the action named `name` and fill it in from `params`.
+    ///
+    /// Actions are named by their module path and action name in
+    /// snake case, separated by dots, such as `people.show`.  Every
+    /// path parameter of the action must be given, query parameters are
+    /// optional, and anything else is an error.  Body parameters aren't
+    /// part of a URL, so they're left `None`.
+    pub fn route_for(name: &str, params: &[(&str, &str)]) -> Result<Route, UrlError> {
+        match name {
+            "extra" => {
+                check_params("extra", params, &["slug"])?;
+                let action = Extra {
+                    slug: required_param("extra", params, "slug", |text, error| Error::Slug {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "new" => {
+                check_params("new", params, &[])?;
+                Ok(New.into())
+            }
+            "show" => {
+                check_params("show", params, &["slug"])?;
+                let action = Show {
+                    slug: required_param("show", params, "slug", |text, error| Error::Slug {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "deep.ab" => {
+                check_params("deep.ab", params, &[])?;
+                Ok(deep::Ab.into())
+            }
+            "deep.c" => {
+                check_params("deep.c", params, &["x"])?;
+                let action = deep::C {
+                    x: required_param("deep.c", params, "x", |text, error| Error::X {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "items.edit" => {
+                check_params("items.edit", params, &["number"])?;
+                let action = items::Edit {
+                    number: required_param("items.edit", params, "number", |text, error| Error::Number {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "items.find" => {
+                check_params("items.find", params, &["slug"])?;
+                let action = items::Find {
+                    slug: required_param("items.find", params, "slug", |text, error| Error::Slug {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "items.history" => {
+                check_params("items.history", params, &["slug"])?;
+                let action = items::History {
+                    slug: required_param("items.history", params, "slug", |text, error| Error::Slug {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "items.show" => {
+                check_params("items.show", params, &["number"])?;
+                let action = items::Show {
+                    number: required_param("items.show", params, "number", |text, error| Error::Number {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "items.update" => {
+                check_params("items.update", params, &["slug"])?;
+                let action = items::Update {
+                    slug: required_param("items.update", params, "slug", |text, error| Error::Slug {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                    note: None,
+                };
+                Ok(action.into())
+            }
+            "people.edit" => {
+                check_params("people.edit", params, &["id"])?;
+                let action = people::Edit {
+                    id: required_param("people.edit", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            "people.new" => {
+                check_params("people.new", params, &[])?;
+                Ok(people::New.into())
+            }
+            "people.newest" => {
+                check_params("people.newest", params, &[])?;
+                Ok(people::Newest.into())
+            }
+            "people.show" => {
+                check_params("people.show", params, &["id"])?;
+                let action = people::Show {
+                    id: required_param("people.show", params, "id", |text, error| Error::Id {
+                        kind: ParamKind::Path,
+                        text,
+                        error,
+                    })?,
+                };
+                Ok(action.into())
+            }
+            _ => Err(UrlError::UnknownRoute(name.to_string())),
+        }
+    }
+
+    /// Make a path to the action named `name` with the given parameters,
+    /// for when the route isn't known until runtime, such as in a
+    /// template.  See [`route_for`] for the details.
+    ///
+    /// [`route_for`]: fn.route_for.html
+    pub fn url_for(name: &str, params: &[(&str, &str)]) -> Result<String, UrlError> {
+        route_for(name, params).map(|route| route.to_path())
+    }
+
+    fn check_params(
+        route: &'static str,
+        params: &[(&str, &str)],
+        known: &[&str],
+    ) -> Result<(), UrlError> {
+        for &(param, _) in params {
+            if !known.contains(&param) {
+                return Err(UrlError::UnknownParam {
+                    route,
+                    param: param.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn optional_param<T: std::str::FromStr>(
+        params: &[(&str, &str)],
+        name: &str,
+        error: fn(String, T::Err) -> Error,
+    ) -> Result<Option<T>, UrlError> {
+        match params.iter().find(|&&(param, _)| param == name) {
+            None => Ok(None),
+            Some(&(_, text)) => text
+                .parse()
+                .map(Some)
+                .map_err(|e| error(text.to_string(), e))
+                .map_err(UrlError::Param),
+        }
+    }
+
+    fn required_param<T: std::str::FromStr>(
+        route: &'static str,
+        params: &[(&str, &str)],
+        name: &'static str,
+        error: fn(String, T::Err) -> Error,
+    ) -> Result<T, UrlError> {
+        optional_param(params, name, error)?.ok_or(UrlError::MissingParam {
+            route,
+            param: name,
+        })
+    }
+
+    /// Match a path and method against this router.
+    ///
+    /// Accepts a byte slice for the request path and HTTP verb.
+    /// Returns a `Result`, usually `Ok` with the result of the
+    /// [`Match`].
+    ///
+    /// If the match was successful, it will be a `Match::Route` or
+    /// `Match::Redirect` with the parameters enclosed.  You can then
+    /// match on the [`Route`] to pass control of the request along to
+    /// a specific handler.
+    ///
+    /// If there is no match, this will return `Match::NotFound`
+    /// if no path matches (which you could return as `404 Not Found`),
+    /// or `Match::NotAllowed` if no method matches (in which case a
+    /// `405 Not Allowed` would be appropriate, with the methods it holds
+    /// in the `Allow` header).
+    ///
+    /// Static path segments take precedence over a parameter in the
+    /// same place.  If the rest of the path doesn't match after a static
+    /// segment, the parameter is tried instead.  A parameter matches a
+    /// whole segment, which can't be empty.  Parameters side by side are
+    /// tried in the order they're declared, and one that fails to parse
+    /// falls through to the next.
+    ///
+    /// Parameters are only parsed once the whole path matches, so a path
+    /// that doesn't match any route is `NotFound` even if a parameter in
+    /// it is invalid.  One that could fall through to the next is parsed
+    /// before the method is checked, and the rest after.  If the last
+    /// route parameter tried fails to parse, this will return `Err` with
+    /// the underlying parsing error.  Usually you'll want to send back a
+    /// `400 Bad Request` for that.
+    ///
+    /// Any bytes may be passed without panicking.  A path segment that
+    /// isn't valid UTF-8 once percent-decoded can't be a parameter, so
+    /// it's `Match::NotFound`.
+    ///
+    /// [`Match`]: enum.Match.html
+    /// [`Route`]: enum.Route.html
+    pub fn match_route<P: AsRef<[u8]>, M: AsRef<[u8]>>(
+        path: P,
+        method: M,
+    ) -> Result<Match<Route>, Error> {
+        match_path(path.as_ref(), b"", method.as_ref())
+    }
+
+    /// Match a request target and method against this router.
+    ///
+    /// The request target is a path with an optional query string, such
+    /// as `/people?lang=en`.  This works like [`match_route`], but query
+    /// parameters are also decoded and parsed from the query string.
+    ///
+    /// [`match_route`]: fn.match_route.html
+    pub fn match_request<T: AsRef<[u8]>, M: AsRef<[u8]>>(
+        target: T,
+        method: M,
+    ) -> Result<Match<Route>, Error> {
+        let target = target.as_ref();
+        match target.iter().position(|&b| b == b'?') {
+            Some(i) => match_path(&target[..i], &target[i + 1..], method.as_ref()),
+            None => match_path(target, b"", method.as_ref()),
+        }
+    }
+
+    fn match_path(path: &[u8], query: &[u8], method: &[u8]) -> Result<Match<Route>, Error> {
+        let len = path.len();
+        let mut i = if len > 0 && &path[0..1] == b"/" { 1 } else { 0 };
+
+        let start = i;
+        'fallback0: {
+            if i == len {
+                break 'fallback0;
+            }
+            match &path[i..i+1] {
+                b"d" => {
+                    i += 1;
+                    if i + 3 > len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+3] {
+                        b"eep" => {
+                            i += 3;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    let start = i;
+                    'fallback1: {
+                        if i == len {
+                            break 'fallback1;
+                        }
+                        match &path[i..i+1] {
+                            b"a" => {
+                                i += 1;
+                            },
+                            _ => break 'fallback1,
+                        }
+                        if i == len {
+                            break 'fallback1;
+                        }
+                        match &path[i..i+1] {
+                            b"/" => {
+                                i += 1;
+                            },
+                            _ => break 'fallback1,
+                        }
+                        if i == len {
+                            break 'fallback1;
+                        }
+                        match &path[i..i+1] {
+                            b"b" => {
+                                i += 1;
+                            },
+                            _ => break 'fallback1,
+                        }
+                        if i == len {
+                            match method {
+                                b"GET" => return Ok(Match::Route(Route::Deep(deep::Route::Ab(deep::Ab {
+                                })))),
+                                _ => return Ok(Match::NotAllowed(&["GET"])),
+                            }
+                        }
+                        match &path[i..i+1] {
+                            b"/" => {
+                                i += 1;
+                            },
+                            _ => break 'fallback1,
+                        }
+                        if i == len {
+                            match method {
+                                b"GET" => return Ok(Match::Route(Route::Deep(deep::Route::Ab(deep::Ab {
+                                })))),
+                                _ => return Ok(Match::NotAllowed(&["GET"])),
+                            }
+                        }
+                        break 'fallback1;
+                    }
+                    i = start;
+
+                    while i < len && &path[i..i+1] != b"/" {
+                        i += 1;
+                    }
+                    if i == start {
+                        break 'fallback0;
+                    }
+                    let param_x = &path[start..i];
+
+                    if i == len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+1] {
+                        b"c" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_x = match parse_path(param_x, false) {
+                                    Some(Ok(param_x)) => param_x,
+                                    Some(Err((text, error))) => return Err(Error::X {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::Deep(deep::Route::C(deep::C {
+                                    x: param_x,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_x = match parse_path(param_x, false) {
+                                    Some(Ok(param_x)) => param_x,
+                                    Some(Err((text, error))) => return Err(Error::X {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::Deep(deep::Route::C(deep::C {
+                                    x: param_x,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    break 'fallback0;
+                },
+                b"i" => {
+                    i += 1;
+                    if i + 4 > len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+4] {
+                        b"tems" => {
+                            i += 4;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    let start = i;
+                    'fallback1: {
+
+                        while i < len && &path[i..i+1] != b"/" {
+                            i += 1;
+                        }
+                        if i == start {
+                            break 'fallback1;
+                        }
+                        let param_number = &path[start..i];
+
+                        if i == len {
+                            let param_number = match parse_path(param_number, false) {
+                                Some(Ok(param_number)) => param_number,
+                                _ => break 'fallback1,
+                            };
+                            match method {
+                                b"GET" => return Ok(Match::Route(Route::Items(items::Route::Show(items::Show {
+                                    number: param_number,
+                                })))),
+                                _ => return Ok(Match::NotAllowed(&["GET"])),
+                            }
+                        }
+                        match &path[i..i+1] {
+                            b"/" => {
+                                i += 1;
+                            },
+                            _ => break 'fallback1,
+                        }
+                        if i == len {
+                            let param_number = match parse_path(param_number, false) {
+                                Some(Ok(param_number)) => param_number,
+                                _ => break 'fallback1,
+                            };
+                            match method {
+                                b"GET" => return Ok(Match::Route(Route::Items(items::Route::Show(items::Show {
+                                    number: param_number,
+                                })))),
+                                _ => return Ok(Match::NotAllowed(&["GET"])),
+                            }
+                        }
+                        if i + 4 > len {
+                            break 'fallback1;
+                        }
+                        match &path[i..i+4] {
+                            b"edit" => {
+                                i += 4;
+                            },
+                            _ => break 'fallback1,
+                        }
+                        if i == len {
+                            let param_number = match parse_path(param_number, false) {
+                                Some(Ok(param_number)) => param_number,
+                                _ => break 'fallback1,
+                            };
+                            match method {
+                                b"GET" => return Ok(Match::Route(Route::Items(items::Route::Edit(items::Edit {
+                                    number: param_number,
+                                })))),
+                                _ => return Ok(Match::NotAllowed(&["GET"])),
+                            }
+                        }
+                        match &path[i..i+1] {
+                            b"/" => {
+                                i += 1;
+                            },
+                            _ => break 'fallback1,
+                        }
+                        if i == len {
+                            let param_number = match parse_path(param_number, false) {
+                                Some(Ok(param_number)) => param_number,
+                                _ => break 'fallback1,
+                            };
+                            match method {
+                                b"GET" => return Ok(Match::Route(Route::Items(items::Route::Edit(items::Edit {
+                                    number: param_number,
+                                })))),
+                                _ => return Ok(Match::NotAllowed(&["GET"])),
+                            }
+                        }
+                        break 'fallback1;
+                    }
+                    i = start;
+
+                    while i < len && &path[i..i+1] != b"/" {
+                        i += 1;
+                    }
+                    if i == start {
+                        break 'fallback0;
+                    }
+                    let param_slug = &path[start..i];
+
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_slug = match parse_path(param_slug, false) {
+                                    Some(Ok(param_slug)) => param_slug,
+                                    Some(Err((text, error))) => return Err(Error::Slug {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::Items(items::Route::Find(items::Find {
+                                    slug: param_slug,
+                                }))));
+                            }
+                            b"POST" => {
+                                let param_slug = match parse_path(param_slug, false) {
+                                    Some(Ok(param_slug)) => param_slug,
+                                    Some(Err((text, error))) => return Err(Error::Slug {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::Items(items::Route::Update(items::Update {
+                                    slug: param_slug,
+                                    note: None,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET", "POST"])),
+                        }
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_slug = match parse_path(param_slug, false) {
+                                    Some(Ok(param_slug)) => param_slug,
+                                    Some(Err((text, error))) => return Err(Error::Slug {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::Items(items::Route::Find(items::Find {
+                                    slug: param_slug,
+                                }))));
+                            }
+                            b"POST" => {
+                                let param_slug = match parse_path(param_slug, false) {
+                                    Some(Ok(param_slug)) => param_slug,
+                                    Some(Err((text, error))) => return Err(Error::Slug {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::Items(items::Route::Update(items::Update {
+                                    slug: param_slug,
+                                    note: None,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET", "POST"])),
+                        }
+                    }
+                    if i + 7 > len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+7] {
+                        b"history" => {
+                            i += 7;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_slug = match parse_path(param_slug, false) {
+                                    Some(Ok(param_slug)) => param_slug,
+                                    Some(Err((text, error))) => return Err(Error::Slug {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::Items(items::Route::History(items::History {
+                                    slug: param_slug,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_slug = match parse_path(param_slug, false) {
+                                    Some(Ok(param_slug)) => param_slug,
+                                    Some(Err((text, error))) => return Err(Error::Slug {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::Items(items::Route::History(items::History {
+                                    slug: param_slug,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    break 'fallback0;
+                },
+                b"n" => {
+                    i += 1;
+                    if i + 2 > len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+2] {
+                        b"ew" => {
+                            i += 2;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => return Ok(Match::Route(Route::New(New {
+                            }))),
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => return Ok(Match::Route(Route::New(New {
+                            }))),
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    break 'fallback0;
+                },
+                b"p" => {
+                    i += 1;
+                    if i + 5 > len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+5] {
+                        b"eople" => {
+                            i += 5;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    let start = i;
+                    'fallback1: {
+                        if i + 3 > len {
+                            break 'fallback1;
+                        }
+                        match &path[i..i+3] {
+                            b"new" => {
+                                i += 3;
+                            },
+                            _ => break 'fallback1,
+                        }
+                        if i == len {
+                            match method {
+                                b"GET" => return Ok(Match::Route(Route::People(people::Route::New(people::New {
+                                })))),
+                                _ => return Ok(Match::NotAllowed(&["GET"])),
+                            }
+                        }
+                        match &path[i..i+1] {
+                            b"e" => {
+                                i += 1;
+                                if i + 2 > len {
+                                    break 'fallback1;
+                                }
+                                match &path[i..i+2] {
+                                    b"st" => {
+                                        i += 2;
+                                    },
+                                    _ => break 'fallback1,
+                                }
+                                if i == len {
+                                    match method {
+                                        b"GET" => return Ok(Match::Route(Route::People(people::Route::Newest(people::Newest {
+                                        })))),
+                                        _ => return Ok(Match::NotAllowed(&["GET"])),
+                                    }
+                                }
+                                match &path[i..i+1] {
+                                    b"/" => {
+                                        i += 1;
+                                    },
+                                    _ => break 'fallback1,
+                                }
+                                if i == len {
+                                    match method {
+                                        b"GET" => return Ok(Match::Route(Route::People(people::Route::Newest(people::Newest {
+                                        })))),
+                                        _ => return Ok(Match::NotAllowed(&["GET"])),
+                                    }
+                                }
+                                break 'fallback1;
+                            },
+                            b"/" => {
+                                i += 1;
+                                if i == len {
+                                    match method {
+                                        b"GET" => return Ok(Match::Route(Route::People(people::Route::New(people::New {
+                                        })))),
+                                        _ => return Ok(Match::NotAllowed(&["GET"])),
+                                    }
+                                }
+                                break 'fallback1;
+                            },
+                            _ => break 'fallback1,
+                        }
+                    }
+                    i = start;
+
+                    while i < len && &path[i..i+1] != b"/" {
+                        i += 1;
+                    }
+                    if i == start {
+                        break 'fallback0;
+                    }
+                    let param_id = &path[start..i];
+
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_id = match parse_path(param_id, false) {
+                                    Some(Ok(param_id)) => param_id,
+                                    Some(Err((text, error))) => return Err(Error::Id {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::People(people::Route::Show(people::Show {
+                                    id: param_id,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_id = match parse_path(param_id, false) {
+                                    Some(Ok(param_id)) => param_id,
+                                    Some(Err((text, error))) => return Err(Error::Id {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::People(people::Route::Show(people::Show {
+                                    id: param_id,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    if i + 4 > len {
+                        break 'fallback0;
+                    }
+                    match &path[i..i+4] {
+                        b"edit" => {
+                            i += 4;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_id = match parse_path(param_id, false) {
+                                    Some(Ok(param_id)) => param_id,
+                                    Some(Err((text, error))) => return Err(Error::Id {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::People(people::Route::Edit(people::Edit {
+                                    id: param_id,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    match &path[i..i+1] {
+                        b"/" => {
+                            i += 1;
+                        },
+                        _ => break 'fallback0,
+                    }
+                    if i == len {
+                        match method {
+                            b"GET" => {
+                                let param_id = match parse_path(param_id, false) {
+                                    Some(Ok(param_id)) => param_id,
+                                    Some(Err((text, error))) => return Err(Error::Id {
+                                        kind: ParamKind::Path,
+                                        text,
+                                        error,
+                                    }),
+                                    None => break 'fallback0,
+                                };
+                                return Ok(Match::Route(Route::People(people::Route::Edit(people::Edit {
+                                    id: param_id,
+                                }))));
+                            }
+                            _ => return Ok(Match::NotAllowed(&["GET"])),
+                        }
+                    }
+                    break 'fallback0;
+                },
+                _ => break 'fallback0,
+            }
+        }
+        i = start;
+
+        while i < len && &path[i..i+1] != b"/" {
+            i += 1;
+        }
+        if i == start {
+            return Ok(Match::NotFound);
+        }
+        let param_slug = &path[start..i];
+
+        if i == len {
+            match method {
+                b"GET" => {
+                    let param_slug = match parse_path(param_slug, false) {
+                        Some(Ok(param_slug)) => param_slug,
+                        Some(Err((text, error))) => return Err(Error::Slug {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    return Ok(Match::Route(Route::Show(Show {
+                        slug: param_slug,
+                    })));
+                }
+                _ => return Ok(Match::NotAllowed(&["GET"])),
+            }
+        }
+        match &path[i..i+1] {
+            b"/" => {
+                i += 1;
+            },
+            _ => return Ok(Match::NotFound),
+        }
+        if i == len {
+            match method {
+                b"GET" => {
+                    let param_slug = match parse_path(param_slug, false) {
+                        Some(Ok(param_slug)) => param_slug,
+                        Some(Err((text, error))) => return Err(Error::Slug {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    return Ok(Match::Route(Route::Show(Show {
+                        slug: param_slug,
+                    })));
+                }
+                _ => return Ok(Match::NotAllowed(&["GET"])),
+            }
+        }
+        if i + 5 > len {
+            return Ok(Match::NotFound);
+        }
+        match &path[i..i+5] {
+            b"extra" => {
+                i += 5;
+            },
+            _ => return Ok(Match::NotFound),
+        }
+        if i == len {
+            match method {
+                b"GET" => {
+                    let param_slug = match parse_path(param_slug, false) {
+                        Some(Ok(param_slug)) => param_slug,
+                        Some(Err((text, error))) => return Err(Error::Slug {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    return Ok(Match::Route(Route::Extra(Extra {
+                        slug: param_slug,
+                    })));
+                }
+                _ => return Ok(Match::NotAllowed(&["GET"])),
+            }
+        }
+        match &path[i..i+1] {
+            b"/" => {
+                i += 1;
+            },
+            _ => return Ok(Match::NotFound),
+        }
+        if i == len {
+            match method {
+                b"GET" => {
+                    let param_slug = match parse_path(param_slug, false) {
+                        Some(Ok(param_slug)) => param_slug,
+                        Some(Err((text, error))) => return Err(Error::Slug {
+                            kind: ParamKind::Path,
+                            text,
+                            error,
+                        }),
+                        None => return Ok(Match::NotFound),
+                    };
+                    return Ok(Match::Route(Route::Extra(Extra {
+                        slug: param_slug,
+                    })));
+                }
+                _ => return Ok(Match::NotAllowed(&["GET"])),
+            }
+        }
+        return Ok(Match::NotFound);
+    }
+
+    /// The longest request line `match_request_line` accepts, in bytes.
+    pub const MAX_REQUEST_LINE_LEN: usize = 8192;
+
+    /// The longest method `match_request_line` accepts, in bytes.
+    pub const MAX_METHOD_LEN: usize = 16;
+
+    /// A request line that couldn't be matched.
+    #[derive(Debug)]
+    pub enum RequestLineError {
+        /// The line is longer than `MAX_REQUEST_LINE_LEN`.
+        TooLong,
+        /// The line isn't three parts separated by spaces.
+        Malformed,
+        /// The method is empty, too long, or not a valid token.
+        BadMethod,
+        /// The request target isn't a path with an optional query, or
+        /// has characters that aren't allowed.
+        BadTarget,
+        /// The version isn't `HTTP/1.0` or `HTTP/1.1`.
+        BadVersion,
+        /// A parameter failed to parse.
+        Param(Error),
+    }
+
+    impl fmt::Display for RequestLineError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                RequestLineError::TooLong => write!(f, "Request line is longer than {} bytes", MAX_REQUEST_LINE_LEN),
+                RequestLineError::Malformed => f.write_str("Malformed request line"),
+                RequestLineError::BadMethod => f.write_str("Invalid method in request line"),
+                RequestLineError::BadTarget => f.write_str("Invalid request target in request line"),
+                RequestLineError::BadVersion => f.write_str("Unsupported HTTP version in request line"),
+                RequestLineError::Param(ref error) => write!(f, "{}", error),
+            }
+        }
+    }
+
+    impl std::error::Error for RequestLineError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match *self {
+                RequestLineError::Param(ref error) => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    fn is_token_char(b: u8) -> bool {
+        b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+    }
+
+    /// Parse the first line of an HTTP/1.x request, such as
+    /// `GET /people?lang=en HTTP/1.1`, and match it against this router.
+    ///
+    /// The line may end with `\r\n`.  The method must be a token of at
+    /// most `MAX_METHOD_LEN` bytes, the request target must be a path
+    /// starting with `/` of visible ASCII, and the whole line may be at
+    /// most `MAX_REQUEST_LINE_LEN` bytes.  Parsing the line doesn't
+    /// allocate, though parameters may when they're decoded and parsed.
+    pub fn match_request_line<L: AsRef<[u8]>>(line: L) -> Result<Match<Route>, RequestLineError> {
+        let mut line = line.as_ref();
+        if line.ends_with(b"\r\n") {
+            line = &line[..line.len() - 2];
+        }
+        if line.len() > MAX_REQUEST_LINE_LEN {
+            return Err(RequestLineError::TooLong);
+        }
+
+        let method_end = match line.iter().position(|&b| b == b' ') {
+            Some(i) => i,
+            None => return Err(RequestLineError::Malformed),
+        };
+        let target_end = match line.iter().rposition(|&b| b == b' ') {
+            Some(i) if i > method_end => i,
+            _ => return Err(RequestLineError::Malformed),
+        };
+        let method = &line[..method_end];
+        let target = &line[method_end + 1..target_end];
+        let version = &line[target_end + 1..];
+
+        if method.is_empty()
+            || method.len() > MAX_METHOD_LEN
+            || !method.iter().all(|&b| is_token_char(b))
+        {
+            return Err(RequestLineError::BadMethod);
+        }
+        if target.first() != Some(&b'/')
+            || !target.iter().all(|&b| b.is_ascii_graphic() && b != b'#')
+        {
+            return Err(RequestLineError::BadTarget);
+        }
+        if version != b"HTTP/1.1" && version != b"HTTP/1.0" {
+            return Err(RequestLineError::BadVersion);
+        }
+
+        let (path, query) = match target.iter().position(|&b| b == b'?') {
+            Some(i) => (&target[..i], &target[i + 1..]),
+            None => (target, &b""[..]),
+        };
+        match_path(path, query, method).map_err(RequestLineError::Param)
+    }
+
+} // mod routes
@@ -0,0 +1,502 @@
+use std::path::PathBuf;
+
+use wayfinder::build::{BuildError, Builder, Job};
+use wayfinder::gen::CodegenOptions;
+use wayfinder::{get, RouteConfig, Routes};
+
+fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("wayfinder-tests");
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    dir.join(name)
+}
+
+fn index_config() -> RouteConfig {
+    RouteConfig {
+        headers: vec![],
+        routes: Routes {
+            resources: vec![get!(Index)],
+            ..Routes::default()
+        },
+    }
+}
+
+#[test]
+fn test_try_build_config() {
+    let output_file = temp_path("try_build_config.rs");
+
+    let output = Builder::new()
+        .input_config(index_config())
+        .output_file(&output_file)
+        .try_build()
+        .expect("build");
+
+    assert_eq!(output.jobs.len(), 1);
+    assert_eq!(output.jobs[0].module_name, "routes");
+    assert_eq!(output.jobs[0].output_file, Some(output_file.clone()));
+    assert_eq!(output.jobs[0].diagnostics, vec![]);
+
+    let mut expected = vec![];
+    wayfinder::gen::codegen(&mut expected, &index_config()).unwrap();
+    let actual = std::fs::read(&output_file).expect("read output");
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_try_build_file() {
+    let input_file = temp_path("try_build_file.routes");
+    let output_file = temp_path("try_build_file.rs");
+    std::fs::write(&input_file, "/\n  GET Index\n").expect("write input");
+
+    let output = Builder::new()
+        .input_file(&input_file)
+        .output_file(&output_file)
+        .try_build()
+        .expect("build");
+
+    assert_eq!(output.jobs[0].output_file, Some(output_file));
+}
+
+#[test]
+fn test_try_build_missing_input() {
+    match Builder::new().output_stdout().try_build() {
+        Err(BuildError::MissingInput) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_missing_output() {
+    match Builder::new().input_config(index_config()).try_build() {
+        Err(BuildError::MissingOutput) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_missing_file() {
+    let input_file = temp_path("does_not_exist.routes");
+
+    match Builder::new()
+        .input_file(&input_file)
+        .output_stdout()
+        .try_build()
+    {
+        Err(BuildError::Io { path, .. }) => assert_eq!(path, input_file),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_parse_error() {
+    let input_file = temp_path("try_build_parse_error.routes");
+    std::fs::write(&input_file, "/\n  {id Uuid}\n    GET Index\n").expect("write input");
+
+    match Builder::new()
+        .input_file(&input_file)
+        .output_stdout()
+        .try_build()
+    {
+        Err(BuildError::Parse { path, message }) => {
+            assert_eq!(path, input_file);
+            assert!(
+                message.contains("Expected resources or child routes"),
+                "message: {}",
+                message
+            );
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_validation_error() {
+    let config = RouteConfig {
+        headers: vec![],
+        routes: Routes {
+            resources: vec![get!(Index), get!(Home)],
+            ..Routes::default()
+        },
+    };
+    let output_file = temp_path("try_build_validation_error.rs");
+    let _ = std::fs::remove_file(&output_file);
+
+    match Builder::new()
+        .input_config(config)
+        .output_file(&output_file)
+        .try_build()
+    {
+        Err(BuildError::Validation(diagnostics)) => assert_eq!(diagnostics.len(), 1),
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    assert!(!output_file.exists());
+}
+
+#[test]
+fn test_try_build_unwritable_output() {
+    let output_file = temp_path("no/such/dir/routes.rs");
+
+    match Builder::new()
+        .input_config(index_config())
+        .output_file(&output_file)
+        .try_build()
+    {
+        Err(BuildError::Io { path, .. }) => assert_eq!(path, output_file),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_module_name() {
+    let output_file = temp_path("try_build_module_name.rs");
+
+    Builder::new()
+        .input_config(index_config())
+        .output_file(&output_file)
+        .module_name("admin")
+        .visibility("pub(crate)")
+        .try_build()
+        .expect("build");
+
+    let mut expected = vec![];
+    let options = CodegenOptions::new()
+        .module_name("admin")
+        .visibility("pub(crate)");
+    wayfinder::gen::codegen_with_options(&mut expected, &index_config(), &options).unwrap();
+    let actual = String::from_utf8(std::fs::read(&output_file).expect("read output")).unwrap();
+    assert_eq!(actual, String::from_utf8(expected).unwrap());
+    assert!(actual.starts_with("pub(crate) mod admin {\n"));
+    assert!(actual.ends_with("} // mod admin\n"));
+}
+
+#[test]
+fn test_try_build_invalid_module_name() {
+    match Builder::new()
+        .input_config(index_config())
+        .output_stdout()
+        .module_name("not a name")
+        .try_build()
+    {
+        Err(BuildError::Codegen(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_handlers_and_async_handlers() {
+    match Builder::new()
+        .input_config(index_config())
+        .output_stdout()
+        .handlers(true)
+        .async_handlers(true)
+        .try_build()
+    {
+        Err(BuildError::Codegen(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_borrowed_and_normalized() {
+    match Builder::new()
+        .input_config(index_config())
+        .output_stdout()
+        .borrowed(true)
+        .collapse_slashes(true)
+        .try_build()
+    {
+        Err(BuildError::Codegen(e)) => {
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+            assert!(e.to_string().contains("normalisation"));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_no_std_and_http() {
+    match Builder::new()
+        .input_config(index_config())
+        .output_stdout()
+        .no_std(true)
+        .http(true)
+        .try_build()
+    {
+        Err(BuildError::Codegen(e)) => {
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+            assert!(e.to_string().contains("without std"));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_ignore_case_conflict() {
+    let input_file = temp_path("try_build_ignore_case_conflict.routes");
+    std::fs::write(
+        &input_file,
+        "/\n  People\n    GET Upper\n  people\n    GET Lower\n",
+    )
+    .expect("write input");
+
+    Builder::new()
+        .input_file(&input_file)
+        .output_stdout()
+        .try_build()
+        .expect("build");
+
+    match Builder::new()
+        .input_file(&input_file)
+        .output_stdout()
+        .ignore_case(true)
+        .try_build()
+    {
+        Err(BuildError::Codegen(e)) => {
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+            assert!(e.to_string().contains("ignoring case"));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_build_jobs() {
+    let input_file = temp_path("try_build_jobs.routes");
+    let public_file = temp_path("try_build_jobs_public.rs");
+    let admin_file = temp_path("try_build_jobs_admin.rs");
+    std::fs::write(&input_file, "/\n  GET Index\n").expect("write input");
+
+    let output = Builder::new()
+        .job(Job::from_file(&input_file, &public_file, "public_api"))
+        .job(Job::from_config(index_config(), &admin_file, "admin_api").visibility(""))
+        .try_build()
+        .expect("build");
+
+    let names = output
+        .jobs
+        .iter()
+        .map(|job| job.module_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["public_api", "admin_api"]);
+
+    let public = std::fs::read_to_string(&public_file).expect("read public");
+    assert!(public.starts_with("pub mod public_api {\n"));
+    let admin = std::fs::read_to_string(&admin_file).expect("read admin");
+    assert!(admin.starts_with("mod admin_api {\n"));
+}
+
+#[test]
+fn test_try_build_jobs_same_output() {
+    let output_file = temp_path("try_build_jobs_same_output.rs");
+
+    Builder::new()
+        .input_config(index_config())
+        .output_file(&output_file)
+        .job(Job::from_config(index_config(), &output_file, "admin"))
+        .try_build()
+        .expect("build");
+
+    let actual = std::fs::read_to_string(&output_file).expect("read output");
+    assert!(actual.starts_with("pub mod routes {\n"));
+    assert!(actual.contains("\n} // mod routes\npub mod admin {\n"));
+}
+
+#[test]
+fn test_try_build_jobs_fail_together() {
+    let output_file = temp_path("try_build_jobs_fail_together.rs");
+    let _ = std::fs::remove_file(&output_file);
+
+    let result = Builder::new()
+        .job(Job::from_config(index_config(), &output_file, "good"))
+        .job(Job::from_file(
+            temp_path("does_not_exist.routes"),
+            "bad.rs",
+            "bad",
+        ))
+        .try_build();
+
+    match result {
+        Err(BuildError::Io { .. }) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(!output_file.exists());
+}
+
+#[test]
+fn test_try_build_write_if_changed() {
+    let output_file = temp_path("try_build_write_if_changed.rs");
+    let _ = std::fs::remove_file(&output_file);
+
+    let build = || {
+        Builder::new()
+            .input_config(index_config())
+            .output_file(&output_file)
+            .try_build()
+            .expect("build")
+    };
+
+    assert!(build().jobs[0].changed);
+    assert!(!build().jobs[0].changed);
+
+    std::fs::write(&output_file, "// stale").expect("write output");
+    assert!(build().jobs[0].changed);
+    assert!(!build().jobs[0].changed);
+}
+
+#[test]
+fn test_try_build_format() {
+    let output_file = temp_path("try_build_format.rs");
+    let config = wayfinder::parse::route_config(include_str!("../cases/example/routes.routes"))
+        .unwrap()
+        .1;
+
+    Builder::new()
+        .input_config(config)
+        .output_file(&output_file)
+        .format(true)
+        .try_build()
+        .expect("build");
+
+    let actual = std::fs::read_to_string(&output_file).expect("read output");
+    assert!(actual.starts_with("pub mod routes {\n    //! Application route configuration.\n"));
+    assert!(actual
+        .contains("\n        let mut i = if len > 0 && &path[0..1] == b\"/\" { 1 } else { 0 };\n"));
+    assert!(!actual.contains("\n\n\n"));
+}
+
+#[test]
+fn test_try_build_unknown_type() {
+    let input_file = temp_path("try_build_unknown_type.routes");
+    let output_file = temp_path("try_build_unknown_type.rs");
+    std::fs::write(&input_file, "/\n  {id: Uuid}\n    GET Show\n").expect("write input");
+
+    let output = Builder::new()
+        .input_file(&input_file)
+        .output_file(&output_file)
+        .try_build()
+        .expect("build");
+    let diagnostics = &output.jobs[0].diagnostics;
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        format!(
+            "{}:2:8: warning: cannot find type `Uuid` for parameter `id` (at /{{id: Uuid}})",
+            input_file.display()
+        )
+    );
+
+    let output = Builder::new()
+        .input_file(&input_file)
+        .output_file(&output_file)
+        .check_types(false)
+        .try_build()
+        .expect("build");
+    assert_eq!(output.jobs[0].diagnostics, vec![]);
+}
+
+#[test]
+fn test_try_build_handler_names() {
+    let input_file = temp_path("try_build_handler_names.routes");
+    let output_file = temp_path("try_build_handler_names.rs");
+    std::fs::write(
+        &input_file,
+        "/\n  GET NotFound\n  POST Routes\n  people\n    GET People::ShowAll\n  all\n    GET PeopleShow::All\n",
+    )
+    .expect("write input");
+
+    let job = || Job::from_file(&input_file, &output_file, "routes");
+    let messages = |job: Job| match Builder::new().job(job).try_build() {
+        Err(BuildError::Validation(diagnostics)) => diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>(),
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    // only clashes once there are handlers named for the actions
+    Builder::new().job(job()).try_build().expect("build");
+    assert_eq!(
+        messages(job().handlers(true)),
+        vec![format!(
+            "{}:7:5: error: action `PeopleShow::All` has the same handler name `people_show_all` as `People::ShowAll` (at /all)",
+            input_file.display()
+        )]
+    );
+
+    // or which clash with the methods for unmatched requests
+    assert_eq!(
+        messages(job().async_handlers(true)),
+        vec![
+            format!(
+                "{}:2:3: error: action `NotFound` has the handler name `not_found`, which `AsyncHandlers` already uses (at /)",
+                input_file.display()
+            ),
+            format!(
+                "{}:7:5: error: action `PeopleShow::All` has the same handler name `people_show_all` as `People::ShowAll` (at /all)",
+                input_file.display()
+            ),
+        ]
+    );
+    assert_eq!(
+        messages(job().tower(true)),
+        vec![
+            format!(
+                "{}:2:3: error: action `NotFound` has the handler name `not_found`, which the tower `Router` already uses (at /)",
+                input_file.display()
+            ),
+            format!(
+                "{}:3:3: error: action `Routes` has the handler name `routes`, which the tower `Router` already uses (at /)",
+                input_file.display()
+            ),
+            format!(
+                "{}:7:5: error: action `PeopleShow::All` has the same handler name `people_show_all` as `People::ShowAll` (at /all)",
+                input_file.display()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_try_build_line_map() {
+    let input_file = temp_path("try_build_line_map.routes");
+    let output_file = temp_path("try_build_line_map.rs");
+    std::fs::write(
+        &input_file,
+        "/\n  {id: usize}\n    GET Show\n      [q: String]\n",
+    )
+    .expect("write input");
+
+    let build = |line_map| {
+        Builder::new()
+            .input_file(&input_file)
+            .output_file(&output_file)
+            .line_map(line_map)
+            .try_build()
+            .expect("build");
+        std::fs::read_to_string(&output_file).expect("read output")
+    };
+
+    let actual = build(true);
+    let declared = |line, column| {
+        format!(
+            "/// Declared at `{}:{}:{}`.\n",
+            input_file.display(),
+            line,
+            column
+        )
+    };
+    assert!(actual.contains(&format!(
+        "    ///\n    {}    #[derive(Debug, PartialEq, Eq)]\n    pub struct Show {{\n",
+        declared(3, 5)
+    )));
+    assert!(actual.contains(&format!(
+        "        {}        pub id: usize,\n",
+        declared(2, 8)
+    )));
+    assert!(actual.contains(&format!(
+        "        {}        pub q: Option<String>,\n",
+        declared(4, 11)
+    )));
+
+    assert!(!build(false).contains("Declared at"));
+}
@@ -0,0 +1,283 @@
+//! Dispatching routes to handlers and `tower` services.
+
+use crate::jobs::{admin_api, public_api};
+
+#[test]
+fn test_dispatch() {
+    struct App;
+
+    impl admin_api::Handlers for App {
+        type Output = String;
+
+        fn index(&self, _: admin_api::Index) -> String {
+            "index".to_string()
+        }
+
+        fn admin_people_show(&self, action: admin_api::admin::people::Show) -> String {
+            format!("show {}", action.id)
+        }
+    }
+
+    let dispatch = |path| match admin_api::match_route(path, b"GET") {
+        Ok(admin_api::Match::Route(route)) => admin_api::dispatch(&App, route),
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    let id = uuid::Uuid::nil();
+    assert_eq!(dispatch("/".to_string()), "index");
+    assert_eq!(dispatch(format!("/{}", id)), format!("show {}", id));
+}
+
+/// Run a future to completion on this thread.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct Unpark(std::thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+#[test]
+fn test_tower_router() {
+    use public_api::{people, BadRequest, NotAllowed, Route, Routed, Router};
+    use tower::{service_fn, ServiceExt};
+
+    fn call<S>(router: S, method: http::Method, path: &str) -> Result<S::Response, S::Error>
+    where
+        S: tower::Service<http::Request<()>>,
+    {
+        let request = http::Request::builder()
+            .method(method)
+            .uri(path)
+            .body(())
+            .unwrap();
+        block_on(router.oneshot(request))
+    }
+
+    let router = Router::<(), String>::default()
+        .people_show(service_fn(|routed: Routed<people::Show, ()>| async move {
+            Ok::<_, std::convert::Infallible>(http::Response::new(format!(
+                "show {}",
+                routed.route.id
+            )))
+        }))
+        .routes(service_fn(|routed: Routed<Route, ()>| async move {
+            match routed.route {
+                Route::People(people::Route::Index(_)) => Ok(http::Response::new("index".into())),
+                _ => Err("no handler"),
+            }
+        }));
+
+    let id = uuid::Uuid::nil();
+    let show = call(
+        router.clone(),
+        http::Method::GET,
+        &format!("/people/{}", id),
+    )
+    .unwrap();
+    assert_eq!(show.status(), http::StatusCode::OK);
+    assert_eq!(show.body(), &format!("show {}", id));
+
+    let index = call(router.clone(), http::Method::GET, "/people").unwrap();
+    assert_eq!(index.body(), "index");
+
+    let redirect = call(router.clone(), http::Method::GET, "/users").unwrap();
+    assert_eq!(redirect.status(), http::StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(redirect.headers()[http::header::LOCATION], "/people");
+    assert_eq!(redirect.body(), "");
+
+    let not_found = call(router.clone(), http::Method::GET, "/nowhere").unwrap();
+    assert_eq!(not_found.status(), http::StatusCode::NOT_FOUND);
+
+    let not_allowed = call(router.clone(), http::Method::PATCH, "/people").unwrap();
+    assert_eq!(not_allowed.status(), http::StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(not_allowed.headers()[http::header::ALLOW], "GET, POST");
+
+    let bad_request = call(router.clone(), http::Method::GET, "/people/oops").unwrap();
+    assert_eq!(bad_request.status(), http::StatusCode::BAD_REQUEST);
+
+    let error = call(router.clone(), http::Method::GET, "/books").unwrap_err();
+    assert_eq!(error.to_string(), "no handler");
+
+    // without a service of its own or for all routes, a route isn't found
+    let unrouted = call(
+        Router::<(), String>::default(),
+        http::Method::GET,
+        "/people",
+    )
+    .unwrap();
+    assert_eq!(unrouted.status(), http::StatusCode::NOT_FOUND);
+
+    let router = router.not_found(service_fn(|request: http::Request<()>| async move {
+        Ok::<_, std::convert::Infallible>(http::Response::new(format!("no {}", request.uri())))
+    }));
+    let not_found = call(router.clone(), http::Method::GET, "/nowhere").unwrap();
+    assert_eq!(not_found.body(), "no /nowhere");
+    let not_allowed = call(router.clone(), http::Method::PATCH, "/people").unwrap();
+    assert_eq!(not_allowed.status(), http::StatusCode::METHOD_NOT_ALLOWED);
+
+    let router = router.not_allowed(service_fn(|not_allowed: NotAllowed<()>| async move {
+        Ok::<_, std::convert::Infallible>(http::Response::new(not_allowed.methods.join(" or ")))
+    }));
+    let not_allowed = call(router.clone(), http::Method::PATCH, "/people").unwrap();
+    assert_eq!(not_allowed.body(), "GET or POST");
+    let bad_request = call(router.clone(), http::Method::GET, "/people/oops").unwrap();
+    assert_eq!(bad_request.status(), http::StatusCode::BAD_REQUEST);
+
+    let router = router.bad_request(service_fn(|bad_request: BadRequest<()>| async move {
+        Ok::<_, std::convert::Infallible>(http::Response::new(
+            bad_request.error.param().to_string(),
+        ))
+    }));
+    let bad_request = call(router.clone(), http::Method::GET, "/people/oops").unwrap();
+    assert_eq!(bad_request.body(), "id");
+    let not_found = call(router, http::Method::GET, "/nowhere").unwrap();
+    assert_eq!(not_found.body(), "no /nowhere");
+}
+
+#[test]
+fn test_async_dispatch() {
+    use public_api::{books, dispatch, people, AsyncHandlers, Error, Request, Route};
+
+    struct App;
+
+    impl AsyncHandlers for App {
+        type Output = String;
+
+        async fn not_found(&self) -> String {
+            "not found".to_string()
+        }
+
+        async fn not_allowed(&self, methods: &'static [&'static str]) -> String {
+            format!("not allowed, try {}", methods.join(" or "))
+        }
+
+        async fn redirect(&self, route: Route) -> String {
+            use public_api::ToPath;
+            format!("redirect to {}", route.to_path())
+        }
+
+        async fn bad_request(&self, error: Error) -> String {
+            format!("bad {}", error.param())
+        }
+
+        async fn people_index(&self, action: people::Index) -> String {
+            format!("people in {:?}", action.lang)
+        }
+
+        async fn people_create(&self, _: people::Create) -> String {
+            "create person".to_string()
+        }
+
+        async fn people_new(&self, _: people::New) -> String {
+            "new person".to_string()
+        }
+
+        async fn people_show(&self, action: people::Show) -> String {
+            format!("show person {}", action.id)
+        }
+
+        async fn people_update(&self, action: people::Update) -> String {
+            format!("update person {} to {:?}", action.id, action.name)
+        }
+
+        async fn people_destroy(&self, _: people::Destroy) -> String {
+            "destroy person".to_string()
+        }
+
+        async fn people_edit(&self, _: people::Edit) -> String {
+            "edit person".to_string()
+        }
+
+        async fn books_index(&self, _: books::Index) -> String {
+            "books".to_string()
+        }
+
+        async fn books_create(&self, action: books::Create) -> String {
+            format!("create book {:?}", action.title)
+        }
+
+        async fn books_new(&self, _: books::New) -> String {
+            "new book".to_string()
+        }
+
+        async fn books_show(&self, _: books::Show) -> String {
+            "show book".to_string()
+        }
+
+        async fn books_update(&self, _: books::Update) -> String {
+            "update book".to_string()
+        }
+
+        async fn books_destroy(&self, _: books::Destroy) -> String {
+            "destroy book".to_string()
+        }
+
+        async fn books_edit(&self, _: books::Edit) -> String {
+            "edit book".to_string()
+        }
+
+        async fn files_show(&self, action: public_api::files::Show) -> String {
+            format!("file {}", action.file)
+        }
+    }
+
+    let dispatch = |request: Request| block_on(dispatch(&App, request));
+
+    let id = uuid::Uuid::nil();
+    let person = format!("/people/{}", id);
+    assert_eq!(
+        dispatch(Request::new(b"GET", b"/people?lang=en")),
+        "people in Some(\"en\")"
+    );
+    assert_eq!(
+        dispatch(Request::new(
+            b"PUT",
+            format!("{}?name=Ann+Lee", person).as_bytes()
+        )),
+        format!("update person {} to Some(\"Ann Lee\")", id)
+    );
+    // form fields are only read for body parameters
+    assert_eq!(
+        dispatch(Request::new(b"PUT", person.as_bytes()).form(b"name=Bo")),
+        format!("update person {} to None", id)
+    );
+    assert_eq!(
+        dispatch(Request::new(b"POST", b"/books").form(b"title=Middle+march")),
+        "create book Some(\"Middle march\")"
+    );
+    assert_eq!(
+        dispatch(Request::new(b"POST", b"/books?title=Emma")),
+        "create book None"
+    );
+    assert_eq!(
+        dispatch(Request::new(b"POST", b"/books").form(b"title=%FF")),
+        "bad title"
+    );
+    assert_eq!(dispatch(Request::new(b"GET", b"/books/new")), "new book");
+    assert_eq!(
+        dispatch(Request::new(b"GET", b"/users?lang=fr")),
+        "redirect to /people?lang=fr"
+    );
+    assert_eq!(dispatch(Request::new(b"GET", b"/nowhere")), "not found");
+    assert_eq!(
+        dispatch(Request::new(b"PATCH", b"/people/new")),
+        "not allowed, try GET"
+    );
+    assert_eq!(dispatch(Request::new(b"GET", b"/people/oops")), "bad id");
+}
@@ -0,0 +1,209 @@
+//! Throw arbitrary bytes at the generated routers, which must never panic.
+
+use proptest::prelude::*;
+
+use crate::jobs::{
+    admin_api, borrowed_api, borrowed_siblings_api, commons_api, locals_api, no_std_api,
+    normalized_api, public_api, siblings_api, slashes_api,
+};
+
+/// Bytes that look enough like the routes in `cases/` to get deep into
+/// the matchers: path pieces, separators, escapes and invalid UTF-8.
+fn path_bytes() -> impl Strategy<Value = Vec<u8>> {
+    let piece = prop_oneof![
+        Just(b"/".to_vec()),
+        Just(b"people".to_vec()),
+        Just(b"books".to_vec()),
+        Just(b"new".to_vec()),
+        Just(b"edit".to_vec()),
+        Just(b"users".to_vec()),
+        Just(b"foo".to_vec()),
+        Just(b"fomo".to_vec()),
+        Just(b"foobar".to_vec()),
+        Just(b"items".to_vec()),
+        Just(b"history".to_vec()),
+        Just(b"deep".to_vec()),
+        Just(b"a".to_vec()),
+        Just(b"b".to_vec()),
+        Just(b"strict".to_vec()),
+        Just(b"redirect".to_vec()),
+        Just(b"files".to_vec()),
+        Just(b"PEOPLE".to_vec()),
+        Just(b"7".to_vec()),
+        Just(b"18446744073709551616".to_vec()),
+        Just(b".".to_vec()),
+        Just(b"..".to_vec()),
+        Just(b"%2E".to_vec()),
+        Just(b"00000000-0000-0000-0000-000000000000".to_vec()),
+        Just(b"?".to_vec()),
+        Just(b"&".to_vec()),
+        Just(b"=".to_vec()),
+        Just(b"%".to_vec()),
+        Just(b"%FF".to_vec()),
+        Just(b"%C3%28".to_vec()),
+        Just(b"lang".to_vec()),
+        Just(b"name".to_vec()),
+        Just(vec![0xFF]),
+        Just(vec![0xC3, 0x28]),
+        Just(vec![0xE2, 0x82]),
+        proptest::collection::vec(any::<u8>(), 0..4),
+    ];
+    proptest::collection::vec(piece, 0..12).prop_map(|pieces| pieces.concat())
+}
+
+fn method_bytes() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        Just(b"GET".to_vec()),
+        Just(b"POST".to_vec()),
+        Just(b"PUT".to_vec()),
+        Just(b"DELETE".to_vec()),
+        proptest::collection::vec(any::<u8>(), 0..8),
+    ]
+}
+
+/// Match against every router, which must not panic.
+fn match_routes(path: &[u8], method: &[u8]) {
+    let _ = public_api::match_route(path, method);
+    let _ = admin_api::match_route(path, method);
+    let _ = commons_api::match_route(path, method);
+    let _ = slashes_api::match_route(path, method);
+    let _ = normalized_api::match_route(path, method);
+    let _ = siblings_api::match_route(path, method);
+    let _ = locals_api::match_route(path, method);
+    let _ = borrowed_api::match_route(path, method);
+    let _ = borrowed_siblings_api::match_route(path, method);
+    let _ = no_std_api::match_route(path, method);
+}
+
+fn match_requests(target: &[u8], method: &[u8]) {
+    let _ = public_api::match_request(target, method);
+    let _ = admin_api::match_request(target, method);
+    let _ = commons_api::match_request(target, method);
+    let _ = slashes_api::match_request(target, method);
+    let _ = normalized_api::match_request(target, method);
+    let _ = siblings_api::match_request(target, method);
+    let _ = locals_api::match_request(target, method);
+    let _ = borrowed_api::match_request(target, method);
+    let _ = borrowed_siblings_api::match_request(target, method);
+    let _ = no_std_api::match_request(target, method);
+}
+
+fn match_request_lines(line: &[u8]) {
+    let _ = public_api::match_request_line(line);
+    let _ = admin_api::match_request_line(line);
+    let _ = commons_api::match_request_line(line);
+    let _ = slashes_api::match_request_line(line);
+    let _ = normalized_api::match_request_line(line);
+    let _ = siblings_api::match_request_line(line);
+    let _ = locals_api::match_request_line(line);
+    let _ = borrowed_api::match_request_line(line);
+    let _ = borrowed_siblings_api::match_request_line(line);
+    let _ = no_std_api::match_request_line(line);
+}
+
+/// The `no_std` router is generated from the same routes as
+/// `public_api`, so it must match everything the same way, and the
+/// routes it matches must make paths that match them again.
+fn match_no_std(target: &[u8], method: &[u8]) {
+    use no_std_api::ToPath;
+
+    let actual = no_std_api::match_request(target, method);
+    let expected = public_api::match_request(target, method);
+    assert_eq!(format!("{:?}", actual), format!("{:?}", expected));
+
+    if let Ok(no_std_api::Match::Route(route)) = actual {
+        let path = route.to_path();
+        match no_std_api::match_request(&path, method) {
+            Ok(no_std_api::Match::Route(ref again)) if *again == route => {}
+            other => panic!("unexpected result {:?} for {}", other, path),
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1024))]
+
+    #[test]
+    fn test_match_route_random_bytes(path in any::<Vec<u8>>(), method in any::<Vec<u8>>()) {
+        match_routes(&path, &method);
+    }
+
+    #[test]
+    fn test_match_route_path_like(path in path_bytes(), method in method_bytes()) {
+        match_routes(&path, &method);
+    }
+
+    #[test]
+    fn test_match_request_path_like(target in path_bytes(), method in method_bytes()) {
+        match_requests(&target, &method);
+    }
+
+    #[test]
+    fn test_match_no_std_path_like(target in path_bytes(), method in method_bytes()) {
+        match_no_std(&target, &method);
+    }
+
+    #[test]
+    fn test_match_request_line_path_like(
+        method in method_bytes(),
+        target in path_bytes(),
+        version in prop_oneof![Just(&b" HTTP/1.1"[..]), Just(&b" HTTP/1.0\r\n"[..]), Just(&b""[..])],
+    ) {
+        let line = [&method[..], b" ", &target[..], version].concat();
+        match_request_lines(&line);
+    }
+}
+
+#[test]
+fn test_match_route_invalid_utf8() {
+    for path in &[&b"/\xFF"[..], b"/%FF", b"/caf\xC3", b"/%C3%28"] {
+        match commons_api::match_route(path, b"GET") {
+            Ok(commons_api::Match::NotFound) => {}
+            other => panic!("unexpected result {:?} for {:?}", other, path),
+        }
+        match admin_api::match_route(path, b"GET") {
+            Ok(admin_api::Match::NotFound) => {}
+            other => panic!("unexpected result {:?} for {:?}", other, path),
+        }
+        match siblings_api::match_route(path, b"GET") {
+            Ok(siblings_api::Match::NotFound) => {}
+            other => panic!("unexpected result {:?} for {:?}", other, path),
+        }
+        match borrowed_siblings_api::match_route(path, b"GET") {
+            Ok(borrowed_siblings_api::Match::NotFound) => {}
+            other => panic!("unexpected result {:?} for {:?}", other, path),
+        }
+    }
+}
+
+#[test]
+fn test_match_request_invalid_utf8_query() {
+    for target in &[&b"/people?lang=%FF"[..], b"/people?lang=caf\xC3"] {
+        match public_api::match_request(target, b"GET") {
+            Err(public_api::Error::NotUtf8 { param, kind, .. }) => {
+                assert_eq!(param, "lang");
+                assert_eq!(kind, public_api::ParamKind::Query);
+            }
+            other => panic!("unexpected result {:?} for {:?}", other, target),
+        }
+        match borrowed_api::match_request(target, b"GET") {
+            Err(borrowed_api::Error::NotUtf8 { param, kind, .. }) => {
+                assert_eq!(param, "lang");
+                assert_eq!(kind, borrowed_api::ParamKind::Query);
+            }
+            other => panic!("unexpected result {:?} for {:?}", other, target),
+        }
+    }
+
+    match public_api::match_request(&b"/files/a?version=\xFF"[..], b"GET") {
+        Err(error) => {
+            assert_eq!(error.param(), "version");
+            assert_eq!(error.text(), "\u{FFFD}");
+            assert_eq!(
+                error.to_string(),
+                "Error parsing query parameter 'version' from \"\u{FFFD}\": not valid UTF-8"
+            );
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
@@ -0,0 +1,46 @@
+//! The helpers for the `http` crate.
+
+use crate::jobs::public_api;
+
+#[test]
+fn test_match_http_request() {
+    let request = http::Request::put("/people/12345678901234567890123456789012?name=a+b")
+        .body(())
+        .unwrap();
+
+    match public_api::match_http_request(&request) {
+        Ok(public_api::Match::Route(public_api::Route::People(
+            public_api::people::Route::Update(update),
+        ))) => assert_eq!(update.name, Some("a b".to_string())),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_http_response() {
+    let response = |path: &str, method: http::Method| -> http::Response<()> {
+        let request = http::Request::builder()
+            .method(method)
+            .uri(path)
+            .body(())
+            .unwrap();
+        public_api::match_http_request(&request).unwrap().into()
+    };
+
+    let ok = response("/people", http::Method::GET);
+    assert_eq!(ok.status(), http::StatusCode::OK);
+
+    let not_found = response("/nowhere", http::Method::GET);
+    assert_eq!(not_found.status(), http::StatusCode::NOT_FOUND);
+
+    let not_allowed = response("/people/new", http::Method::DELETE);
+    assert_eq!(not_allowed.status(), http::StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(not_allowed.headers()[http::header::ALLOW], "GET");
+
+    let redirect = response("/users?lang=en", http::Method::GET);
+    assert_eq!(redirect.status(), http::StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(
+        redirect.headers()[http::header::LOCATION],
+        "/people?lang=en"
+    );
+}
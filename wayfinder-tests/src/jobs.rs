@@ -0,0 +1,12 @@
+//! The routers generated by the build script, for the other modules to test.
+
+include!(concat!(env!("OUT_DIR"), "/public.rs"));
+include!(concat!(env!("OUT_DIR"), "/admin.rs"));
+include!(concat!(env!("OUT_DIR"), "/commons.rs"));
+include!(concat!(env!("OUT_DIR"), "/slashes.rs"));
+include!(concat!(env!("OUT_DIR"), "/normalized.rs"));
+include!(concat!(env!("OUT_DIR"), "/siblings.rs"));
+include!(concat!(env!("OUT_DIR"), "/locals.rs"));
+include!(concat!(env!("OUT_DIR"), "/borrowed.rs"));
+include!(concat!(env!("OUT_DIR"), "/borrowed_siblings.rs"));
+include!(concat!(env!("OUT_DIR"), "/no_std.rs"));
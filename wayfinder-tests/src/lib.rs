@@ -1,8 +1,22 @@
 #![allow(unused_imports)]
 #![allow(dead_code)]
 
+// For the `no_std` router, which uses `alloc` rather than `std`.
+extern crate alloc;
+
+mod build;
 mod diff;
+mod dispatch;
+#[cfg(test)]
+mod fuzz;
+mod http_helpers;
+mod jobs;
 mod macros;
+mod matching;
+mod normalize;
+mod siblings;
+mod urls;
+mod validate;
 
 use nom::types::CompleteStr;
 
@@ -27,6 +41,22 @@ fn test_method_display() {
     }
 }
 
+#[test]
+fn test_method_http() {
+    use std::convert::TryFrom;
+
+    for (method, expected) in get_method_cases().into_iter() {
+        let http_method = http::Method::from(method);
+        assert_eq!(http_method.as_str(), expected);
+        assert_eq!(Method::try_from(&http_method), Ok(method));
+    }
+
+    assert_eq!(
+        Method::try_from(&http::Method::PATCH),
+        Err(UnsupportedMethod(http::Method::PATCH))
+    );
+}
+
 #[test]
 fn test_method_parse() {
     for (expected, text) in get_method_cases().into_iter() {
@@ -41,6 +71,8 @@ fn get_param_cases() -> Vec<(Param, &'static str)> {
         (Param::new("id", "Uuid"), "id: Uuid"),
         (Param::new("x", "isize"), "x: isize"),
         (Param::new("name", "String"), "name: String"),
+        (Param::new_raw("path", "String"), "path: raw String"),
+        (Param::new("rawr", "raw"), "rawr: raw"),
     ]
 }
 
@@ -124,56 +156,19 @@ fn test_query_parameter_parse() {
 
 fn get_resource_cases() -> Vec<(Resource, &'static str)> {
     vec![
+        (get!(person::show), "GET person::show\n"),
+        (get!(-> person::show), "GET -> person::show\n"),
         (
-            Resource {
-                method: Method::Get,
-                modules: vec!["person".to_string()],
-                name: "show".to_string(),
-                is_redirect: false,
-                query_parameters: vec![],
-            },
-            "GET person::show\n",
-        ),
-        (
-            Resource {
-                method: Method::Get,
-                modules: vec!["person".to_string()],
-                name: "show".to_string(),
-                is_redirect: true,
-                query_parameters: vec![],
-            },
-            "GET -> person::show\n",
-        ),
-        (
-            Resource {
-                method: Method::Get,
-                modules: vec!["person".to_string()],
-                name: "show".to_string(),
-                is_redirect: false,
-                query_parameters: vec![Param::new("id", "usize")],
-            },
+            get!(person::show, Param::new("id", "usize")),
             "GET person::show\n  [id: usize]\n",
         ),
         (
-            Resource {
-                method: Method::Get,
-                modules: vec![],
-                name: "index".to_string(),
-                is_redirect: false,
-                query_parameters: vec![],
-            },
-            "GET index\n",
-        ),
-        (
-            Resource {
-                method: Method::Get,
-                modules: vec!["admin".to_string(), "person".to_string()],
-                name: "show".to_string(),
-                is_redirect: false,
-                query_parameters: vec![],
-            },
-            "GET admin::person::show\n",
+            post!(person::create, Param::new("id", "usize"))
+                .body_parameters(vec![Param::new("name", "String")]),
+            "POST person::create\n  [id: usize]\n  <name: String>\n",
         ),
+        (get!(index), "GET index\n"),
+        (get!(admin::person::show), "GET admin::person::show\n"),
     ]
 }
 
@@ -200,22 +195,7 @@ fn get_routes_cases() -> Vec<(Routes, &'static str)> {
     vec![
         (
             Routes {
-                resources: vec![
-                    Resource {
-                        method: Method::Get,
-                        modules: vec!["People".to_string()],
-                        name: "Index".to_string(),
-                        is_redirect: false,
-                        query_parameters: vec![],
-                    },
-                    Resource {
-                        method: Method::Post,
-                        modules: vec!["People".to_string()],
-                        name: "New".to_string(),
-                        is_redirect: false,
-                        query_parameters: vec![],
-                    },
-                ],
+                resources: vec![get!(People::Index), post!(People::New)],
                 routes: vec![],
                 query_parameters: vec![],
             },
@@ -223,13 +203,7 @@ fn get_routes_cases() -> Vec<(Routes, &'static str)> {
         ),
         (
             Routes {
-                resources: vec![Resource {
-                    method: Method::Get,
-                    modules: vec!["People".to_string()],
-                    name: "Index".to_string(),
-                    is_redirect: false,
-                    query_parameters: vec![],
-                }],
+                resources: vec![get!(People::Index)],
                 routes: vec![],
                 query_parameters: vec![Param::new("lang", "String")],
             },
@@ -237,27 +211,15 @@ fn get_routes_cases() -> Vec<(Routes, &'static str)> {
         ),
         (
             Routes {
-                resources: vec![Resource {
-                    method: Method::Get,
-                    modules: vec!["People".to_string()],
-                    name: "Index".to_string(),
-                    is_redirect: false,
-                    query_parameters: vec![],
-                }],
-                routes: vec![NestedRoutes {
-                    path_segment: PathSegment::from(Param::new("id", "Uuid")),
-                    routes: Routes {
-                        resources: vec![Resource {
-                            method: Method::Get,
-                            modules: vec!["People".to_string()],
-                            name: "Show".to_string(),
-                            is_redirect: false,
-                            query_parameters: vec![],
-                        }],
+                resources: vec![get!(People::Index)],
+                routes: vec![NestedRoutes::new(
+                    PathSegment::from(Param::new("id", "Uuid")),
+                    Routes {
+                        resources: vec![get!(People::Show)],
                         routes: vec![],
                         query_parameters: vec![],
                     },
-                }],
+                )],
                 query_parameters: vec![],
             },
             "GET People::Index\n{id: Uuid}\n  GET People::Show\n",
@@ -287,48 +249,42 @@ fn test_routes_parse() {
 fn get_nested_routes_cases() -> Vec<(NestedRoutes, &'static str)> {
     vec![
         (
-            NestedRoutes {
-                path_segment: PathSegment::from("people"),
-                routes: Routes {
-                    resources: vec![Resource {
-                        method: Method::Get,
-                        modules: vec!["People".to_string()],
-                        name: "Index".to_string(),
-                        is_redirect: false,
-                        query_parameters: vec![],
-                    }],
+            NestedRoutes::new(
+                PathSegment::from("people"),
+                Routes {
+                    resources: vec![get!(People::Index)],
                     routes: vec![],
                     query_parameters: vec![],
                 },
-            },
+            ),
             "people\n  GET People::Index\n",
         ),
         (
-            NestedRoutes {
-                path_segment: PathSegment::from(Param::new("id", "Uuid")),
-                routes: Routes {
+            NestedRoutes::new(
+                PathSegment::from(Param::new("id", "Uuid")),
+                Routes {
                     resources: vec![
-                        Resource {
-                            method: Method::Get,
-                            modules: vec!["People".to_string()],
-                            name: "Show".to_string(),
-                            is_redirect: false,
-                            query_parameters: vec![],
-                        },
-                        Resource {
-                            method: Method::Put,
-                            modules: vec!["People".to_string()],
-                            name: "Update".to_string(),
-                            is_redirect: false,
-                            query_parameters: vec![Param::new("name", "String")],
-                        },
+                        get!(People::Show),
+                        put!(People::Update, Param::new("name", "String")),
                     ],
                     routes: vec![],
                     query_parameters: vec![],
                 },
-            },
+            ),
             "{id: Uuid}\n  GET People::Show\n  PUT People::Update\n    [name: String]\n",
         ),
+        (
+            NestedRoutes::new(
+                PathSegment::from("books"),
+                Routes {
+                    resources: vec![get!(Books::Index)],
+                    routes: vec![],
+                    query_parameters: vec![],
+                },
+            )
+            .trailing_slash(TrailingSlash::Redirect),
+            "books (trailing slash: redirect)\n  GET Books::Index\n",
+        ),
     ]
 }
 
@@ -360,42 +316,24 @@ fn get_route_config_cases() -> Vec<(RouteConfig, &'static str)> {
                 }],
                 routes: Routes {
                     resources: vec![],
-                    routes: vec![NestedRoutes {
-                        path_segment: PathSegment::from("people"),
-                        routes: Routes {
-                            resources: vec![Resource {
-                                method: Method::Get,
-                                modules: vec!["People".to_string()],
-                                name: "Index".to_string(),
-                                is_redirect: false,
-                                query_parameters: vec![],
-                            }],
-                            routes: vec![NestedRoutes {
-                                path_segment: PathSegment::from(Param::new("id", "Uuid")),
-                                routes: Routes {
+                    routes: vec![NestedRoutes::new(
+                        PathSegment::from("people"),
+                        Routes {
+                            resources: vec![get!(People::Index)],
+                            routes: vec![NestedRoutes::new(
+                                PathSegment::from(Param::new("id", "Uuid")),
+                                Routes {
                                     resources: vec![
-                                        Resource {
-                                            method: Method::Get,
-                                            modules: vec!["People".to_string()],
-                                            name: "Show".to_string(),
-                                            is_redirect: false,
-                                            query_parameters: vec![],
-                                        },
-                                        Resource {
-                                            method: Method::Put,
-                                            modules: vec!["People".to_string()],
-                                            name: "Update".to_string(),
-                                            is_redirect: false,
-                                            query_parameters: vec![Param::new("name", "String")],
-                                        },
+                                        get!(People::Show),
+                                        put!(People::Update, Param::new("name", "String")),
                                     ],
                                     routes: vec![],
                                     query_parameters: vec![],
                                 },
-                            }],
+                            )],
                             query_parameters: vec![],
                         },
-                    }],
+                    )],
                     query_parameters: vec![Param::new("lang", "String")],
                 },
             },
@@ -417,52 +355,22 @@ fn get_route_config_cases() -> Vec<(RouteConfig, &'static str)> {
                 routes: Routes {
                     resources: vec![],
                     routes: vec![
-                        NestedRoutes {
-                            path_segment: PathSegment::from("one"),
-                            routes: Routes {
-                                resources: vec![
-                                    Resource {
-                                        method: Method::Get,
-                                        modules: vec!["One".to_string()],
-                                        name: "Show".to_string(),
-                                        is_redirect: false,
-                                        query_parameters: vec![],
-                                    },
-                                    Resource {
-                                        method: Method::Post,
-                                        modules: vec!["One".to_string()],
-                                        name: "Make".to_string(),
-                                        is_redirect: false,
-                                        query_parameters: vec![],
-                                    },
-                                ],
+                        NestedRoutes::new(
+                            PathSegment::from("one"),
+                            Routes {
+                                resources: vec![get!(One::Show), post!(One::Make)],
                                 routes: vec![],
                                 query_parameters: vec![],
                             },
-                        },
-                        NestedRoutes {
-                            path_segment: PathSegment::from("two"),
-                            routes: Routes {
-                                resources: vec![
-                                    Resource {
-                                        method: Method::Get,
-                                        modules: vec!["Two".to_string()],
-                                        name: "Show".to_string(),
-                                        is_redirect: false,
-                                        query_parameters: vec![],
-                                    },
-                                    Resource {
-                                        method: Method::Post,
-                                        modules: vec!["Two".to_string()],
-                                        name: "Make".to_string(),
-                                        is_redirect: false,
-                                        query_parameters: vec![],
-                                    },
-                                ],
+                        ),
+                        NestedRoutes::new(
+                            PathSegment::from("two"),
+                            Routes {
+                                resources: vec![get!(Two::Show), post!(Two::Make)],
                                 routes: vec![],
                                 query_parameters: vec![],
                             },
-                        },
+                        ),
                     ],
                     query_parameters: vec![],
                 },
@@ -14,6 +14,10 @@ fn test_param_macro() {
     let p = param!(id: Uuid);
 
     assert_eq!(p, Param::new("id", "Uuid"));
+
+    let p = param!(path: raw String);
+
+    assert_eq!(p, Param::new_raw("path", "String"));
 }
 
 #[test]
@@ -22,13 +26,12 @@ fn test_get_macro_basic() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Get,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Get,
+            vec!["User".into(), "New".into()],
+            false,
+            vec![]
+        )
     );
 }
 
@@ -38,13 +41,7 @@ fn test_get_macro_redirect() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Get,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: true,
-            query_parameters: vec![],
-        }
+        Resource::make(Method::Get, vec!["User".into(), "New".into()], true, vec![])
     );
 }
 
@@ -54,13 +51,12 @@ fn test_get_macro_params() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Get,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: false,
-            query_parameters: vec![param!(name: String)],
-        }
+        Resource::make(
+            Method::Get,
+            vec!["User".into(), "New".into()],
+            false,
+            vec![param!(name: String)]
+        )
     );
 }
 
@@ -70,13 +66,7 @@ fn test_get_macro_no_module() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Get,
-            modules: vec![],
-            name: "Homepage".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(Method::Get, vec!["Homepage".into()], false, vec![])
     );
 }
 
@@ -86,13 +76,12 @@ fn test_get_macro_several_modules() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Get,
-            modules: vec!["admin".into(), "users".into()],
-            name: "Create".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Get,
+            vec!["admin".into(), "users".into(), "Create".into()],
+            false,
+            vec![]
+        )
     );
 }
 
@@ -102,13 +91,12 @@ fn test_post_macro_basic() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Post,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Post,
+            vec!["User".into(), "New".into()],
+            false,
+            vec![]
+        )
     );
 }
 
@@ -118,13 +106,12 @@ fn test_post_macro_redirect() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Post,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: true,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Post,
+            vec!["User".into(), "New".into()],
+            true,
+            vec![]
+        )
     );
 }
 
@@ -134,13 +121,12 @@ fn test_post_macro_params() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Post,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: false,
-            query_parameters: vec![param!(name: String)],
-        }
+        Resource::make(
+            Method::Post,
+            vec!["User".into(), "New".into()],
+            false,
+            vec![param!(name: String)]
+        )
     );
 }
 
@@ -150,13 +136,7 @@ fn test_post_macro_no_module() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Post,
-            modules: vec![],
-            name: "Homepage".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(Method::Post, vec!["Homepage".into()], false, vec![])
     );
 }
 
@@ -166,13 +146,12 @@ fn test_post_macro_several_modules() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Post,
-            modules: vec!["admin".into(), "users".into()],
-            name: "Create".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Post,
+            vec!["admin".into(), "users".into(), "Create".into()],
+            false,
+            vec![]
+        )
     );
 }
 
@@ -182,13 +161,12 @@ fn test_put_macro_basic() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Put,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Put,
+            vec!["User".into(), "New".into()],
+            false,
+            vec![]
+        )
     );
 }
 
@@ -198,13 +176,7 @@ fn test_put_macro_redirect() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Put,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: true,
-            query_parameters: vec![],
-        }
+        Resource::make(Method::Put, vec!["User".into(), "New".into()], true, vec![])
     );
 }
 
@@ -214,13 +186,12 @@ fn test_put_macro_params() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Put,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: false,
-            query_parameters: vec![param!(name: String)],
-        }
+        Resource::make(
+            Method::Put,
+            vec!["User".into(), "New".into()],
+            false,
+            vec![param!(name: String)]
+        )
     );
 }
 
@@ -230,13 +201,7 @@ fn test_put_macro_no_module() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Put,
-            modules: vec![],
-            name: "Homepage".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(Method::Put, vec!["Homepage".into()], false, vec![])
     );
 }
 
@@ -246,13 +211,12 @@ fn test_put_macro_several_modules() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Put,
-            modules: vec!["admin".into(), "users".into()],
-            name: "Create".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Put,
+            vec!["admin".into(), "users".into(), "Create".into()],
+            false,
+            vec![]
+        )
     );
 }
 
@@ -262,13 +226,12 @@ fn test_delete_macro_basic() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Delete,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Delete,
+            vec!["User".into(), "New".into()],
+            false,
+            vec![]
+        )
     );
 }
 
@@ -278,13 +241,12 @@ fn test_delete_macro_redirect() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Delete,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: true,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Delete,
+            vec!["User".into(), "New".into()],
+            true,
+            vec![]
+        )
     );
 }
 
@@ -294,13 +256,12 @@ fn test_delete_macro_params() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Delete,
-            modules: vec!["User".into()],
-            name: "New".into(),
-            is_redirect: false,
-            query_parameters: vec![param!(name: String)],
-        }
+        Resource::make(
+            Method::Delete,
+            vec!["User".into(), "New".into()],
+            false,
+            vec![param!(name: String)]
+        )
     );
 }
 
@@ -310,13 +271,7 @@ fn test_delete_macro_no_module() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Delete,
-            modules: vec![],
-            name: "Homepage".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(Method::Delete, vec!["Homepage".into()], false, vec![])
     );
 }
 
@@ -326,12 +281,11 @@ fn test_delete_macro_several_modules() {
 
     assert_eq!(
         g,
-        Resource {
-            method: Method::Delete,
-            modules: vec!["admin".into(), "users".into()],
-            name: "Create".into(),
-            is_redirect: false,
-            query_parameters: vec![],
-        }
+        Resource::make(
+            Method::Delete,
+            vec!["admin".into(), "users".into(), "Create".into()],
+            false,
+            vec![]
+        )
     );
 }
@@ -0,0 +1,264 @@
+//! Matching paths and requests, and parsing their parameters.
+
+use crate::jobs::{admin_api, borrowed_api, commons_api, locals_api, public_api};
+
+#[test]
+fn test_public_api() {
+    match public_api::match_route("/people/new", b"GET") {
+        Ok(public_api::Match::Route(public_api::Route::People(
+            public_api::people::Route::New(_),
+        ))) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_admin_api() {
+    let path = "/12345678901234567890123456789012";
+    match admin_api::match_route(path, b"GET") {
+        Ok(admin_api::Match::Route(admin_api::Route::Admin(admin_api::admin::Route::People(
+            admin_api::admin::people::Route::Show(_),
+        )))) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_into_route() {
+    let id = uuid::Uuid::nil();
+    let show = || admin_api::admin::people::Show { id };
+
+    let people: admin_api::admin::people::Route = show().into();
+    let admin: admin_api::admin::Route = show().into();
+    let route: admin_api::Route = show().into();
+
+    assert_eq!(admin, admin_api::admin::Route::People(people));
+    assert_eq!(route, admin_api::Route::Admin(admin));
+    assert_eq!(
+        admin_api::Route::from(admin_api::Index),
+        admin_api::Route::Index(admin_api::Index)
+    );
+}
+
+#[test]
+fn test_match_request_decodes() {
+    match commons_api::match_request("/a%20b%2Fc?unused=1", b"GET") {
+        Ok(commons_api::Match::Route(commons_api::Route::Bar(commons_api::bar::Route::Dyn(
+            commons_api::bar::Dyn { a },
+        )))) => assert_eq!(a, "a b/c"),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_match_static_literally() {
+    for path in &["/peop%6Ce", "/people%2Fnew"] {
+        match public_api::match_route(path, b"GET") {
+            Ok(public_api::Match::NotFound) => {}
+            other => panic!("unexpected result {:?} for {}", other, path),
+        }
+    }
+    // not the static `new`, so it's decoded as the `id`
+    match public_api::match_route("/people/%6Eew", b"GET") {
+        Err(e) => assert_eq!((e.param(), e.text()), ("id", "new")),
+        other => panic!("unexpected result {:?}", other),
+    }
+    match commons_api::match_route("/Jos%C3%A9", b"GET") {
+        Ok(commons_api::Match::Route(commons_api::Route::Bar(commons_api::bar::Route::Dyn(
+            dyn_,
+        )))) => {
+            assert_eq!(dyn_.a, "José")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_only_matched_routes() {
+    use public_api::Match;
+
+    for path in &[
+        "/people/not-a-uuid/nonexistent",
+        "/people/not-a-uuid/edit/more",
+    ] {
+        match public_api::match_route(path, b"GET") {
+            Ok(Match::NotFound) => {}
+            other => panic!("unexpected result {:?} for {}", other, path),
+        }
+    }
+    match public_api::match_route("/people/not-a-uuid/edit", b"POST") {
+        Ok(Match::NotAllowed(allowed)) => assert_eq!(allowed, ["GET"]),
+        other => panic!("unexpected result {:?}", other),
+    }
+    match public_api::match_route("/people/not-a-uuid/edit", b"GET") {
+        Err(e) => assert_eq!((e.param(), e.text()), ("id", "not-a-uuid")),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_raw_params() {
+    use public_api::{files, Match, Route, ToPath};
+
+    match public_api::match_request("/files/a%2Fb?version=1+2%20", b"GET") {
+        Ok(Match::Route(Route::Files(files::Route::Show(show)))) => {
+            assert_eq!(show.file, "a%2Fb");
+            assert_eq!(show.version, Some("1+2%20".to_string()));
+            assert_eq!(show.to_path(), "/files/a%2Fb?version=1+2%20");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_param_names() {
+    use locals_api::{Match, Route, Show, ToPath, Write};
+
+    // parameters named like the matcher's own locals don't shadow them
+    assert_eq!(
+        locals_api::match_request("/a/POST/b?len=3&lang=en", b"GET").unwrap(),
+        Match::Route(Route::Show(Show {
+            path: "a".to_string(),
+            method: "POST".to_string(),
+            query: "b".to_string(),
+            lang: Some("en".to_string()),
+            len: Some(3),
+        }))
+    );
+
+    // or the locals of `write_path`
+    let write = Write {
+        path: "a".to_string(),
+        method: "POST".to_string(),
+        query: "b".to_string(),
+        w: "c".to_string(),
+        lang: None,
+        first: Some("d".to_string()),
+    };
+    assert_eq!(write.to_path(), "/a/POST/b/c?first=d");
+    assert_eq!(
+        locals_api::match_request(write.to_path(), b"GET").unwrap(),
+        Match::Route(Route::Write(write))
+    );
+}
+
+#[test]
+fn test_borrowed_params() {
+    use borrowed_api::{files, people, Match, Route, ToPath};
+    use std::borrow::Cow;
+
+    // borrowed from the target unless they had to be decoded
+    match borrowed_api::match_request("/files/a%2Fb?version=1+2", b"GET") {
+        Ok(Match::Route(Route::Files(files::Route::Show(show)))) => {
+            assert!(matches!(show.file, Cow::Borrowed("a%2Fb")));
+            assert!(matches!(show.version, Some(Cow::Borrowed("1+2"))));
+            assert_eq!(show.to_path(), "/files/a%2Fb?version=1+2");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    match borrowed_api::match_request("/people/new?lang=caf%C3%A9", b"GET") {
+        Ok(Match::Route(Route::People(people::Route::New(new)))) => {
+            assert!(matches!(new.lang, Some(Cow::Owned(ref lang)) if lang == "café"));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    // other types are still parsed
+    let id = uuid::Uuid::nil();
+    let target = format!("/people/{}?lang=en", id);
+    match borrowed_api::match_request(&target, b"GET") {
+        Ok(Match::Route(Route::People(people::Route::Show(show)))) => {
+            assert_eq!(show.id, id);
+            assert!(matches!(show.lang, Some(Cow::Borrowed("en"))));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(borrowed_api::match_route("/people/not-a-uuid", b"GET").is_err());
+
+    match borrowed_api::match_request_line("GET /files/a%20b HTTP/1.1") {
+        Ok(Match::Route(Route::Files(files::Route::Show(show)))) => {
+            assert_eq!(show.file, "a%20b");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    // route_for borrows from the parameters
+    let file = String::from("readme");
+    match borrowed_api::route_for("files.show", &[("file", &file)]) {
+        Ok(Route::Files(files::Route::Show(show))) => {
+            assert!(matches!(show.file, Cow::Borrowed("readme")));
+            assert_eq!(show.version, None);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_match_request_line() {
+    use public_api::{match_request_line, Match, RequestLineError, Route};
+
+    match match_request_line(b"GET /people?lang=en HTTP/1.1\r\n") {
+        Ok(Match::Route(Route::People(public_api::people::Route::Index(index)))) => {
+            assert_eq!(index.lang, Some("en".to_string()))
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    match match_request_line("PATCH /people/new HTTP/1.0") {
+        Ok(Match::NotAllowed(allowed)) => assert_eq!(allowed, ["GET"]),
+        other => panic!("unexpected result {:?}", other),
+    }
+    match match_request_line("GET /people/oops HTTP/1.1") {
+        Err(RequestLineError::Param(e)) => assert_eq!(e.param(), "id"),
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let long = format!(
+        "GET /{} HTTP/1.1",
+        "a".repeat(public_api::MAX_REQUEST_LINE_LEN)
+    );
+    let method = format!("{} / HTTP/1.1", "A".repeat(public_api::MAX_METHOD_LEN + 1));
+    let malformed = vec![
+        (long.as_str(), "TooLong"),
+        ("", "Malformed"),
+        ("GET /people", "Malformed"),
+        (" /people HTTP/1.1", "BadMethod"),
+        ("G(T /people HTTP/1.1", "BadMethod"),
+        (method.as_str(), "BadMethod"),
+        ("GET people HTTP/1.1", "BadTarget"),
+        ("GET * HTTP/1.1", "BadTarget"),
+        ("GET /a b HTTP/1.1", "BadTarget"),
+        ("GET /people#top HTTP/1.1", "BadTarget"),
+        ("GET /people HTTP/2", "BadVersion"),
+        ("GET /people http/1.1\r\n", "BadVersion"),
+    ];
+    for (line, expected) in malformed {
+        match match_request_line(line) {
+            Err(ref e) if format!("{:?}", e) == expected => {}
+            other => panic!("unexpected result {:?} for {:?}", other, line),
+        }
+    }
+}
+
+#[test]
+fn test_param_error() {
+    let error = match public_api::match_route("/people/not-a-uuid", b"GET") {
+        Err(e) => e,
+        Ok(m) => panic!("Unexpected match: {:?}", m),
+    };
+
+    assert_eq!(error.param(), "id");
+    assert_eq!(error.kind(), public_api::ParamKind::Path);
+    assert_eq!(error.text(), "not-a-uuid");
+    match error {
+        public_api::Error::Id { error, .. } => {
+            let _: uuid::parser::ParseError = error;
+        }
+        other => panic!("Unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_param_error_send_sync() {
+    fn assert_send_sync<T: Send + Sync + std::error::Error>() {}
+    assert_send_sync::<public_api::Error>();
+}
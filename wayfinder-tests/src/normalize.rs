@@ -0,0 +1,97 @@
+//! Trailing slashes and the path normalisation options.
+
+use crate::jobs::{normalized_api, public_api, slashes_api};
+
+#[test]
+fn test_trailing_slash() {
+    use slashes_api::{lenient, redirect, strict, Match, Route, ToPath};
+
+    let get = |path| slashes_api::match_route(path, b"GET").unwrap();
+
+    // the root always matches
+    assert_eq!(get("/"), Match::Route(Route::Index(slashes_api::Index {})));
+
+    // strict by default for this job
+    let index = Route::Strict(strict::Route::Index(strict::Index {}));
+    assert_eq!(get("/strict"), Match::Route(index));
+    assert_eq!(get("/strict/"), Match::NotFound);
+
+    // unless overridden in the route file
+    let index = || Route::Lenient(lenient::Route::Index(lenient::Index {}));
+    assert_eq!(get("/lenient"), Match::Route(index()));
+    assert_eq!(get("/lenient/"), Match::Route(index()));
+    let show = Route::Lenient(lenient::Route::Show(lenient::Show {
+        id: "a".to_string(),
+    }));
+    assert_eq!(get("/lenient/a/"), Match::Route(show));
+
+    let index = || Route::Redirect(redirect::Route::Index(redirect::Index {}));
+    assert_eq!(get("/redirect"), Match::Route(index()));
+    assert_eq!(get("/redirect/"), Match::Redirect(index()));
+    let show = || {
+        Route::Redirect(redirect::Route::Show(redirect::Show {
+            id: "a".to_string(),
+        }))
+    };
+    assert_eq!(get("/redirect/a"), Match::Route(show()));
+    match get("/redirect/a/") {
+        Match::Redirect(route) => {
+            assert_eq!(route, show());
+            assert_eq!(route.to_path(), "/redirect/a");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert_eq!(
+        slashes_api::match_route("/redirect/a/", b"PUT").unwrap(),
+        Match::NotAllowed(&["GET", "POST"])
+    );
+
+    // only GET is redirected, since a client would drop the body
+    let update = || {
+        Route::Redirect(redirect::Route::Update(redirect::Update {
+            id: "a".to_string(),
+        }))
+    };
+    let post = |path| slashes_api::match_route(path, b"POST").unwrap();
+    assert_eq!(post("/redirect/a"), Match::Route(update()));
+    assert_eq!(post("/redirect/a/"), Match::Route(update()));
+}
+
+#[test]
+fn test_normalize_path() {
+    use normalized_api::{people, Match, Route};
+
+    let get = |path| normalized_api::match_route(path, b"GET").unwrap();
+    let new = || Route::People(people::Route::New(people::New { lang: None }));
+    let index = || Route::People(people::Route::Index(people::Index { lang: None }));
+
+    assert_eq!(get("/people/new"), Match::Route(new()));
+    assert_eq!(get("//people///new"), Match::Route(new()));
+    assert_eq!(get("/people/./new"), Match::Route(new()));
+    assert_eq!(get("/books/../people/new/."), Match::Route(new()));
+    assert_eq!(get("/../people/new/x/.."), Match::Route(new()));
+    assert_eq!(get("/PEOPLE/New"), Match::Route(new()));
+    assert_eq!(get("/people/new/.."), Match::Route(index()));
+    // an encoded dot is an id, not a dot segment
+    assert_eq!(get("/people/%2E/new"), Match::NotFound);
+    assert!(normalized_api::match_route("/people/%2E/edit", b"GET").is_err());
+    assert_eq!(get("/x/../Users"), Match::Redirect(index()));
+
+    // parameters are parsed from the path as given
+    match normalized_api::match_route("/FILES/./A%2Fb", b"GET") {
+        Ok(Match::Route(Route::Files(normalized_api::files::Route::Show(show)))) => {
+            assert_eq!(show.file, "A%2Fb");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    // unless normalisation is enabled, paths are matched as given
+    assert_eq!(
+        public_api::match_route("//people/new", b"GET").unwrap(),
+        public_api::Match::NotFound
+    );
+    assert_eq!(
+        public_api::match_route("/People/new", b"GET").unwrap(),
+        public_api::Match::NotFound
+    );
+}
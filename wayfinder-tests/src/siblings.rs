@@ -0,0 +1,146 @@
+//! Precedence between static and dynamic sibling routes.
+
+use crate::jobs::{borrowed_siblings_api, siblings_api};
+
+#[test]
+fn test_sibling_precedence() {
+    use siblings_api::{deep, people, Error, Match, Route};
+
+    let get = |path| siblings_api::match_route(path, b"GET");
+    let slug = |slug: &str| slug.to_string();
+
+    // static segments win
+    assert_eq!(
+        get("/new").unwrap(),
+        Match::Route(Route::New(siblings_api::New {}))
+    );
+    assert_eq!(
+        get("/people/newest").unwrap(),
+        Match::Route(Route::People(people::Route::Newest(people::Newest {})))
+    );
+
+    // but fall back to a parameter when the rest doesn't match
+    let extra = |s: &str| Route::Extra(siblings_api::Extra { slug: slug(s) });
+    assert_eq!(get("/new/extra").unwrap(), Match::Route(extra("new")));
+    assert_eq!(get("/newx/extra").unwrap(), Match::Route(extra("newx")));
+    assert_eq!(
+        get("/peoplex").unwrap(),
+        Match::Route(Route::Show(siblings_api::Show {
+            slug: slug("peoplex")
+        }))
+    );
+    assert_eq!(
+        get("/people/7/edit").unwrap(),
+        Match::Route(Route::People(people::Route::Edit(people::Edit { id: 7 })))
+    );
+
+    // even after matching several static segments
+    let c = |x: &str| Route::Deep(deep::Route::C(deep::C { x: x.to_string() }));
+    assert_eq!(
+        get("/deep/a/b").unwrap(),
+        Match::Route(Route::Deep(deep::Route::Ab(deep::Ab {})))
+    );
+    assert_eq!(get("/deep/a/c").unwrap(), Match::Route(c("a")));
+    assert_eq!(get("/deep/ab/c").unwrap(), Match::Route(c("ab")));
+    assert_eq!(get("/deep/extra").unwrap(), Match::Route(extra("deep")));
+    assert_eq!(get("/deep/a/d").unwrap(), Match::NotFound);
+
+    // a parameter is never empty
+    assert_eq!(get("/").unwrap(), Match::NotFound);
+    assert_eq!(get("/deep//c").unwrap(), Match::NotFound);
+
+    // a path that matches a route but not its method is not allowed
+    assert_eq!(
+        siblings_api::match_route("/new", b"POST").unwrap(),
+        Match::NotAllowed(&["GET"])
+    );
+
+    // a parameter that fails to parse is an error, even if the path
+    // could match another route
+    match get("/people/new/edit") {
+        Err(Error::Id { text, .. }) => assert_eq!(text, "new"),
+        other => panic!("unexpected result {:?}", other),
+    }
+    match get("/people/extra") {
+        Err(Error::Id { text, .. }) => assert_eq!(text, "extra"),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_dynamic_siblings() {
+    use siblings_api::{items, Match, Route};
+
+    let get = |path| siblings_api::match_route(path, b"GET").unwrap();
+    let find = |slug: &str| {
+        Route::Items(items::Route::Find(items::Find {
+            slug: slug.to_string(),
+        }))
+    };
+
+    // tried in the order declared
+    assert_eq!(
+        get("/items/7"),
+        Match::Route(Route::Items(items::Route::Show(items::Show { number: 7 })))
+    );
+    assert_eq!(
+        get("/items/7/edit"),
+        Match::Route(Route::Items(items::Route::Edit(items::Edit { number: 7 })))
+    );
+
+    // a parse failure falls through to the next one
+    assert_eq!(get("/items/abc"), Match::Route(find("abc")));
+    assert_eq!(
+        get("/items/18446744073709551616"),
+        Match::Route(find("18446744073709551616"))
+    );
+
+    // and so does the rest of the path not matching
+    assert_eq!(
+        get("/items/7/history"),
+        Match::Route(Route::Items(items::Route::History(items::History {
+            slug: "7".to_string(),
+        })))
+    );
+    assert_eq!(get("/items/abc/edit"), Match::NotFound);
+
+    // even when the method doesn't match the first one
+    let post = |path| siblings_api::match_route(path, b"POST").unwrap();
+    let update = |slug: &str| {
+        Route::Items(items::Route::Update(items::Update {
+            slug: slug.to_string(),
+            note: None,
+        }))
+    };
+    assert_eq!(post("/items/abc"), Match::Route(update("abc")));
+    assert_eq!(post("/items/7"), Match::NotAllowed(&["GET"]));
+}
+
+#[test]
+fn test_borrowed_siblings() {
+    use borrowed_siblings_api::{items, people, Match, Route, Show};
+    use std::borrow::Cow;
+
+    let get = |path| borrowed_siblings_api::match_route(path, b"GET").unwrap();
+
+    assert_eq!(
+        get("/new%20one"),
+        Match::Route(Route::Show(Show {
+            slug: Cow::Owned("new one".to_string())
+        }))
+    );
+    match get("/items/abc/history") {
+        Match::Route(Route::Items(items::Route::History(history))) => {
+            assert!(matches!(history.slug, Cow::Borrowed("abc")));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    // types that don't borrow have no lifetime
+    let show: people::Route = people::Show { id: 7 }.into();
+    assert_eq!(get("/people/7"), Match::Route(Route::People(show)));
+    assert_eq!(
+        get("/items/7"),
+        Match::Route(Route::Items(items::Route::Show(items::Show { number: 7 })))
+    );
+}
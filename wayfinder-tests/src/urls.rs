@@ -0,0 +1,194 @@
+//! Paths and links made from routes, and matching them back.
+
+use crate::jobs::{admin_api, commons_api, public_api};
+
+#[test]
+fn test_path_round_trip() {
+    use commons_api::ToPath;
+
+    for a in &[
+        "plain",
+        "a b",
+        "a/b",
+        "100%",
+        "1+1",
+        "this&that",
+        "café",
+        "?#",
+    ] {
+        let route: commons_api::Route = commons_api::bar::Dyn { a: a.to_string() }.into();
+        let path = route.to_path();
+        assert!(path.is_ascii(), "path: {}", path);
+
+        match commons_api::match_request(&path, b"GET") {
+            Ok(commons_api::Match::Route(actual)) => assert_eq!(actual, route),
+            other => panic!("unexpected result {:?} for {}", other, path),
+        }
+    }
+}
+
+#[test]
+fn test_url_for_nested() {
+    let id = uuid::Uuid::nil().to_string();
+
+    assert_eq!(admin_api::url_for("index", &[]).unwrap(), "/");
+    assert_eq!(
+        admin_api::url_for("admin.people.show", &[("id", &id)]).unwrap(),
+        format!("/{}", id)
+    );
+    assert_eq!(commons_api::url_for("fomo.as_usual", &[]).unwrap(), "/fomo");
+}
+
+#[test]
+fn test_to_path() {
+    use public_api::ToPath;
+    use uuid::Uuid;
+
+    let id: Uuid = "12345678901234567890123456789012".parse().unwrap();
+
+    let edit = public_api::people::Edit { id, lang: None };
+    assert_eq!(edit.to_path(), format!("/people/{}/edit", id));
+
+    let route: public_api::Route = public_api::books::Index { lang: None }.into();
+    assert_eq!(route.to_path(), "/books");
+
+    let mut path = String::from("https://example.com");
+    edit.write_path(&mut path).unwrap();
+    assert_eq!(path, format!("https://example.com/people/{}/edit", id));
+
+    let update = public_api::people::Update {
+        id,
+        lang: Some("en&fr".to_string()),
+        name: Some("Zoë Smith+".to_string()),
+    };
+    assert_eq!(
+        update.to_path(),
+        format!("/people/{}?lang=en%26fr&name=Zo%C3%AB%20Smith%2B", id)
+    );
+}
+
+#[test]
+fn test_urls() {
+    use uuid::Uuid;
+
+    let id: Uuid = "12345678901234567890123456789012".parse().unwrap();
+    let show = public_api::people::Show { id, lang: None };
+    let index = public_api::books::Index {
+        lang: Some("en".to_string()),
+    };
+
+    let urls = public_api::Urls::new();
+    assert_eq!(urls.link_to(&index, None), "/books?lang=en");
+    assert_eq!(urls.url_for(&index, None), "/books?lang=en");
+
+    let urls = public_api::Urls::new()
+        .base("https://example.com/")
+        .mount("app/");
+    assert_eq!(urls.link_to(&index, None), "/app/books?lang=en");
+    assert_eq!(
+        urls.url_for(&show, Some("see also")),
+        format!("https://example.com/app/people/{}#see%20also", id)
+    );
+
+    let route: public_api::Route = show.into();
+    let mut link = String::from("<a href=\"");
+    urls.write_link(&mut link, &route, Some("top")).unwrap();
+    assert_eq!(link, format!("<a href=\"/app/people/{}#top", id));
+}
+
+#[test]
+fn test_url_for() {
+    use uuid::Uuid;
+
+    let id = "12345678901234567890123456789012";
+    let uuid: Uuid = id.parse().unwrap();
+
+    assert_eq!(public_api::url_for("people.index", &[]).unwrap(), "/people");
+    assert_eq!(
+        public_api::url_for("books.update", &[("id", id), ("lang", "en")]).unwrap(),
+        format!("/books/{}?lang=en", uuid)
+    );
+    assert_eq!(
+        public_api::route_for("people.edit", &[("id", id)]).unwrap(),
+        public_api::people::Edit {
+            id: uuid,
+            lang: None
+        }
+        .into()
+    );
+
+    match public_api::url_for("people.shwo", &[("id", id)]) {
+        Err(public_api::UrlError::UnknownRoute(name)) => assert_eq!(name, "people.shwo"),
+        other => panic!("unexpected result {:?}", other),
+    }
+    match public_api::url_for("people.show", &[]) {
+        Err(public_api::UrlError::MissingParam { route, param }) => {
+            assert_eq!((route, param), ("people.show", "id"))
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    match public_api::url_for("people.show", &[("id", id), ("name", "x")]) {
+        Err(e @ public_api::UrlError::UnknownParam { .. }) => assert_eq!(
+            e.to_string(),
+            "Unknown parameter 'name' for route 'people.show'"
+        ),
+        other => panic!("unexpected result {:?}", other),
+    }
+    match public_api::url_for("people.show", &[("id", "nope")]) {
+        Err(public_api::UrlError::Param(e)) => {
+            assert_eq!(e.param(), "id");
+            assert_eq!(e.text(), "nope");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_match_request() {
+    use public_api::{Match, ToPath};
+    use uuid::Uuid;
+
+    let id: Uuid = "12345678901234567890123456789012".parse().unwrap();
+    let routes: Vec<public_api::Route> = vec![
+        public_api::people::Edit { id, lang: None }.into(),
+        public_api::people::Edit {
+            id,
+            lang: Some("en-US".to_string()),
+        }
+        .into(),
+        public_api::books::Index {
+            lang: Some("a=b & c?".to_string()),
+        }
+        .into(),
+    ];
+
+    for route in routes {
+        let path = route.to_path();
+        assert_eq!(
+            public_api::match_request(&path, b"GET").unwrap(),
+            Match::Route(route),
+            "path: {}",
+            path
+        );
+    }
+
+    let update = public_api::people::Update {
+        id,
+        lang: Some("fr".to_string()),
+        name: Some("a b".to_string()),
+    };
+    let target = format!("/people/{}?name=a+b&lang=fr&lang=en", id);
+    assert_eq!(
+        public_api::match_request(&target, b"PUT").unwrap(),
+        Match::Route(public_api::Route::People(
+            public_api::people::Route::Update(update)
+        ))
+    );
+
+    match public_api::match_request(format!("/people/{}?lang", id), b"GET") {
+        Ok(Match::Route(public_api::Route::People(public_api::people::Route::Show(show)))) => {
+            assert_eq!(show.lang, Some(String::new()))
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
@@ -0,0 +1,470 @@
+use wayfinder::{
+    get, param, post, Diagnostic, Location, Method, NestedRoutes, RouteConfig, Routes, Severity,
+    SourceMap,
+};
+
+fn parse(text: &str) -> RouteConfig {
+    wayfinder::parse::route_config(text).unwrap().1
+}
+
+#[test]
+fn test_validate_example() {
+    let config = parse(include_str!("../cases/example/routes.routes"));
+
+    assert_eq!(config.validate(), vec![]);
+}
+
+#[test]
+fn test_validate_duplicate_path() {
+    let config = parse(
+        "/
+  people
+    GET People::Index
+  people
+    POST People::Create
+",
+    );
+
+    assert_eq!(
+        config.validate(),
+        vec![Diagnostic::error(
+            "/",
+            "path segment `people` is declared more than once"
+        )]
+    );
+}
+
+#[test]
+fn test_validate_duplicate_dynamic_path() {
+    let config = parse(
+        "/
+  {id: Uuid}
+    GET People::Show
+  {id: usize}
+    POST People::Update
+",
+    );
+
+    assert_eq!(
+        config.validate(),
+        vec![Diagnostic::error(
+            "/",
+            "path segment `{id}` is declared more than once"
+        )]
+    );
+}
+
+#[test]
+fn test_validate_dynamic_siblings() {
+    let config = parse(
+        "/
+  {id: Uuid}
+    GET People::Show
+  {slug: String}
+    GET People::Find
+",
+    );
+
+    assert_eq!(config.validate(), vec![]);
+}
+
+#[test]
+fn test_validate_conflicting_dynamic_path() {
+    let config = parse(
+        "/
+  {slug: String}
+    GET People::Find
+  {id: Uuid}
+    GET People::Show
+",
+    );
+
+    let diagnostics = config.validate();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].path, "/");
+    assert_eq!(
+        diagnostics[0].message,
+        "path segment `{id: Uuid}` is unreachable; `{slug: String}` is tried first and parses the same segments"
+    );
+}
+
+#[test]
+fn test_validate_same_type_dynamic_path() {
+    let config = parse(
+        "/
+  {id: u64}
+    GET People::Show
+  {number: u64}
+    GET People::Find
+",
+    );
+
+    let diagnostics = config.validate();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+}
+
+#[test]
+fn test_validate_wider_type_dynamic_path() {
+    let shadowed = vec![
+        ("u64", "u32"),
+        ("u16", "u8"),
+        ("i64", "i8"),
+        ("i32", "u16"),
+        ("u64", "usize"),
+        ("usize", "u32"),
+        ("f64", "u64"),
+        ("f32", "i8"),
+        ("f32", "f64"),
+    ];
+    for (earlier, later) in shadowed {
+        let config = parse(&format!(
+            "/\n  {{a: {}}}\n    GET People::Show\n  {{b: {}}}\n    GET People::Find\n",
+            earlier, later
+        ));
+
+        let diagnostics = config.validate();
+        assert_eq!(diagnostics.len(), 1, "{} before {}", earlier, later);
+        assert_eq!(
+            diagnostics[0].message,
+            format!(
+                "path segment `{{b: {}}}` is unreachable; `{{a: {}}}` is tried first and parses the same segments",
+                later, earlier
+            )
+        );
+    }
+
+    let reachable = vec![
+        ("u32", "u64"),
+        ("u64", "i8"),
+        ("i16", "u16"),
+        ("u32", "usize"),
+        ("usize", "u64"),
+        ("u64", "f64"),
+    ];
+    for (earlier, later) in reachable {
+        let config = parse(&format!(
+            "/\n  {{a: {}}}\n    GET People::Show\n  {{b: {}}}\n    GET People::Find\n",
+            earlier, later
+        ));
+
+        assert_eq!(config.validate(), vec![], "{} before {}", earlier, later);
+    }
+}
+
+#[test]
+fn test_validate_duplicate_action() {
+    let config = parse(
+        "/
+  people
+    GET People::Index
+  users
+    GET People::Index
+",
+    );
+
+    assert_eq!(
+        config.validate(),
+        vec![Diagnostic::error(
+            "/users",
+            "duplicate action `People::Index`"
+        )]
+    );
+}
+
+#[test]
+fn test_validate_duplicate_method() {
+    let config = parse(
+        "/
+  people
+    GET People::Index
+    GET People::List
+",
+    );
+
+    assert_eq!(
+        config.validate(),
+        vec![Diagnostic::error(
+            "/people",
+            "method GET is declared more than once; `People::List` is unreachable"
+        )]
+    );
+}
+
+#[test]
+fn test_validate_duplicate_param() {
+    let config = parse(
+        "/
+  [id: usize]
+  people
+    {id: Uuid}
+      GET People::Show
+",
+    );
+
+    assert_eq!(
+        config.validate(),
+        vec![Diagnostic::error(
+            "/people/{id: Uuid}",
+            "parameter `id` is declared more than once for `People::Show`"
+        )]
+    );
+}
+
+#[test]
+fn test_validate_duplicate_resource_param() {
+    let config = parse(
+        "/
+  [lang: String]
+  people
+    GET People::Index
+      [lang: String]
+",
+    );
+
+    assert_eq!(
+        config.validate(),
+        vec![Diagnostic::error(
+            "/people",
+            "parameter `lang` is declared more than once for `People::Index`"
+        )]
+    );
+}
+
+#[test]
+fn test_validate_redirect() {
+    let config = parse(
+        "/
+  users
+    GET -> People::Index
+  people
+    GET People::Index
+",
+    );
+
+    assert_eq!(config.validate(), vec![]);
+}
+
+#[test]
+fn test_validate_missing_redirect() {
+    let config = parse(
+        "/
+  users
+    GET -> People::List
+  people
+    GET People::Index
+",
+    );
+
+    assert_eq!(
+        config.validate(),
+        vec![Diagnostic::error(
+            "/users",
+            "redirect to `People::List`, which is not an action"
+        )]
+    );
+}
+
+#[test]
+fn test_validate_empty_route() {
+    let config = RouteConfig {
+        headers: vec![],
+        routes: Routes {
+            resources: vec![get!(Index)],
+            routes: vec![NestedRoutes::new(
+                "people",
+                Routes {
+                    routes: vec![NestedRoutes::new(param!(id: usize), Routes::default())],
+                    ..Routes::default()
+                },
+            )],
+            ..Routes::default()
+        },
+    };
+
+    assert_eq!(
+        config.validate(),
+        vec![Diagnostic::warning(
+            "/people/{id: usize}",
+            "route has no resources or nested routes"
+        )]
+    );
+}
+
+#[test]
+fn test_validate_reports_all() {
+    let config = RouteConfig {
+        headers: vec![],
+        routes: Routes {
+            resources: vec![get!(Index), post!(Index)],
+            routes: vec![NestedRoutes::new(
+                "people",
+                Routes {
+                    resources: vec![get!(-> People::Index)],
+                    ..Routes::default()
+                },
+            )],
+            ..Routes::default()
+        },
+    };
+
+    let diagnostics = config.validate();
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics.iter().all(|d| d.is_error()));
+    assert_eq!(diagnostics[0].path, "/");
+    assert_eq!(diagnostics[1].path, "/people");
+}
+
+#[test]
+fn test_source_map() {
+    let input = include_str!("../cases/example/routes.routes");
+    let config = parse(input);
+    let source_map = wayfinder::parse::source_map(input, &config);
+
+    let at = |location: Option<&Location>| location.map(|l| (l.line, l.column));
+    let people = vec!["people".into()];
+    let person = vec!["people".into(), param!(id: Uuid).into()];
+
+    assert_eq!(at(source_map.route(&[])), Some((3, 1)));
+    assert_eq!(at(source_map.route(&people)), Some((9, 3)));
+    assert_eq!(at(source_map.route(&person)), Some((16, 5)));
+    assert_eq!(
+        at(source_map.resource(&person, Method::Put, "People::Update")),
+        Some((18, 7))
+    );
+    assert_eq!(at(source_map.param(&person, None, "id")), Some((16, 10)));
+    assert_eq!(
+        at(source_map.param(&person, Some(Method::Put), "name")),
+        Some((19, 16))
+    );
+    assert_eq!(
+        at(source_map.param(&person, Some(Method::Put), "lang")),
+        Some((4, 10))
+    );
+    assert_eq!(source_map.param(&person, None, "name"), None);
+}
+
+#[test]
+fn test_validate_with_source_map() {
+    let input = "/
+  people
+    GET People::Index
+    GET People::List
+";
+    let config = parse(input);
+    let source_map = wayfinder::parse::source_map(input, &config).with_file("app.routes");
+
+    let diagnostics = config.validate_with_source_map(&source_map);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "app.routes:4:5: error: method GET is declared more than once; `People::List` is unreachable (at /people)"
+    );
+}
+
+#[test]
+fn test_check_param_types() {
+    let input = "use uuid::Uuid;
+
+/
+  [lang: Strng]
+  people
+    {id: Uuid}
+      GET People::Show
+        [page: usize]
+    {slug: raw Slug}
+      GET People::Find
+";
+    let config = parse(input);
+    let source_map = wayfinder::parse::source_map(input, &config);
+
+    let diagnostics = config.check_param_types(&source_map);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    let found = diagnostics
+        .iter()
+        .map(|d| {
+            let location = d.location.as_ref().unwrap();
+            (d.message.as_str(), location.line, location.column)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            ("cannot find type `Strng` for parameter `lang`", 4, 10),
+            ("cannot find type `Slug` for parameter `slug`", 9, 16),
+        ]
+    );
+}
+
+#[test]
+fn test_check_param_types_headers() {
+    let input = "use std::{net::IpAddr, num::NonZeroU32 as Count};
+use chrono::{self, NaiveDate};
+pub struct Slug(String);
+type Id = u64; // struct Commented;
+
+/
+  {a: IpAddr}
+    GET A
+  b
+    {b: Count}
+      GET B
+  c
+    {c: NaiveDate}
+      GET C
+  d
+    {d: Slug}
+      GET D
+  e
+    {e: Id}
+      GET E
+  f
+    {f: NonZeroU32}
+      GET F
+  g
+    {g: Commented}
+      GET G
+";
+    let config = parse(input);
+
+    let diagnostics = config.check_param_types(&SourceMap::default());
+    let messages = diagnostics.iter().map(|d| &d.message).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "cannot find type `NonZeroU32` for parameter `f`",
+            "cannot find type `Commented` for parameter `g`",
+        ]
+    );
+}
+
+#[test]
+fn test_check_param_types_glob() {
+    let config = parse(
+        "use crate::types::*;
+
+/
+  {id: Uuid}
+    GET Show
+",
+    );
+
+    assert_eq!(config.check_param_types(&SourceMap::default()), vec![]);
+}
+
+#[test]
+fn test_codegen_rejects_invalid() {
+    let config = parse(
+        "/
+  people
+    GET People::Index
+  people
+    POST People::Create
+",
+    );
+
+    let mut dest = vec![];
+    let err = wayfinder::gen::codegen(&mut dest, &config).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
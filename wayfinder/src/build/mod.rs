@@ -4,12 +4,15 @@
 //! more details and examples.
 
 use std::env;
+use std::error;
+use std::fmt;
 use std::fs::File;
+use std::io;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::core::RouteConfig;
-use crate::gen::codegen;
+use crate::core::{Diagnostic, RouteConfig, SourceMap, TrailingSlash};
+use crate::gen::{check_handler_names, codegen_with_source_map, CodegenOptions};
 use crate::parse;
 use crate::parse::errors::show_errors;
 
@@ -25,27 +28,198 @@ macro_rules! fail {
     };
 }
 
-enum Either<L, R> {
-    Left(L),
-    Right(R),
+/// The setters for the codegen options that a `Job` and the `Builder`
+/// both have, so that they're documented the same way.
+macro_rules! option_setters {
+    ($t:ident) => {
+        /// Set the visibility of the generated module, for instance
+        /// `pub(crate)`.  Defaults to `pub`.
+        pub fn visibility<S: AsRef<str>>(mut self, visibility: S) -> $t {
+            self.options = self.options.visibility(visibility);
+            self
+        }
+
+        /// Set how paths with a trailing slash are matched.  Defaults to
+        /// `TrailingSlash::Lenient`.  See
+        /// [`CodegenOptions::trailing_slash`](../gen/struct.CodegenOptions.html#method.trailing_slash).
+        pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> $t {
+            self.options = self.options.trailing_slash(trailing_slash);
+            self
+        }
+
+        /// Collapse repeated slashes in a path before matching it.  Off by
+        /// default.  See
+        /// [`CodegenOptions::collapse_slashes`](../gen/struct.CodegenOptions.html#method.collapse_slashes).
+        pub fn collapse_slashes(mut self, collapse_slashes: bool) -> $t {
+            self.options = self.options.collapse_slashes(collapse_slashes);
+            self
+        }
+
+        /// Resolve `.` and `..` segments in a path before matching it.  Off
+        /// by default.  See
+        /// [`CodegenOptions::resolve_dot_segments`](../gen/struct.CodegenOptions.html#method.resolve_dot_segments).
+        pub fn resolve_dot_segments(mut self, resolve_dot_segments: bool) -> $t {
+            self.options = self.options.resolve_dot_segments(resolve_dot_segments);
+            self
+        }
+
+        /// Match static path segments ignoring ASCII case.  Off by default.
+        /// See
+        /// [`CodegenOptions::ignore_case`](../gen/struct.CodegenOptions.html#method.ignore_case).
+        pub fn ignore_case(mut self, ignore_case: bool) -> $t {
+            self.options = self.options.ignore_case(ignore_case);
+            self
+        }
+
+        /// Borrow `String` parameters from the matched path as
+        /// `Cow<'a, str>`.  Off by default.  See
+        /// [`CodegenOptions::borrowed`](../gen/struct.CodegenOptions.html#method.borrowed).
+        pub fn borrowed(mut self, borrowed: bool) -> $t {
+            self.options = self.options.borrowed(borrowed);
+            self
+        }
+
+        /// Generate code that only uses `core` and `alloc`.  Off by
+        /// default.  See
+        /// [`CodegenOptions::no_std`](../gen/struct.CodegenOptions.html#method.no_std).
+        pub fn no_std(mut self, no_std: bool) -> $t {
+            self.options = self.options.no_std(no_std);
+            self
+        }
+
+        /// Also generate a `Handlers` trait and `dispatch` function.  Off
+        /// by default.  See
+        /// [`CodegenOptions::handlers`](../gen/struct.CodegenOptions.html#method.handlers).
+        pub fn handlers(mut self, handlers: bool) -> $t {
+            self.options = self.options.handlers(handlers);
+            self
+        }
+
+        /// Also generate an `AsyncHandlers` trait and async `dispatch`
+        /// function.  Off by default.  See
+        /// [`CodegenOptions::async_handlers`](../gen/struct.CodegenOptions.html#method.async_handlers).
+        pub fn async_handlers(mut self, async_handlers: bool) -> $t {
+            self.options = self.options.async_handlers(async_handlers);
+            self
+        }
+
+        /// Also generate helpers for the `http` crate.  Off by default.
+        ///
+        /// Requires the `http` feature.  See
+        /// [`CodegenOptions::http`](../gen/struct.CodegenOptions.html#method.http).
+        #[cfg(feature = "http")]
+        pub fn http(mut self, http: bool) -> $t {
+            self.options = self.options.http(http);
+            self
+        }
+
+        /// Also generate a `tower::Service` router.  Off by default.
+        ///
+        /// Requires the `tower` feature.  See
+        /// [`CodegenOptions::tower`](../gen/struct.CodegenOptions.html#method.tower).
+        #[cfg(feature = "tower")]
+        pub fn tower(mut self, tower: bool) -> $t {
+            self.options = self.options.tower(tower);
+            self
+        }
+    };
+}
+
+/// The reasons a route matcher build can fail.
+#[derive(Debug)]
+pub enum BuildError {
+    /// A Cargo environment variable is missing or not valid Unicode.
+    Env {
+        name: &'static str,
+        error: env::VarError,
+    },
+    /// No route config input was set.
+    MissingInput,
+    /// No output target was set.
+    MissingOutput,
+    /// Reading the route config file or writing the output failed.
+    Io { path: PathBuf, error: io::Error },
+    /// The route config file could not be parsed.
+    Parse { path: PathBuf, message: String },
+    /// The route config has errors.  All diagnostics are included,
+    /// warnings as well as errors.
+    Validation(Vec<Diagnostic>),
+    /// Generating the route matcher failed.
+    Codegen(io::Error),
 }
 
-impl<L: Write, R: Write> Write for Either<L, R> {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Either::Left(l) => l.write(buf),
-            Either::Right(r) => r.write(buf),
+            BuildError::Env {
+                name,
+                error: env::VarError::NotPresent,
+            } => write!(f, "Env var {} not found.", name),
+            BuildError::Env {
+                name,
+                error: env::VarError::NotUnicode(_),
+            } => write!(f, "Env var {} not valid Unicode.", name),
+            BuildError::MissingInput => write!(
+                f,
+                "Source not configured.  Try builder.input_file(\"app.routes\")."
+            ),
+            BuildError::MissingOutput => write!(
+                f,
+                "Target not configured.  Try builder.output_file(\"routes.rs\")."
+            ),
+            BuildError::Io { path, error } => {
+                write!(f, "Unable to access {}: {}", path.display(), error)
+            }
+            BuildError::Parse { path, message } => write!(
+                f,
+                "Unable to parse route config file {}:\n{}",
+                path.display(),
+                message
+            ),
+            BuildError::Validation(diagnostics) => {
+                write!(f, "Route config is invalid:")?;
+                for diagnostic in diagnostics.iter() {
+                    write!(f, "\n{}", diagnostic)?;
+                }
+                Ok(())
+            }
+            BuildError::Codegen(error) => write!(f, "Error generating code: {}", error),
         }
     }
+}
 
-    fn flush(&mut self) -> std::io::Result<()> {
+impl error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            Either::Left(l) => l.flush(),
-            Either::Right(r) => r.flush(),
+            BuildError::Env { error, .. } => Some(error),
+            BuildError::Io { error, .. } => Some(error),
+            BuildError::Codegen(error) => Some(error),
+            _ => None,
         }
     }
 }
 
+/// The result of a successful route matcher build.
+#[derive(Debug)]
+pub struct BuildOutput {
+    /// The output of each job, in the order they were added.
+    pub jobs: Vec<JobOutput>,
+}
+
+/// The result of a single job in a successful build.
+#[derive(Debug)]
+pub struct JobOutput {
+    /// The name of the generated module.
+    pub module_name: String,
+    /// The file the route matcher was written to, if not standard out.
+    pub output_file: Option<PathBuf>,
+    /// Whether the output was written.  Output files are only rewritten
+    /// if their contents change, so this is `false` if nothing changed.
+    pub changed: bool,
+    /// Any warnings found validating the route config.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
 enum Source {
     File(PathBuf),
     Config(RouteConfig),
@@ -56,6 +230,60 @@ enum Target {
     Stdout,
 }
 
+/// A single route matcher to generate as part of a build.
+///
+/// Add jobs to a [`Builder`](struct.Builder.html) to generate several
+/// independent routers in one build script, for instance a public
+/// API and an admin API:
+///
+/// ```ignore
+/// Builder::from_env()
+///     .job(Job::from_file("public.routes", "public.rs", "public_api"))
+///     .job(
+///         Job::from_config(admin::routes(), "admin.rs", "admin_api")
+///             .visibility("pub(crate)"),
+///     )
+///     .build();
+/// ```
+pub struct Job {
+    source: Source,
+    target: Target,
+    options: CodegenOptions,
+}
+
+impl Job {
+    /// Generate the module `name` from the route file `input`, writing
+    /// it to the file `output`.
+    pub fn from_file<P, Q, S>(input: P, output: Q, name: S) -> Job
+    where
+        P: Into<PathBuf>,
+        Q: Into<PathBuf>,
+        S: AsRef<str>,
+    {
+        Job {
+            source: Source::File(input.into()),
+            target: Target::File(output.into()),
+            options: CodegenOptions::new().module_name(name),
+        }
+    }
+
+    /// Generate the module `name` from the route config, writing it
+    /// to the file `output`.
+    pub fn from_config<Q, S>(config: RouteConfig, output: Q, name: S) -> Job
+    where
+        Q: Into<PathBuf>,
+        S: AsRef<str>,
+    {
+        Job {
+            source: Source::Config(config),
+            target: Target::File(output.into()),
+            options: CodegenOptions::new().module_name(name),
+        }
+    }
+
+    option_setters!(Job);
+}
+
 /// Helper for building a server-side route matcher.
 ///
 /// Basic usage in a build script looks like:
@@ -66,12 +294,27 @@ enum Target {
 ///     .output_file("routes.rs")
 ///     .build();
 /// ```
+///
+/// To generate more than one router, see [`Job`](struct.Job.html).
 pub struct Builder {
     use_cargo: bool,
+    env_error: Option<BuildError>,
     source_dir: Option<PathBuf>,
     source: Option<Source>,
     target_dir: Option<PathBuf>,
     target: Option<Target>,
+    options: CodegenOptions,
+    jobs: Vec<Job>,
+    check_types: bool,
+    line_map: bool,
+    #[cfg(feature = "format")]
+    format: bool,
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
 }
 
 impl Builder {
@@ -79,32 +322,51 @@ impl Builder {
     pub fn new() -> Builder {
         Builder {
             use_cargo: false,
+            env_error: None,
             source_dir: None,
             source: None,
             target_dir: None,
             target: None,
+            options: CodegenOptions::new(),
+            jobs: vec![],
+            check_types: true,
+            line_map: false,
+            #[cfg(feature = "format")]
+            format: false,
         }
     }
 
     /// Create a new builder for use from a Cargo build script.
+    ///
+    /// If the Cargo environment variables are missing, the error is
+    /// reported when the build is run.
     pub fn from_env() -> Builder {
-        let source_dir = match env::var("CARGO_MANIFEST_DIR") {
-            Err(env::VarError::NotPresent) => fail!("Env var CARGO_MANIFEST_DIR not found."),
-            Err(env::VarError::NotUnicode(_)) => fail!("Env var CARGO_MANIFEST_DIR not valid Unicode."),
-            Ok(d) => Some(d.into()),
-        };
-        let target_dir = match env::var("OUT_DIR") {
-            Err(env::VarError::NotPresent) => fail!("Env var OUT_DIR not found."),
-            Err(env::VarError::NotUnicode(_)) => fail!("Env var OUT_DIR not valid Unicode."),
+        let mut env_error = None;
+        let mut var = |name| match env::var(name) {
             Ok(d) => Some(d.into()),
+            Err(error) => {
+                if env_error.is_none() {
+                    env_error = Some(BuildError::Env { name, error });
+                }
+                None
+            }
         };
+        let source_dir = var("CARGO_MANIFEST_DIR");
+        let target_dir = var("OUT_DIR");
 
         Builder {
             use_cargo: true,
+            env_error,
             source_dir,
             source: None,
             target_dir,
             target: None,
+            options: CodegenOptions::new(),
+            jobs: vec![],
+            check_types: true,
+            line_map: false,
+            #[cfg(feature = "format")]
+            format: false,
         }
     }
 
@@ -134,69 +396,288 @@ impl Builder {
         self
     }
 
+    /// Set the name of the generated module.  Defaults to `routes`.
+    pub fn module_name<S: AsRef<str>>(mut self, name: S) -> Builder {
+        self.options = self.options.module_name(name);
+        self
+    }
+
+    option_setters!(Builder);
+
+    /// Check that every parameter type is in scope in the generated
+    /// code, warning about unknown types with their route file
+    /// location.  On by default.
+    ///
+    /// The check can't see everything that's in scope, so it never
+    /// fails the build.  To trace rustc's errors in the generated code
+    /// back to the route file, see [`line_map`](#method.line_map).
+    ///
+    /// See [`RouteConfig::check_param_types`](../struct.RouteConfig.html#method.check_param_types)
+    /// for what is checked.
+    pub fn check_types(mut self, check_types: bool) -> Builder {
+        self.check_types = check_types;
+        self
+    }
+
+    /// Document each generated request struct and field with where it
+    /// was declared in the route file, so that compile errors in the
+    /// generated code can be traced back to the route file.  Off by
+    /// default.
+    ///
+    /// Only route config files have locations to map.
+    pub fn line_map(mut self, line_map: bool) -> Builder {
+        self.line_map = line_map;
+        self
+    }
+
+    /// Pretty-print the generated code so it reads like hand-written
+    /// Rust.  Off by default.
+    ///
+    /// Requires the `format` feature.
+    #[cfg(feature = "format")]
+    pub fn format(mut self, format: bool) -> Builder {
+        self.format = format;
+        self
+    }
+
+    /// Add another route matcher to generate.
+    ///
+    /// Jobs are run after the one configured directly on the builder,
+    /// if any.  Jobs writing to the same output file are concatenated.
+    pub fn job(mut self, job: Job) -> Builder {
+        self.jobs.push(job);
+        self
+    }
+
     /// Execute the route matcher build.
     ///
+    /// Validation warnings are printed, as `cargo:warning` lines when
+    /// run from a build script.
+    ///
     /// # Process termination
     ///
-    /// This method exits the process if an error is encountered.
-    // TODO: should it return a result instead?
+    /// This method exits the process if an error is encountered.  Use
+    /// [`try_build`](#method.try_build) to handle errors yourself.
     pub fn build(self) {
-        let source = match self.source {
-            None => fail!("Source not configured.  Try builder.input_file(\"app.routes\")."),
-            Some(s) => s,
-        };
-        let target = match self.target {
-            None => fail!("Target not configured.  Try builder.output_file(\"routes.rs\")."),
-            Some(t) => t,
+        let use_cargo = self.use_cargo;
+        let show = |diagnostic: &Diagnostic| {
+            if use_cargo && !diagnostic.is_error() {
+                println!("cargo:warning={}", diagnostic);
+            } else {
+                eprintln!("{}", diagnostic);
+            }
         };
 
-        let input = match source {
-            Source::Config(c) => c,
+        match self.try_build() {
+            Ok(output) => output
+                .jobs
+                .iter()
+                .flat_map(|job| job.diagnostics.iter())
+                .for_each(show),
+            Err(BuildError::Validation(diagnostics)) => {
+                diagnostics.iter().for_each(show);
+                fail!("Route config is invalid, not generating code.");
+            }
+            Err(e) => fail!("{}", e),
+        }
+    }
+
+    /// Execute the route matcher build, returning any error encountered.
+    ///
+    /// Nothing is written unless every job succeeds, and output files
+    /// are only rewritten if their contents change, to avoid triggering
+    /// needless recompilation.
+    ///
+    /// ```
+    /// # use wayfinder::build::{Builder, BuildError};
+    /// # use wayfinder::{get, RouteConfig, Routes};
+    /// let config = RouteConfig {
+    ///     headers: vec![],
+    ///     routes: Routes {
+    ///         resources: vec![get!(Index), get!(Home)],
+    ///         ..Routes::default()
+    ///     },
+    /// };
+    ///
+    /// let result = Builder::new()
+    ///     .input_config(config)
+    ///     .output_stdout()
+    ///     .try_build();
+    ///
+    /// match result {
+    ///     Err(BuildError::Validation(diagnostics)) => assert_eq!(diagnostics.len(), 1),
+    ///     _ => panic!("expected a validation error"),
+    /// }
+    /// ```
+    pub fn try_build(mut self) -> Result<BuildOutput, BuildError> {
+        if let Some(e) = self.env_error.take() {
+            return Err(e);
+        }
+
+        let mut jobs = vec![];
+        match (self.source.take(), self.target.take()) {
+            (Some(source), Some(target)) => jobs.push(Job {
+                source,
+                target,
+                options: self.options.clone(),
+            }),
+            (None, Some(_)) => return Err(BuildError::MissingInput),
+            (Some(_), None) => return Err(BuildError::MissingOutput),
+            (None, None) if self.jobs.is_empty() => return Err(BuildError::MissingInput),
+            (None, None) => {}
+        }
+        jobs.append(&mut self.jobs);
+
+        let mut outputs: Vec<(Option<PathBuf>, Vec<u8>)> = vec![];
+        let mut results = vec![];
+        for job in jobs {
+            let (result, code) = self.run_job(job)?;
+
+            match outputs
+                .iter_mut()
+                .find(|(file, _)| *file == result.output_file)
+            {
+                Some((_, existing)) => existing.extend_from_slice(&code),
+                None => outputs.push((result.output_file.clone(), code)),
+            }
+
+            results.push(result);
+        }
+
+        for (output_file, code) in outputs {
+            let written = match output_file {
+                None => std::io::stdout().write_all(&code).map(|_| true),
+                Some(ref output_file) => write_if_changed(output_file, &code),
+            };
+            match written {
+                Ok(changed) => {
+                    for result in results.iter_mut() {
+                        if result.output_file == output_file {
+                            result.changed = changed;
+                        }
+                    }
+                }
+                Err(error) => {
+                    return Err(BuildError::Io {
+                        path: output_file.unwrap_or_else(|| "<stdout>".into()),
+                        error,
+                    })
+                }
+            }
+        }
+
+        Ok(BuildOutput { jobs: results })
+    }
+
+    fn run_job(&self, job: Job) -> Result<(JobOutput, Vec<u8>), BuildError> {
+        let Job {
+            source,
+            target,
+            options,
+        } = job;
+
+        let (input, source_map) = match source {
+            Source::Config(c) => (c, SourceMap::default()),
             Source::File(filename) => {
                 let input_file = match self.source_dir {
                     None => filename,
-                    Some(dir) => dir.join(filename),
+                    Some(ref dir) => dir.join(filename),
                 };
                 if self.use_cargo {
                     println!("cargo:rerun-if-changed={}", input_file.display());
                 }
 
-                let contents = match std::fs::read_to_string(input_file.clone()) {
+                let contents = match std::fs::read_to_string(&input_file) {
                     Ok(c) => c,
-                    Err(e) => fail!("Unable to load file {}: {}", input_file.display(), e),
+                    Err(error) => {
+                        return Err(BuildError::Io {
+                            path: input_file,
+                            error,
+                        })
+                    }
                 };
 
                 match parse::route_config(&contents) {
-                    Ok(config) => config.1,
+                    Ok((_, config)) => {
+                        let source_map =
+                            parse::source_map(&contents, &config).with_file(input_file);
+                        (config, source_map)
+                    }
                     result => {
-                        eprintln!("Unable to parse route config file {}:", input_file.display());
-                        show_errors(&mut std::io::stderr(), &contents, result, "");
-                        fail!()
+                        let mut message = vec![];
+                        show_errors(&mut message, &contents, result, "");
+                        return Err(BuildError::Parse {
+                            path: input_file,
+                            message: String::from_utf8_lossy(&message).into_owned(),
+                        });
                     }
                 }
             }
         };
 
-        let mut output = match target {
-            Target::Stdout => Either::Left(std::io::stdout()),
-            Target::File(filename) => {
-                let output_file = match self.target_dir {
-                    None => filename,
-                    Some(dir) => dir.join(filename),
-                };
+        let mut diagnostics = input.validate_with_source_map(&source_map);
+        diagnostics.extend(check_handler_names(&input.routes, &options, &source_map));
+        if self.check_types {
+            diagnostics.extend(input.check_param_types(&source_map));
+        }
+        if diagnostics.iter().any(|d| d.is_error()) {
+            return Err(BuildError::Validation(diagnostics));
+        }
 
-                match File::create(&output_file) {
-                    Ok(f) => Either::Right(f),
-                    Err(e) => fail!("Unable to create output file {}: {}", output_file.display(), e),
-                }
-            }
+        let source_map = if self.line_map {
+            source_map
+        } else {
+            SourceMap::default()
         };
 
-        match codegen(&mut output, &input) {
-            Ok(_) => {}
-            Err(e) => {
-                fail!("Error generating code: {}", e);
-            }
-        }
+        let mut code = vec![];
+        codegen_with_source_map(&mut code, &input, &options, &source_map)
+            .map_err(BuildError::Codegen)?;
+
+        #[cfg(feature = "format")]
+        let code = if self.format {
+            format_code(&code).map_err(BuildError::Codegen)?
+        } else {
+            code
+        };
+
+        let output_file = match target {
+            Target::Stdout => None,
+            Target::File(filename) => Some(match self.target_dir {
+                None => filename,
+                Some(ref dir) => dir.join(filename),
+            }),
+        };
+
+        let output = JobOutput {
+            module_name: options.module_name,
+            output_file,
+            changed: false,
+            diagnostics,
+        };
+
+        Ok((output, code))
     }
 }
+
+fn write_if_changed(path: &Path, contents: &[u8]) -> io::Result<bool> {
+    match std::fs::read(path) {
+        Ok(ref existing) if existing.as_slice() == contents => return Ok(false),
+        Ok(_) => {}
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    File::create(path).and_then(|mut f| f.write_all(contents))?;
+    Ok(true)
+}
+
+#[cfg(feature = "format")]
+fn format_code(code: &[u8]) -> io::Result<Vec<u8>> {
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+
+    let code = std::str::from_utf8(code).map_err(|e| invalid(e.to_string()))?;
+    let file = syn::parse_file(code).map_err(|e| invalid(e.to_string()))?;
+
+    Ok(prettyplease::unparse(&file).into_bytes())
+}
@@ -19,43 +19,37 @@ macro_rules! header {
 /// Create a [`Resource`](struct.Resource.html) for an HTTP GET request.
 ///
 /// ```
-/// # use wayfinder::{get, Resource, Method};
+/// # use wayfinder::{get, Method};
 /// let show_person = get!(People::Show);
-/// # assert_eq!(show_person, Resource {
-/// #     method: Method::Get,
-/// #     modules: vec!["People".to_string()],
-/// #     name: "Show".to_string(),
-/// #     is_redirect: false,
-/// #     query_parameters: vec![],
-/// # });
+/// # assert_eq!(show_person.method, Method::Get);
+/// # assert_eq!(show_person.modules, vec!["People".to_string()]);
+/// # assert_eq!(show_person.name, "Show".to_string());
+/// # assert_eq!(show_person.is_redirect, false);
+/// # assert!(show_person.query_parameters.is_empty());
 /// ```
 ///
 /// Use the sigil `->` at the start to indicate a redirect-style route.
 ///
 /// ```
-/// # use wayfinder::{get, Resource, Method};
+/// # use wayfinder::{get, Method};
 /// let show_person_redirect = get!(-> People::Show);
-/// # assert_eq!(show_person_redirect, Resource {
-/// #     method: Method::Get,
-/// #     modules: vec!["People".to_string()],
-/// #     name: "Show".to_string(),
-/// #     is_redirect: true,
-/// #     query_parameters: vec![],
-/// # });
+/// # assert_eq!(show_person_redirect.method, Method::Get);
+/// # assert_eq!(show_person_redirect.modules, vec!["People".to_string()]);
+/// # assert_eq!(show_person_redirect.name, "Show".to_string());
+/// # assert_eq!(show_person_redirect.is_redirect, true);
+/// # assert!(show_person_redirect.query_parameters.is_empty());
 /// ```
 ///
 /// Add query parameters after the handler name.
 ///
 /// ```
-/// # use wayfinder::{get, param, Resource, Method, Param};
+/// # use wayfinder::{get, param, Method, Param};
 /// let search = get!(Search, param!(q: String));
-/// # assert_eq!(search, Resource {
-/// #     method: Method::Get,
-/// #     modules: vec![],
-/// #     name: "Search".to_string(),
-/// #     is_redirect: false,
-/// #     query_parameters: vec![param!(q: String)],
-/// # });
+/// # assert_eq!(search.method, Method::Get);
+/// # assert!(search.modules.is_empty());
+/// # assert_eq!(search.name, "Search".to_string());
+/// # assert_eq!(search.is_redirect, false);
+/// # assert_eq!(search.query_parameters, vec![param!(q: String)]);
 /// ```
 #[macro_export]
 macro_rules! get {
@@ -84,43 +78,37 @@ macro_rules! get {
 /// Create a [`Resource`](struct.Resource.html) for an HTTP POST request.
 ///
 /// ```
-/// # use wayfinder::{post, Resource, Method};
+/// # use wayfinder::{post, Method};
 /// let create_person = post!(People::Create);
-/// # assert_eq!(create_person, Resource {
-/// #     method: Method::Post,
-/// #     modules: vec!["People".to_string()],
-/// #     name: "Create".to_string(),
-/// #     is_redirect: false,
-/// #     query_parameters: vec![],
-/// # });
+/// # assert_eq!(create_person.method, Method::Post);
+/// # assert_eq!(create_person.modules, vec!["People".to_string()]);
+/// # assert_eq!(create_person.name, "Create".to_string());
+/// # assert_eq!(create_person.is_redirect, false);
+/// # assert!(create_person.query_parameters.is_empty());
 /// ```
 ///
 /// Use the sigil `->` at the start to indicate a redirect-style route.
 ///
 /// ```
-/// # use wayfinder::{post, Resource, Method};
+/// # use wayfinder::{post, Method};
 /// let create_person_redirect = post!(-> People::Create);
-/// # assert_eq!(create_person_redirect, Resource {
-/// #     method: Method::Post,
-/// #     modules: vec!["People".to_string()],
-/// #     name: "Create".to_string(),
-/// #     is_redirect: true,
-/// #     query_parameters: vec![],
-/// # });
+/// # assert_eq!(create_person_redirect.method, Method::Post);
+/// # assert_eq!(create_person_redirect.modules, vec!["People".to_string()]);
+/// # assert_eq!(create_person_redirect.name, "Create".to_string());
+/// # assert_eq!(create_person_redirect.is_redirect, true);
+/// # assert!(create_person_redirect.query_parameters.is_empty());
 /// ```
 ///
 /// Add query parameters after the handler name.
 ///
 /// ```
-/// # use wayfinder::{post, param, Resource, Method, Param};
+/// # use wayfinder::{post, param, Method, Param};
 /// let search = post!(Search, param!(q: String));
-/// # assert_eq!(search, Resource {
-/// #     method: Method::Post,
-/// #     modules: vec![],
-/// #     name: "Search".to_string(),
-/// #     is_redirect: false,
-/// #     query_parameters: vec![param!(q: String)],
-/// # });
+/// # assert_eq!(search.method, Method::Post);
+/// # assert!(search.modules.is_empty());
+/// # assert_eq!(search.name, "Search".to_string());
+/// # assert_eq!(search.is_redirect, false);
+/// # assert_eq!(search.query_parameters, vec![param!(q: String)]);
 /// ```
 #[macro_export]
 macro_rules! post {
@@ -149,43 +137,37 @@ macro_rules! post {
 /// Create a [`Resource`](struct.Resource.html) for an HTTP PUT request.
 ///
 /// ```
-/// # use wayfinder::{put, Resource, Method};
+/// # use wayfinder::{put, Method};
 /// let update_person = put!(People::Update);
-/// # assert_eq!(update_person, Resource {
-/// #     method: Method::Put,
-/// #     modules: vec!["People".to_string()],
-/// #     name: "Update".to_string(),
-/// #     is_redirect: false,
-/// #     query_parameters: vec![],
-/// # });
+/// # assert_eq!(update_person.method, Method::Put);
+/// # assert_eq!(update_person.modules, vec!["People".to_string()]);
+/// # assert_eq!(update_person.name, "Update".to_string());
+/// # assert_eq!(update_person.is_redirect, false);
+/// # assert!(update_person.query_parameters.is_empty());
 /// ```
 ///
 /// Use the sigil `->` at the start to indicate a redirect-style route.
 ///
 /// ```
-/// # use wayfinder::{put, Resource, Method};
+/// # use wayfinder::{put, Method};
 /// let update_person_redirect = put!(-> People::Update);
-/// # assert_eq!(update_person_redirect, Resource {
-/// #     method: Method::Put,
-/// #     modules: vec!["People".to_string()],
-/// #     name: "Update".to_string(),
-/// #     is_redirect: true,
-/// #     query_parameters: vec![],
-/// # });
+/// # assert_eq!(update_person_redirect.method, Method::Put);
+/// # assert_eq!(update_person_redirect.modules, vec!["People".to_string()]);
+/// # assert_eq!(update_person_redirect.name, "Update".to_string());
+/// # assert_eq!(update_person_redirect.is_redirect, true);
+/// # assert!(update_person_redirect.query_parameters.is_empty());
 /// ```
 ///
 /// Add query parameters after the handler name.
 ///
 /// ```
-/// # use wayfinder::{put, param, Resource, Method, Param};
+/// # use wayfinder::{put, param, Method, Param};
 /// let update_person_by_id = put!(Person::Update, param!(id: i32));
-/// # assert_eq!(update_person_by_id, Resource {
-/// #     method: Method::Put,
-/// #     modules: vec!["Person".to_string()],
-/// #     name: "Update".to_string(),
-/// #     is_redirect: false,
-/// #     query_parameters: vec![param!(id: i32)],
-/// # });
+/// # assert_eq!(update_person_by_id.method, Method::Put);
+/// # assert_eq!(update_person_by_id.modules, vec!["Person".to_string()]);
+/// # assert_eq!(update_person_by_id.name, "Update".to_string());
+/// # assert_eq!(update_person_by_id.is_redirect, false);
+/// # assert_eq!(update_person_by_id.query_parameters, vec![param!(id: i32)]);
 /// ```
 #[macro_export]
 macro_rules! put {
@@ -214,43 +196,37 @@ macro_rules! put {
 /// Create a [`Resource`](struct.Resource.html) for an HTTP DELETE request.
 ///
 /// ```
-/// # use wayfinder::{delete, Resource, Method};
+/// # use wayfinder::{delete, Method};
 /// let destroy_person = delete!(People::Destroy);
-/// # assert_eq!(destroy_person, Resource {
-/// #     method: Method::Delete,
-/// #     modules: vec!["People".to_string()],
-/// #     name: "Destroy".to_string(),
-/// #     is_redirect: false,
-/// #     query_parameters: vec![],
-/// # });
+/// # assert_eq!(destroy_person.method, Method::Delete);
+/// # assert_eq!(destroy_person.modules, vec!["People".to_string()]);
+/// # assert_eq!(destroy_person.name, "Destroy".to_string());
+/// # assert_eq!(destroy_person.is_redirect, false);
+/// # assert!(destroy_person.query_parameters.is_empty());
 /// ```
 ///
 /// Use the sigil `->` at the start to indicate a redirect-style route.
 ///
 /// ```
-/// # use wayfinder::{delete, Resource, Method};
+/// # use wayfinder::{delete, Method};
 /// let destroy_person_redirect = delete!(-> People::Destroy);
-/// # assert_eq!(destroy_person_redirect, Resource {
-/// #     method: Method::Delete,
-/// #     modules: vec!["People".to_string()],
-/// #     name: "Destroy".to_string(),
-/// #     is_redirect: true,
-/// #     query_parameters: vec![],
-/// # });
+/// # assert_eq!(destroy_person_redirect.method, Method::Delete);
+/// # assert_eq!(destroy_person_redirect.modules, vec!["People".to_string()]);
+/// # assert_eq!(destroy_person_redirect.name, "Destroy".to_string());
+/// # assert_eq!(destroy_person_redirect.is_redirect, true);
+/// # assert!(destroy_person_redirect.query_parameters.is_empty());
 /// ```
 ///
 /// Add query parameters after the handler name.
 ///
 /// ```
-/// # use wayfinder::{delete, param, Resource, Method, Param};
+/// # use wayfinder::{delete, param, Method, Param};
 /// let destroy_person_by_id = delete!(Person::Destroy, param!(id: i32));
-/// # assert_eq!(destroy_person_by_id, Resource {
-/// #     method: Method::Delete,
-/// #     modules: vec!["Person".to_string()],
-/// #     name: "Destroy".to_string(),
-/// #     is_redirect: false,
-/// #     query_parameters: vec![param!(id: i32)],
-/// # });
+/// # assert_eq!(destroy_person_by_id.method, Method::Delete);
+/// # assert_eq!(destroy_person_by_id.modules, vec!["Person".to_string()]);
+/// # assert_eq!(destroy_person_by_id.name, "Destroy".to_string());
+/// # assert_eq!(destroy_person_by_id.is_redirect, false);
+/// # assert_eq!(destroy_person_by_id.query_parameters, vec![param!(id: i32)]);
 /// ```
 #[macro_export]
 macro_rules! delete {
@@ -284,8 +260,21 @@ macro_rules! delete {
 /// let username = param!(username: String);
 /// # assert_eq!(username, Param::new("username", "String"));
 /// ```
+///
+/// Mark a parameter `raw` to skip percent-decoding it.
+///
+/// ```
+/// # use wayfinder::{param, Param};
+/// let path = param!(path: raw String);
+/// # assert_eq!(path, Param::new_raw("path", "String"));
+/// ```
 #[macro_export]
 macro_rules! param {
+    (
+        $name:ident : raw $type:ty
+    ) => {
+        ::wayfinder::Param::new_raw(stringify!($name), stringify!($type))
+    };
     (
         $name:ident : $type:ty
     ) => {
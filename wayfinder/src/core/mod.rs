@@ -3,6 +3,15 @@ use std::fmt;
 mod macros;
 pub use macros::*;
 
+mod source_map;
+pub(crate) use source_map::{display_action, display_action_name};
+pub use source_map::*;
+
+mod types;
+
+mod validate;
+pub use validate::*;
+
 /// An entire routing file.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct RouteConfig {
@@ -101,17 +110,25 @@ impl Routes {
 }
 
 /// A resource available at a specific path.
+///
+/// Create one with [`Resource::make`](#method.make), or one of the
+/// macros like [`get!`](macro.get.html).
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Resource {
     pub method: Method,
     pub modules: Vec<String>,
     pub name: String,
     pub is_redirect: bool,
     pub query_parameters: Vec<Param>,
+    /// Parameters read from a form body, rather than the query string.
+    pub body_parameters: Vec<Param>,
 }
 
 impl Resource {
-    /// A helper for macros to use.  Generally, you should just use the struct create syntax.
+    /// A helper for macros to use.  Outside this crate, it's also the way
+    /// to create a resource, with [`body_parameters`](#method.body_parameters)
+    /// to add any body parameters.
     ///
     /// # Panics
     ///
@@ -133,16 +150,26 @@ impl Resource {
             name,
             is_redirect,
             query_parameters,
+            body_parameters: vec![],
         }
     }
 
+    /// Set the parameters read from a form body.
+    pub fn body_parameters(mut self, body_parameters: Vec<Param>) -> Resource {
+        self.body_parameters = body_parameters;
+        self
+    }
+
     pub fn stringify(&self, level: usize) -> String {
-        let params = self
+        let query_params = self
             .query_parameters
             .iter()
-            .map(|param| format!("\n{}[{}]", indent(level + 1), param))
-            .collect::<Vec<_>>()
-            .concat();
+            .map(|param| format!("\n{}[{}]", indent(level + 1), param));
+        let body_params = self
+            .body_parameters
+            .iter()
+            .map(|param| format!("\n{}<{}>", indent(level + 1), param));
+        let params = query_params.chain(body_params).collect::<Vec<_>>().concat();
 
         let modules = self
             .modules
@@ -164,10 +191,16 @@ impl Resource {
 }
 
 /// A block of routes nested under a path segment.
+///
+/// Create one with [`NestedRoutes::new`](#method.new).
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct NestedRoutes {
     pub path_segment: PathSegment,
     pub routes: Routes,
+    /// How trailing slashes are handled for these routes, or `None` to
+    /// use the same policy as the parent.
+    pub trailing_slash: Option<TrailingSlash>,
 }
 
 impl NestedRoutes {
@@ -175,22 +208,59 @@ impl NestedRoutes {
         NestedRoutes {
             path_segment: path_segment.into(),
             routes,
+            trailing_slash: None,
         }
     }
 
+    /// Set the trailing slash policy for these routes.
+    pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> NestedRoutes {
+        self.trailing_slash = Some(trailing_slash);
+        self
+    }
+
     pub fn stringify(&self, level: usize) -> String {
         format!(
-            "{}{}\n{}",
+            "{}{}{}\n{}",
             indent(level),
             match self.path_segment {
                 PathSegment::Static(ref p) => format!("{}", p),
                 PathSegment::Dynamic(ref p) => format!("{{{}}}", p),
             },
+            match self.trailing_slash {
+                Some(policy) => format!(" (trailing slash: {})", policy),
+                None => String::new(),
+            },
             self.routes.stringify(level + 1),
         )
     }
 }
 
+/// How a route matches a path with a trailing slash, like `/people/`.
+///
+/// The canonical path of a route has no trailing slash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TrailingSlash {
+    /// Match with or without the trailing slash.
+    #[default]
+    Lenient,
+    /// Only match the canonical path.
+    Strict,
+    /// Redirect `GET` requests to the canonical path.  Other methods
+    /// match with or without the trailing slash, since clients would
+    /// turn the redirect into a `GET`.
+    Redirect,
+}
+
+impl fmt::Display for TrailingSlash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            TrailingSlash::Lenient => "lenient",
+            TrailingSlash::Strict => "strict",
+            TrailingSlash::Redirect => "redirect",
+        })
+    }
+}
+
 /// A path segment is either a static string or a dynamic parameter.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PathSegment {
@@ -219,11 +289,20 @@ impl fmt::Display for PathSegment {
     }
 }
 
-/// Path and query parameters have a name and type.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Path, query and body parameters have a name and type.
+///
+/// Parameters are percent-decoded before they're parsed, unless they're
+/// `raw`, in which case the text is passed along exactly as it appears
+/// in the request, and written into links as is.
+///
+/// Create one with [`Param::new`](#method.new) or
+/// [`Param::new_raw`](#method.new_raw).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
 pub struct Param {
     pub name: String,
     pub typ: String, // TODO: something else?
+    pub raw: bool,
 }
 
 impl Param {
@@ -231,6 +310,15 @@ impl Param {
         Param {
             name: name.as_ref().to_string(),
             typ: typ.as_ref().to_string(),
+            raw: false,
+        }
+    }
+
+    /// Create a parameter that isn't percent-decoded.
+    pub fn new_raw<S: AsRef<str>, T: AsRef<str>>(name: S, typ: T) -> Param {
+        Param {
+            raw: true,
+            ..Param::new(name, typ)
         }
     }
 }
@@ -238,12 +326,15 @@ impl fmt::Display for Param {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(&self.name)?;
         f.write_str(": ")?;
+        if self.raw {
+            f.write_str("raw ")?;
+        }
         f.write_str(&self.typ)
     }
 }
 
 /// HTTP methods that resources can respond to.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Method {
     Get,
     Post,
@@ -269,15 +360,44 @@ impl fmt::Display for Method {
     }
 }
 
+/// An HTTP method that resources can't be declared for.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedMethod(pub http::Method);
+
+#[cfg(feature = "http")]
+impl fmt::Display for UnsupportedMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Method {} is not supported", self.0)
+    }
+}
+
+#[cfg(feature = "http")]
+impl std::error::Error for UnsupportedMethod {}
+
+#[cfg(feature = "http")]
+impl std::convert::TryFrom<&http::Method> for Method {
+    type Error = UnsupportedMethod;
+
+    fn try_from(method: &http::Method) -> Result<Method, UnsupportedMethod> {
+        match *method {
+            http::Method::GET => Ok(Method::Get),
+            http::Method::POST => Ok(Method::Post),
+            http::Method::PUT => Ok(Method::Put),
+            http::Method::DELETE => Ok(Method::Delete),
+            _ => Err(UnsupportedMethod(method.clone())),
+        }
+    }
+}
+
 #[cfg(feature = "http")]
-impl std::convert::From<&http::Method> for Method {
-    fn from(method: &http::Method) -> Method {
+impl From<Method> for http::Method {
+    fn from(method: Method) -> http::Method {
         match method {
-            &http::Method::GET => Method::Get,
-            &http::Method::POST => Method::Post,
-            &http::Method::PUT => Method::Put,
-            &http::Method::DELETE => Method::Delete,
-            _ => unimplemented!("Method unimplemented: {}", method.as_str()),
+            Method::Get => http::Method::GET,
+            Method::Post => http::Method::POST,
+            Method::Put => http::Method::PUT,
+            Method::Delete => http::Method::DELETE,
         }
     }
 }
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use super::{Method, PathSegment, Resource};
+
+/// A position in a route config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// The route config file, if known.
+    pub file: Option<PathBuf>,
+    /// The line number, starting from one.
+    pub line: usize,
+    /// The column number, starting from one.
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref file) = self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Where the parts of a route config were declared in the route file.
+///
+/// Build one with [`parse::source_map`](parse/fn.source_map.html).  A
+/// config built in code has no source, so the default, empty source
+/// map is used for it.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    routes: HashMap<String, Location>,
+    resources: HashMap<(String, Method, String), Location>,
+    params: HashMap<(String, Option<Method>, String), Location>,
+}
+
+pub(crate) fn display_path(path: &[PathSegment]) -> String {
+    if path.is_empty() {
+        return "/".to_string();
+    }
+
+    path.iter()
+        .map(|segment| match segment {
+            PathSegment::Static(s) => format!("/{}", s),
+            PathSegment::Dynamic(p) => format!("/{{{}}}", p),
+        })
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+pub(crate) fn display_action(resource: &Resource) -> String {
+    display_action_name(&resource.modules, &resource.name)
+}
+
+pub(crate) fn display_action_name(modules: &[String], name: &str) -> String {
+    modules
+        .iter()
+        .map(|module| format!("{}::", module))
+        .chain(std::iter::once(name.to_string()))
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+impl SourceMap {
+    /// Set the file name for every location in this source map.
+    pub fn with_file<P: Into<PathBuf>>(mut self, file: P) -> SourceMap {
+        let file = file.into();
+        let locations = self
+            .routes
+            .values_mut()
+            .chain(self.resources.values_mut())
+            .chain(self.params.values_mut());
+        for location in locations {
+            location.file = Some(file.clone());
+        }
+        self
+    }
+
+    /// The location of the route at this path, that is, the location
+    /// of its last path segment.
+    pub fn route(&self, path: &[PathSegment]) -> Option<&Location> {
+        self.routes.get(&display_path(path))
+    }
+
+    /// The location of the resource for the action `name`, such as
+    /// `People::Show`, at this path.
+    pub fn resource(&self, path: &[PathSegment], method: Method, name: &str) -> Option<&Location> {
+        self.resources
+            .get(&(display_path(path), method, name.to_string()))
+    }
+
+    /// The location of the type of the parameter `name`, as seen from
+    /// this path.  This might be a path segment or query parameter of
+    /// the route or any of its parents, or if a method is given, a
+    /// query parameter of that resource.
+    pub fn param(
+        &self,
+        path: &[PathSegment],
+        method: Option<Method>,
+        name: &str,
+    ) -> Option<&Location> {
+        let name = name.to_string();
+        if let Some(method) = method {
+            let key = (display_path(path), Some(method), name.clone());
+            if let Some(location) = self.params.get(&key) {
+                return Some(location);
+            }
+        }
+
+        (0..=path.len())
+            .rev()
+            .filter_map(|len| {
+                self.params
+                    .get(&(display_path(&path[..len]), None, name.clone()))
+            })
+            .next()
+    }
+
+    pub(crate) fn insert_route(&mut self, path: &[PathSegment], location: Location) {
+        self.routes.entry(display_path(path)).or_insert(location);
+    }
+
+    pub(crate) fn insert_resource(
+        &mut self,
+        path: &[PathSegment],
+        method: Method,
+        name: &str,
+        location: Location,
+    ) {
+        self.resources
+            .entry((display_path(path), method, name.to_string()))
+            .or_insert(location);
+    }
+
+    pub(crate) fn insert_param(
+        &mut self,
+        path: &[PathSegment],
+        method: Option<Method>,
+        name: &str,
+        location: Location,
+    ) {
+        self.params
+            .entry((display_path(path), method, name.to_string()))
+            .or_insert(location);
+    }
+}
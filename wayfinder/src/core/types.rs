@@ -0,0 +1,278 @@
+use std::collections::HashSet;
+
+use super::source_map::display_path;
+use super::{Diagnostic, Header, Location, PathSegment, RouteConfig, Routes, SourceMap};
+
+/// Types that are always in scope in the generated code.
+const PRELUDE: &[&str] = &[
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64", "String", "Box", "Option", "Result", "Vec",
+];
+
+impl RouteConfig {
+    /// Check that the type of every parameter is in scope in the
+    /// generated code, that is, that it's a primitive, in the standard
+    /// prelude, or brought in by one of the headers.
+    ///
+    /// Only plain identifiers are checked: paths like `uuid::Uuid` and
+    /// generic types are assumed to be fine.  If any header has a glob
+    /// import there's no telling what's in scope, so nothing is checked.
+    ///
+    /// Types can also come into scope in ways the headers don't show,
+    /// such as from macros, so unknown types are only warnings.  If the
+    /// type really is missing, rustc reports the error.
+    ///
+    /// ```
+    /// # use wayfinder::{parse, SourceMap};
+    /// let config = parse::route_config("use uuid::Uuid;\n\n/\n  {id: Uuid}\n    GET Show\n  {n: Nmber}\n    GET Find\n")
+    ///     .unwrap()
+    ///     .1;
+    ///
+    /// let diagnostics = config.check_param_types(&SourceMap::default());
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].message, "cannot find type `Nmber` for parameter `n`");
+    /// ```
+    pub fn check_param_types(&self, source_map: &SourceMap) -> Vec<Diagnostic> {
+        let known = match known_types(&self.headers) {
+            Some(known) => known,
+            None => return vec![],
+        };
+
+        let mut checker = TypeChecker {
+            known,
+            source_map,
+            diagnostics: vec![],
+        };
+        checker.check_routes(&self.routes, &mut vec![]);
+
+        checker.diagnostics
+    }
+}
+
+struct TypeChecker<'a> {
+    known: HashSet<String>,
+    source_map: &'a SourceMap,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> TypeChecker<'a> {
+    fn check_routes(&mut self, routes: &Routes, path: &mut Vec<PathSegment>) {
+        for param in routes.query_parameters.iter() {
+            let location = self.source_map.param(path, None, &param.name);
+            self.check_type(&param.name, &param.typ, path, location);
+        }
+
+        for resource in routes.resources.iter() {
+            let params = resource
+                .query_parameters
+                .iter()
+                .chain(resource.body_parameters.iter());
+            for param in params {
+                let location = self
+                    .source_map
+                    .param(path, Some(resource.method), &param.name);
+                self.check_type(&param.name, &param.typ, path, location);
+            }
+        }
+
+        for child in routes.routes.iter() {
+            path.push(child.path_segment.clone());
+            if let PathSegment::Dynamic(ref param) = child.path_segment {
+                let location = self.source_map.param(path, None, &param.name);
+                self.check_type(&param.name, &param.typ, path, location);
+            }
+            self.check_routes(&child.routes, path);
+            path.pop();
+        }
+    }
+
+    fn check_type(
+        &mut self,
+        name: &str,
+        typ: &str,
+        path: &[PathSegment],
+        location: Option<&Location>,
+    ) {
+        let typ = typ.trim();
+        if !is_identifier(typ) || self.known.contains(typ) {
+            return;
+        }
+
+        self.diagnostics.push(
+            Diagnostic::warning(
+                display_path(path),
+                format!("cannot find type `{}` for parameter `{}`", typ, name),
+            )
+            .at(location),
+        );
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => chars.all(crate::parse::is_identifier_char),
+        _ => false,
+    }
+}
+
+/// Find the names of the types the headers bring into scope, along with
+/// the prelude.  Returns `None` if they include a glob import.
+fn known_types(headers: &[Header]) -> Option<HashSet<String>> {
+    let text = headers
+        .iter()
+        .map(|h| format!("{}\n", h.text))
+        .collect::<Vec<_>>()
+        .concat();
+
+    let mut scanner = Scanner {
+        tokens: tokenize(&text),
+        pos: 0,
+        names: PRELUDE.iter().map(|s| s.to_string()).collect(),
+        glob: false,
+    };
+
+    while let Some(token) = scanner.next() {
+        match token.as_str() {
+            "use" => scanner.use_tree(),
+            "struct" | "enum" | "union" | "type" | "trait" => {
+                if let Some(name) = scanner.peek().filter(|t| is_identifier(t)) {
+                    let name = name.to_string();
+                    scanner.names.insert(name);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if scanner.glob {
+        None
+    } else {
+        Some(scanner.names)
+    }
+}
+
+/// Split Rust source into identifiers, `::` and other punctuation,
+/// skipping whitespace, comments and string literals.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            ':' if chars.peek() == Some(&':') => {
+                chars.next();
+                tokens.push("::".to_string());
+            }
+            c if crate::parse::is_identifier_char(c) => {
+                let mut ident = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !crate::parse::is_identifier_char(c) {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(ident);
+            }
+            c => tokens.push(c.to_string()),
+        }
+    }
+
+    tokens
+}
+
+struct Scanner {
+    tokens: Vec<String>,
+    pos: usize,
+    names: HashSet<String>,
+    glob: bool,
+}
+
+impl Scanner {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|t| t.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Read a use tree, such as `a::b::{C, d::E as F}`, recording the
+    /// names it imports.
+    fn use_tree(&mut self) {
+        let mut last = None;
+
+        loop {
+            match self.peek() {
+                Some("::") => {
+                    self.next();
+                }
+                Some("*") => {
+                    self.next();
+                    self.glob = true;
+                    return;
+                }
+                Some("{") => {
+                    self.next();
+                    while self.peek().is_some() && self.peek() != Some("}") {
+                        let start = self.pos;
+                        self.use_tree();
+                        if self.peek() == Some(",") || self.pos == start {
+                            self.next();
+                        }
+                    }
+                    self.next();
+                    return;
+                }
+                Some(token) if is_identifier(token) => {
+                    last = self.next();
+                    if self.peek() == Some("as") {
+                        self.next();
+                        last = self.next();
+                        break;
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        match last {
+            Some(ref name) if !["self", "super", "crate", "_"].contains(&name.as_str()) => {
+                self.names.insert(name.clone());
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use super::source_map::{display_action, display_path};
+use super::{Location, Method, Param, PathSegment, Resource, RouteConfig, Routes, SourceMap};
+
+/// How serious a problem found in a route config is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Code can still be generated, but something looks off.
+    Warning,
+    /// The generated code would panic, fail to compile, or be wrong.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A problem found by [`RouteConfig::validate`](struct.RouteConfig.html#method.validate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The path to the offending node, such as `/people/{id: Uuid}`.
+    pub path: String,
+    pub message: String,
+    /// Where in the route file the problem is, if known.
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    pub fn error<P: AsRef<str>, M: AsRef<str>>(path: P, message: M) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            path: path.as_ref().to_string(),
+            message: message.as_ref().to_string(),
+            location: None,
+        }
+    }
+
+    pub fn warning<P: AsRef<str>, M: AsRef<str>>(path: P, message: M) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            path: path.as_ref().to_string(),
+            message: message.as_ref().to_string(),
+            location: None,
+        }
+    }
+
+    /// Attach a route file location to this diagnostic.
+    pub fn at(mut self, location: Option<&Location>) -> Diagnostic {
+        self.location = location.cloned();
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref location) = self.location {
+            write!(f, "{}: ", location)?;
+        }
+        write!(f, "{}: {} (at {})", self.severity, self.message, self.path)
+    }
+}
+
+impl RouteConfig {
+    /// Check the route config for problems that would otherwise only
+    /// show up as a panic during codegen or a compile error in the
+    /// generated code.
+    ///
+    /// Returns every problem found, in route file order.  The config
+    /// is fine to generate code for if none of them are errors.
+    ///
+    /// ```
+    /// # use wayfinder::{get, RouteConfig, Routes, NestedRoutes, Severity};
+    /// let config = RouteConfig {
+    ///     headers: vec![],
+    ///     routes: Routes {
+    ///         resources: vec![get!(Index), get!(Home)],
+    ///         ..Routes::default()
+    ///     },
+    /// };
+    ///
+    /// let diagnostics = config.validate();
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].severity, Severity::Error);
+    /// assert_eq!(diagnostics[0].path, "/");
+    /// ```
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        self.validate_with_source_map(&SourceMap::default())
+    }
+
+    /// Like [`validate`](#method.validate), but with the route file
+    /// location of each problem filled in from the source map.
+    pub fn validate_with_source_map(&self, source_map: &SourceMap) -> Vec<Diagnostic> {
+        let mut actions = vec![];
+        collect_actions(&self.routes, &mut actions);
+
+        let mut validator = Validator {
+            source_map,
+            actions,
+            seen_actions: HashSet::new(),
+            diagnostics: vec![],
+        };
+        validator.check_routes(&self.routes, &mut vec![], &[], true);
+
+        validator.diagnostics
+    }
+}
+
+/// Whether every segment that parses as `later` also parses as
+/// `earlier`, so that `later` would never be tried.
+fn shadows(earlier: &Param, later: &Param) -> bool {
+    match earlier.typ.as_str() {
+        "String" | "OsString" | "PathBuf" => true,
+        "f32" | "f64" => is_float(&later.typ) || integer_bits(&later.typ).is_some(),
+        typ if typ == later.typ => true,
+        typ => match (integer_bits(typ), integer_bits(&later.typ)) {
+            (Some((true, earlier_bits, _)), Some((true, _, later_bits)))
+            | (Some((false, earlier_bits, _)), Some((false, _, later_bits))) => {
+                earlier_bits >= later_bits
+            }
+            (Some((true, earlier_bits, _)), Some((false, _, later_bits))) => {
+                earlier_bits > later_bits
+            }
+            _ => false,
+        },
+    }
+}
+
+fn is_float(typ: &str) -> bool {
+    typ == "f32" || typ == "f64"
+}
+
+/// Whether a primitive integer type is signed, and the fewest and most
+/// bits it could have.  `usize` and `isize` are taken to be 32 or 64
+/// bits, so they only shadow, or are shadowed by, a type when that
+/// holds for either width.
+fn integer_bits(typ: &str) -> Option<(bool, u32, u32)> {
+    match typ {
+        "u8" => Some((false, 8, 8)),
+        "u16" => Some((false, 16, 16)),
+        "u32" => Some((false, 32, 32)),
+        "u64" => Some((false, 64, 64)),
+        "u128" => Some((false, 128, 128)),
+        "usize" => Some((false, 32, 64)),
+        "i8" => Some((true, 8, 8)),
+        "i16" => Some((true, 16, 16)),
+        "i32" => Some((true, 32, 32)),
+        "i64" => Some((true, 64, 64)),
+        "i128" => Some((true, 128, 128)),
+        "isize" => Some((true, 32, 64)),
+        _ => None,
+    }
+}
+
+fn collect_actions(routes: &Routes, actions: &mut Vec<(Vec<String>, String)>) {
+    for resource in routes.resources.iter() {
+        if !resource.is_redirect {
+            actions.push((resource.modules.clone(), resource.name.clone()));
+        }
+    }
+    for child in routes.routes.iter() {
+        collect_actions(&child.routes, actions);
+    }
+}
+
+struct Validator<'a> {
+    source_map: &'a SourceMap,
+    actions: Vec<(Vec<String>, String)>,
+    seen_actions: HashSet<(Vec<String>, String)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Validator<'a> {
+    fn check_routes(
+        &mut self,
+        routes: &Routes,
+        path: &mut Vec<PathSegment>,
+        inherited_parameters: &[Param],
+        is_root: bool,
+    ) {
+        let here = display_path(path);
+        let location = self.source_map.route(path);
+
+        if !is_root && routes.resources.is_empty() && routes.routes.is_empty() {
+            self.diagnostics.push(
+                Diagnostic::warning(&here, "route has no resources or nested routes").at(location),
+            );
+        }
+
+        let mut parameters = inherited_parameters.to_vec();
+        parameters.extend_from_slice(&routes.query_parameters);
+
+        let mut methods: Vec<Method> = vec![];
+        for resource in routes.resources.iter() {
+            if methods.contains(&resource.method) {
+                let location =
+                    self.source_map
+                        .resource(path, resource.method, &display_action(resource));
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        &here,
+                        format!(
+                            "method {} is declared more than once; `{}` is unreachable",
+                            resource.method,
+                            display_action(resource),
+                        ),
+                    )
+                    .at(location),
+                );
+            }
+            methods.push(resource.method);
+
+            self.check_resource(resource, path, &parameters, &here);
+        }
+
+        let mut statics = HashSet::new();
+        let mut dynamics: Vec<&Param> = vec![];
+        for child in routes.routes.iter() {
+            match child.path_segment {
+                PathSegment::Static(ref s) => {
+                    if !statics.insert(s) {
+                        self.diagnostics.push(
+                            Diagnostic::error(
+                                &here,
+                                format!("path segment `{}` is declared more than once", s),
+                            )
+                            .at(location),
+                        );
+                    }
+                }
+                PathSegment::Dynamic(ref p) => {
+                    if dynamics.iter().any(|d| d.name == p.name) {
+                        self.diagnostics.push(
+                            Diagnostic::error(
+                                &here,
+                                format!("path segment `{{{}}}` is declared more than once", p.name),
+                            )
+                            .at(location),
+                        );
+                    } else if let Some(first) = dynamics.iter().find(|d| shadows(d, p)) {
+                        self.diagnostics.push(
+                            Diagnostic::error(
+                                &here,
+                                format!(
+                                    "path segment `{{{}}}` is unreachable; `{{{}}}` is tried first and parses the same segments",
+                                    p, first,
+                                ),
+                            )
+                            .at(location),
+                        );
+                    }
+                    dynamics.push(p);
+                }
+            }
+
+            path.push(child.path_segment.clone());
+            self.check_routes(&child.routes, path, &parameters, false);
+            path.pop();
+        }
+    }
+
+    fn check_resource(
+        &mut self,
+        resource: &Resource,
+        path: &[PathSegment],
+        parameters: &[Param],
+        here: &str,
+    ) {
+        let action = (resource.modules.clone(), resource.name.clone());
+        let location = self
+            .source_map
+            .resource(path, resource.method, &display_action(resource));
+
+        if resource.is_redirect {
+            if !self.actions.contains(&action) {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        here,
+                        format!(
+                            "redirect to `{}`, which is not an action",
+                            display_action(resource),
+                        ),
+                    )
+                    .at(location),
+                );
+            }
+            return;
+        }
+
+        if !self.seen_actions.insert(action) {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    here,
+                    format!("duplicate action `{}`", display_action(resource)),
+                )
+                .at(location),
+            );
+        }
+
+        let mut names = HashSet::new();
+        let route_parameters = path.iter().filter_map(|segment| match segment {
+            PathSegment::Dynamic(p) => Some(p),
+            _ => None,
+        });
+        for param in route_parameters
+            .chain(parameters.iter())
+            .chain(resource.query_parameters.iter())
+            .chain(resource.body_parameters.iter())
+        {
+            if !names.insert(&param.name) {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        here,
+                        format!(
+                            "parameter `{}` is declared more than once for `{}`",
+                            param.name,
+                            display_action(resource),
+                        ),
+                    )
+                    .at(location),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::io;
+use std::io::Write;
+
+use crate::core::{Param, PathSegment, Routes};
+use crate::gen::gen::to_caps_case;
+use crate::gen::options::CodegenOptions;
+
+/// The variant of the generated `Error` enum for a query or body
+/// parameter that isn't valid UTF-8.
+pub const NOT_UTF8: &str = "NotUtf8";
+
+/// The variants of the generated `Error` enum, one for each distinct
+/// parameter name and type.
+pub struct ErrorVariants {
+    variants: Vec<(Param, String)>,
+}
+
+impl ErrorVariants {
+    pub fn from(routes: &Routes) -> ErrorVariants {
+        let mut params: Vec<Param> = vec![];
+        collect_params(routes, &mut params);
+
+        let variants = params
+            .iter()
+            .map(|param| {
+                let clashes = to_caps_case(&param.name) == NOT_UTF8
+                    || params
+                        .iter()
+                        .any(|other| other.name == param.name && other.typ != param.typ);
+                let name = if clashes {
+                    format!("{}{}", to_caps_case(&param.name), type_suffix(&param.typ))
+                } else {
+                    to_caps_case(&param.name)
+                };
+                (param.clone(), name)
+            })
+            .collect();
+
+        ErrorVariants { variants }
+    }
+
+    /// The name of the variant for errors parsing this parameter.
+    pub fn variant(&self, param: &Param) -> &str {
+        self.variants
+            .iter()
+            .find(|(p, _)| p.name == param.name && p.typ == param.typ)
+            .map(|(_, name)| name.as_str())
+            .expect("all params should have an error variant")
+    }
+
+    pub fn write<W>(&self, w: &mut W, options: &CodegenOptions) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let core = options.core();
+        writeln!(
+            w,
+            "    /// Which part of the request a parameter came from."
+        )?;
+        writeln!(w, "    #[derive(Debug, Clone, Copy, PartialEq, Eq)]")?;
+        writeln!(w, "    pub enum ParamKind {{")?;
+        writeln!(w, "        Path,")?;
+        writeln!(w, "        Query,")?;
+        writeln!(w, "        Body,")?;
+        writeln!(w, "    }}")?;
+        writeln!(w)?;
+        writeln!(w, "    impl fmt::Display for ParamKind {{")?;
+        writeln!(
+            w,
+            "        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{"
+        )?;
+        writeln!(w, "            f.write_str(match self {{")?;
+        writeln!(w, "                ParamKind::Path => \"path\",")?;
+        writeln!(w, "                ParamKind::Query => \"query\",")?;
+        writeln!(w, "                ParamKind::Body => \"body\",")?;
+        writeln!(w, "            }})")?;
+        writeln!(w, "        }}")?;
+        writeln!(w, "    }}")?;
+        writeln!(w)?;
+
+        writeln!(w, "    /// A parameter that failed to parse.")?;
+        writeln!(w, "    ///")?;
+        writeln!(
+            w,
+            "    /// There is a variant for each parameter, holding the error from"
+        )?;
+        writeln!(
+            w,
+            "    /// its type's `FromStr` implementation, the text that failed to"
+        )?;
+        writeln!(
+            w,
+            "    /// parse, and where in the request it came from.  A query or body"
+        )?;
+        writeln!(
+            w,
+            "    /// parameter that isn't valid UTF-8 once it's decoded is `{}`",
+            NOT_UTF8
+        )?;
+        writeln!(
+            w,
+            "    /// instead, while a path parameter that isn't is not a match."
+        )?;
+        writeln!(w, "    #[derive(Debug)]")?;
+        writeln!(w, "    pub enum Error {{")?;
+        for (param, name) in self.variants.iter() {
+            writeln!(
+                w,
+                "        /// The `{}` parameter is not a valid `{}`.",
+                param.name, param.typ
+            )?;
+            writeln!(w, "        {} {{", name)?;
+            writeln!(w, "            kind: ParamKind,")?;
+            writeln!(w, "            text: String,")?;
+            writeln!(
+                w,
+                "            error: <{} as {}::str::FromStr>::Err,",
+                param.typ, core
+            )?;
+            writeln!(w, "        }},")?;
+        }
+        writeln!(
+            w,
+            "        /// The query or body parameter named by `param` is not valid"
+        )?;
+        writeln!(
+            w,
+            "        /// UTF-8.  The text has each invalid sequence replaced with `U+FFFD`."
+        )?;
+        writeln!(w, "        {} {{", NOT_UTF8)?;
+        writeln!(w, "            param: &'static str,")?;
+        writeln!(w, "            kind: ParamKind,")?;
+        writeln!(w, "            text: String,")?;
+        writeln!(w, "        }},")?;
+        writeln!(w, "    }}")?;
+        writeln!(w)?;
+
+        writeln!(w, "    impl Error {{")?;
+        writeln!(
+            w,
+            "        /// The name of the parameter that failed to parse."
+        )?;
+        writeln!(w, "        pub fn param(&self) -> &'static str {{")?;
+        writeln!(w, "            match *self {{")?;
+        for (param, name) in self.variants.iter() {
+            writeln!(
+                w,
+                "                Error::{} {{ .. }} => \"{}\",",
+                name, param.name
+            )?;
+        }
+        writeln!(
+            w,
+            "                Error::{} {{ param, .. }} => param,",
+            NOT_UTF8
+        )?;
+        writeln!(w, "            }}")?;
+        writeln!(w, "        }}")?;
+        writeln!(w)?;
+        writeln!(
+            w,
+            "        /// Whether the parameter was part of the path, the query or the body."
+        )?;
+        writeln!(w, "        pub fn kind(&self) -> ParamKind {{")?;
+        writeln!(w, "            match *self {{")?;
+        for (_, name) in self.variants.iter() {
+            writeln!(w, "                Error::{} {{ kind, .. }} => kind,", name)?;
+        }
+        writeln!(
+            w,
+            "                Error::{} {{ kind, .. }} => kind,",
+            NOT_UTF8
+        )?;
+        writeln!(w, "            }}")?;
+        writeln!(w, "        }}")?;
+        writeln!(w)?;
+        writeln!(w, "        /// The text that failed to parse.")?;
+        writeln!(w, "        pub fn text(&self) -> &str {{")?;
+        writeln!(w, "            match *self {{")?;
+        for (_, name) in self.variants.iter() {
+            writeln!(
+                w,
+                "                Error::{} {{ ref text, .. }} => text,",
+                name
+            )?;
+        }
+        writeln!(
+            w,
+            "                Error::{} {{ ref text, .. }} => text,",
+            NOT_UTF8
+        )?;
+        writeln!(w, "            }}")?;
+        writeln!(w, "        }}")?;
+        writeln!(w, "    }}")?;
+        writeln!(w)?;
+
+        writeln!(w, "    impl fmt::Display for Error {{")?;
+        writeln!(
+            w,
+            "        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{"
+        )?;
+        writeln!(w, "            write!(")?;
+        writeln!(w, "                f,")?;
+        writeln!(
+            w,
+            "                \"Error parsing {{}} parameter '{{}}' from {{:?}}: \","
+        )?;
+        writeln!(w, "                self.kind(),")?;
+        writeln!(w, "                self.param(),")?;
+        writeln!(w, "                self.text()")?;
+        writeln!(w, "            )?;")?;
+        writeln!(w, "            match *self {{")?;
+        for (_, name) in self.variants.iter() {
+            writeln!(
+                w,
+                "                Error::{} {{ ref error, .. }} => write!(f, \"{{:?}}\", error),",
+                name
+            )?;
+        }
+        writeln!(
+            w,
+            "                Error::{} {{ .. }} => f.write_str(\"not valid UTF-8\"),",
+            NOT_UTF8
+        )?;
+        writeln!(w, "            }}")?;
+        writeln!(w, "        }}")?;
+        writeln!(w, "    }}")?;
+        writeln!(w)?;
+        writeln!(w, "    impl {}::error::Error for Error {{}}", core)?;
+        writeln!(w)?;
+
+        Ok(())
+    }
+}
+
+fn collect_params(routes: &Routes, params: &mut Vec<Param>) {
+    let mut add = |param: &Param| {
+        if !params
+            .iter()
+            .any(|other| other.name == param.name && other.typ == param.typ)
+        {
+            params.push(param.clone());
+        }
+    };
+
+    for param in routes.query_parameters.iter() {
+        add(param);
+    }
+    for resource in routes.resources.iter() {
+        for param in resource
+            .query_parameters
+            .iter()
+            .chain(resource.body_parameters.iter())
+        {
+            add(param);
+        }
+    }
+    for child in routes.routes.iter() {
+        if let PathSegment::Dynamic(ref param) = child.path_segment {
+            add(param);
+        }
+    }
+
+    for child in routes.routes.iter() {
+        collect_params(&child.routes, params);
+    }
+}
+
+/// Make a type into something that can be appended to a variant name,
+/// so that `id: Uuid` and `id: usize` become `IdUuid` and `IdUsize`.
+fn type_suffix(typ: &str) -> String {
+    let last = typ.rsplit("::").next().unwrap_or(typ);
+    let ident = last
+        .chars()
+        .map(|c| {
+            if crate::parse::is_identifier_char(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    to_caps_case(&ident)
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use itertools::Either;
 
 use crate::core::*;
@@ -6,32 +8,41 @@ use crate::gen::trie::*;
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
 pub enum Charlike {
     Static(char),
-    Dynamic(String), // the param name
+    /// A parameter, after its position among its siblings in the route
+    /// file, so that sibling parameters are tried in the order declared.
+    Dynamic(usize, Param),
     Separator,
 }
 
 #[derive(Debug, Clone)]
 pub struct FlattenedPath {
     segments: Vec<PathSegment>,
+    /// The position of each segment among its siblings.
+    positions: Vec<usize>,
 }
 
 impl FlattenedPath {
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = Charlike> + 'a {
         self.segments
             .iter()
-            .map(|segment| match segment {
+            .zip(self.positions.iter())
+            .map(|(segment, &position)| match segment {
                 PathSegment::Static(s) => Either::Left(
                     s.chars()
                         .map(Charlike::Static)
                         .chain(std::iter::once(Charlike::Separator)),
                 ),
                 PathSegment::Dynamic(d) => Either::Right(
-                    vec![Charlike::Dynamic(d.name.clone()), Charlike::Separator].into_iter(),
+                    vec![Charlike::Dynamic(position, d.clone()), Charlike::Separator].into_iter(),
                 ),
             })
             .flatten()
     }
 
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
     pub fn dynamics<'a>(&'a self) -> impl Iterator<Item = &'a Param> + 'a {
         self.segments.iter().filter_map(|segment| match segment {
             PathSegment::Dynamic(s) => Some(s),
@@ -40,29 +51,49 @@ impl FlattenedPath {
     }
 }
 
+impl fmt::Display for FlattenedPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.segments.is_empty() {
+            return f.write_str("/");
+        }
+
+        for segment in self.segments.iter() {
+            match segment {
+                PathSegment::Static(s) => write!(f, "/{}", s)?,
+                PathSegment::Dynamic(p) => write!(f, "/{{{}}}", p.name)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FlattenedRoute {
     pub path: FlattenedPath,
     pub resources: Vec<Resource>,
     pub query_parameters: Vec<Param>,
+    pub trailing_slash: TrailingSlash,
 }
 
 pub struct FlattenedRoutes {
     routes: Vec<FlattenedRoute>,
 }
 
-impl<'a> From<&'a Routes> for FlattenedRoutes {
-    fn from(routes: &Routes) -> FlattenedRoutes {
-        let routes = FlattenedRoutes::flatten(routes, vec![], vec![]);
+impl FlattenedRoutes {
+    /// Flatten the routes, with the trailing slash policy for any that
+    /// don't set their own.
+    pub fn new(routes: &Routes, trailing_slash: TrailingSlash) -> FlattenedRoutes {
+        let routes = FlattenedRoutes::flatten(routes, vec![], vec![], vec![], trailing_slash);
         FlattenedRoutes { routes }
     }
-}
 
-impl FlattenedRoutes {
     fn flatten(
         routes: &Routes,
         path: Vec<PathSegment>,
+        positions: Vec<usize>,
         mut query_parameters: Vec<Param>,
+        trailing_slash: TrailingSlash,
     ) -> Vec<FlattenedRoute> {
         let mut flattened = vec![];
 
@@ -73,18 +104,24 @@ impl FlattenedRoutes {
         flattened.push(FlattenedRoute {
             path: FlattenedPath {
                 segments: path.clone(),
+                positions: positions.clone(),
             },
             resources: routes.resources.clone(),
             query_parameters: query_parameters.clone(),
+            trailing_slash,
         });
 
-        for child in routes.routes.iter() {
+        for (position, child) in routes.routes.iter().enumerate() {
             let mut new_path = path.clone();
             new_path.push(child.path_segment.clone());
+            let mut new_positions = positions.clone();
+            new_positions.push(position);
             flattened.extend_from_slice(&FlattenedRoutes::flatten(
                 &child.routes,
                 new_path,
+                new_positions,
                 query_parameters.clone(),
+                child.trailing_slash.unwrap_or(trailing_slash),
             ));
         }
 
@@ -97,17 +134,26 @@ impl FlattenedRoutes {
         }
     */
 
-    pub fn to_trie(&self) -> Trie<Charlike, FlattenedRoute> {
+    /// Build the trie of paths to match.  If `ignore_case` is set,
+    /// static segments are lowercased.  Fails if two paths are the same.
+    pub fn to_trie(&self, ignore_case: bool) -> Result<Trie<Charlike, FlattenedRoute>, String> {
         let mut t = Trie::new();
 
         for route in self.routes.iter() {
-            t = t
-                .add(route.path.iter(), route.clone())
-                .map_err(|_| "all paths should be unique!")
-                .unwrap();
+            let path = route.path.iter().map(|c| match c {
+                Charlike::Static(ch) if ignore_case => Charlike::Static(ch.to_ascii_lowercase()),
+                c => c,
+            });
+            t = t.add(path, route.clone()).map_err(|_| {
+                if ignore_case {
+                    format!("Route {} is the same as another ignoring case.", route.path)
+                } else {
+                    format!("Route {} is declared more than once.", route.path)
+                }
+            })?;
         }
 
-        t
+        Ok(t)
     }
 }
 
@@ -116,10 +162,12 @@ pub struct FlattenedModules {
     pub root: FlattenedModule,
 }
 
-impl<'a> From<&'a Routes> for FlattenedModules {
-    fn from(routes: &Routes) -> FlattenedModules {
-        let root = FlattenedModules::flatten(routes, vec![], vec![]);
-        FlattenedModules { root }
+impl FlattenedModules {
+    /// Gather the actions into their modules.  Fails if an action is
+    /// declared more than once.
+    pub fn new(routes: &Routes) -> Result<FlattenedModules, String> {
+        let root = FlattenedModules::flatten(routes, vec![], vec![], vec![])?;
+        Ok(FlattenedModules { root })
     }
 }
 
@@ -168,16 +216,17 @@ impl FlattenedModules {
     fn flatten(
         routes: &Routes,
         path: Vec<PathSegment>,
+        positions: Vec<usize>,
         query_parameters: Vec<Param>,
-    ) -> FlattenedModule {
+    ) -> Result<FlattenedModule, String> {
         let mut accum = helper::Module::default();
 
-        let mut routes_to_process = vec![(routes, path, query_parameters)];
+        let mut routes_to_process = vec![(routes, path, positions, query_parameters)];
 
         loop {
-            let (routes, path, mut query_parameters) = match routes_to_process.pop() {
+            let (routes, path, positions, mut query_parameters) = match routes_to_process.pop() {
                 None => break,
-                Some((r, p, qp)) => (r, p, qp),
+                Some((r, p, ps, qp)) => (r, p, ps, qp),
             };
 
             for param in routes.query_parameters.iter() {
@@ -186,6 +235,7 @@ impl FlattenedModules {
 
             let flat_path = FlattenedPath {
                 segments: path.clone(),
+                positions: positions.clone(),
             };
             for resource in routes.resources.iter() {
                 if resource.is_redirect {
@@ -202,34 +252,39 @@ impl FlattenedModules {
                         .or_insert(helper::Module::default());
                 }
 
-                match entry.insert(
-                    &resource.name,
-                    FlattenedAction {
-                        name: resource.name.clone(),
-                        method: resource.method.clone(),
-                        path: flat_path.clone(),
-                        route_parameters: flat_path.dynamics().cloned().collect(),
-                        query_parameters,
-                    },
-                ) {
-                    Some(_) => panic!(
-                        "Duplicate controller action `{}::{}`!",
-                        resource.modules.iter().next().unwrap(),
-                        resource.name,
-                    ),
-                    None => {}
+                let action = FlattenedAction {
+                    name: resource.name.clone(),
+                    modules: resource.modules.clone(),
+                    method: resource.method.clone(),
+                    path: flat_path.clone(),
+                    route_parameters: flat_path.dynamics().cloned().collect(),
+                    query_parameters,
+                    body_parameters: resource.body_parameters.clone(),
+                };
+                if entry.insert(&resource.name, action).is_some() {
+                    return Err(format!(
+                        "Action `{}` is declared more than once.",
+                        display_action(resource),
+                    ));
                 }
             }
 
-            for child in routes.routes.iter() {
+            for (position, child) in routes.routes.iter().enumerate() {
                 let mut new_path = path.clone();
                 new_path.push(child.path_segment.clone());
-
-                routes_to_process.push((&child.routes, new_path, query_parameters.clone()));
+                let mut new_positions = positions.clone();
+                new_positions.push(position);
+
+                routes_to_process.push((
+                    &child.routes,
+                    new_path,
+                    new_positions,
+                    query_parameters.clone(),
+                ));
             }
         }
 
-        accum.finalize("routes".into())
+        Ok(accum.finalize("routes".into()))
     }
 }
 
@@ -240,11 +295,108 @@ pub struct FlattenedModule {
     pub modules: Vec<FlattenedModule>,
 }
 
+impl FlattenedModule {
+    /// Every action in this module and its submodules, along with the
+    /// names of the modules leading to it.
+    pub fn all_actions(&self) -> Vec<(Vec<&str>, &FlattenedAction)> {
+        let mut actions = vec![];
+        self.collect_actions(&mut vec![], &mut actions);
+        actions
+    }
+
+    fn collect_actions<'a>(
+        &'a self,
+        path: &mut Vec<&'a str>,
+        actions: &mut Vec<(Vec<&'a str>, &'a FlattenedAction)>,
+    ) {
+        for action in self.actions.iter() {
+            actions.push((path.clone(), action));
+        }
+
+        for child in self.modules.iter() {
+            path.push(&child.name);
+            child.collect_actions(path, actions);
+            path.pop();
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct FlattenedAction {
     pub name: String,
+    pub modules: Vec<String>,
     pub method: Method,
     pub path: FlattenedPath,
     pub route_parameters: Vec<Param>,
     pub query_parameters: Vec<Param>,
+    pub body_parameters: Vec<Param>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_trie_duplicate_path() {
+        let routes = Routes {
+            routes: vec![
+                NestedRoutes::new("people", Routes::default()),
+                NestedRoutes::new("people", Routes::default()),
+            ],
+            ..Routes::default()
+        };
+        let flattened = FlattenedRoutes::new(&routes, TrailingSlash::Lenient);
+
+        match flattened.to_trie(false) {
+            Err(message) => assert_eq!(message, "Route /people is declared more than once."),
+            Ok(_) => panic!("duplicate paths should fail"),
+        }
+    }
+
+    #[test]
+    fn test_to_trie_ignore_case() {
+        let routes = Routes {
+            routes: vec![
+                NestedRoutes::new("People", Routes::default()),
+                NestedRoutes::new("people", Routes::default()),
+            ],
+            ..Routes::default()
+        };
+        let flattened = FlattenedRoutes::new(&routes, TrailingSlash::Lenient);
+
+        assert!(flattened.to_trie(false).is_ok());
+        match flattened.to_trie(true) {
+            Err(message) => assert!(message.contains("ignoring case"), "message: {}", message),
+            Ok(_) => panic!("paths differing by case should fail"),
+        }
+    }
+
+    #[test]
+    fn test_modules_duplicate_action() {
+        let index = Resource::make(
+            Method::Get,
+            vec!["People".into(), "Index".into()],
+            false,
+            vec![],
+        );
+        let routes = Routes {
+            resources: vec![index.clone()],
+            routes: vec![NestedRoutes::new(
+                "people",
+                Routes {
+                    resources: vec![index],
+                    ..Routes::default()
+                },
+            )],
+            ..Routes::default()
+        };
+
+        match FlattenedModules::new(&routes) {
+            Err(message) => assert_eq!(
+                message,
+                "Action `People::Index` is declared more than once."
+            ),
+            Ok(_) => panic!("duplicate actions should fail"),
+        }
+    }
 }
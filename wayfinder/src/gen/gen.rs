@@ -1,11 +1,22 @@
 use std::io;
 use std::io::Write;
 
-use crate::core::{Header, RouteConfig};
+use crate::core::{
+    display_action_name, Header, Location, Method, Param, RouteConfig, SourceMap, TrailingSlash,
+};
+use crate::gen::error::ErrorVariants;
 use crate::gen::flat::{
-    Charlike, FlattenedModule, FlattenedModules, FlattenedRoute, FlattenedRoutes,
+    Charlike, FlattenedAction, FlattenedModule, FlattenedModules, FlattenedRoute,
+    FlattenedRoutes,
 };
+use crate::gen::handlers::{check_handler_names, write_async_handlers, write_handlers};
+use crate::gen::http::write_http;
+use crate::gen::normalize::write_normalize_path;
+use crate::gen::options::CodegenOptions;
+use crate::gen::request_line::write_request_line;
+use crate::gen::tower::write_tower;
 use crate::gen::trie::Trie;
+use crate::gen::url::{write_url_for, write_url_helpers, write_urls};
 
 pub fn to_caps_case(s: &str) -> String {
     let mut chars = s.chars();
@@ -60,12 +71,16 @@ pub fn codegen_module<W>(
     w: &mut W,
     module: &FlattenedModule,
     headers: &[Header],
+    source_map: &SourceMap,
+    options: &CodegenOptions,
     indent: &str,
 ) -> io::Result<()>
 where
     W: Write,
 {
     let is_root = indent == "    ";
+    // the path from this module back to the root, where `ToPath` lives
+    let root = "super::".repeat(indent.len() / 4 - 1);
 
     // TODO: can we do something smarter than repeating these everywhere?
     for header in headers.iter() {
@@ -76,49 +91,83 @@ where
         writeln!(w)?;
     }
 
-    for action in module.actions.iter() {
-        write!(w, "{}/// Renders for `{} /", indent, action.method)?;
+    // without std, the parts of its prelude the generated code uses
+    if options.no_std {
+        writeln!(w, "{}use alloc::borrow::ToOwned;", indent)?;
+        writeln!(w, "{}use alloc::format;", indent)?;
+        writeln!(w, "{}use alloc::string::{{String, ToString}};", indent)?;
+        writeln!(w, "{}use alloc::vec::Vec;", indent)?;
+        writeln!(w)?;
+    }
 
-        let mut path = action.path.iter().peekable();
-        loop {
-            let ch = match path.next() {
-                None => break,
-                Some(c) => c.clone(),
-            };
-            match ch {
-                Charlike::Static(s) => {
-                    write!(w, "{}", s)?;
-                }
-                Charlike::Dynamic(ref p) => {
-                    write!(w, "{{{}}}", p)?;
-                }
-                Charlike::Separator => match path.peek() {
-                    None => {}
-                    Some(_) => {
-                        write!(w, "/")?;
-                    }
-                },
-            }
-        }
+    for action in module.actions.iter() {
+        writeln!(
+            w,
+            "{}/// Renders for `{} {}`.",
+            indent, action.method, action.path
+        )?;
 
-        writeln!(w, "`.")?;
+        let path = action.path.segments();
+        let name = display_action_name(&action.modules, &action.name);
+        let location = source_map.resource(path, action.method, &name);
+        write_location(w, location, indent)?;
 
+        let borrows = action_borrows(action, options);
         writeln!(w, "{}#[derive(Debug, PartialEq, Eq)]", indent)?;
-        write!(w, "{}pub struct {}", indent, to_caps_case(&action.name))?;
+        write!(
+            w,
+            "{}pub struct {}{}",
+            indent,
+            to_caps_case(&action.name),
+            lifetime(borrows, "'a")
+        )?;
 
-        if action.route_parameters.is_empty() && action.query_parameters.is_empty() {
+        if action.route_parameters.is_empty()
+            && action.query_parameters.is_empty()
+            && action.body_parameters.is_empty()
+        {
             writeln!(w, ";")?;
         } else {
             writeln!(w, " {{")?;
 
+            let field_indent = format!("{}    ", indent);
             for param in action.route_parameters.iter() {
-                writeln!(w, "{}    pub {}: {},", indent, param.name, param.typ)?;
+                let location = source_map.param(path, None, &param.name);
+                write_location(w, location, &field_indent)?;
+                writeln!(
+                    w,
+                    "{}    pub {}: {},",
+                    indent,
+                    param.name,
+                    param_type(param, options)
+                )?;
             }
             for param in action.query_parameters.iter() {
+                let location = source_map.param(path, Some(action.method), &param.name);
+                write_location(w, location, &field_indent)?;
+                writeln!(
+                    w,
+                    "{}    pub {}: Option<{}>,",
+                    indent,
+                    param.name,
+                    param_type(param, options)
+                )?;
+            }
+            for param in action.body_parameters.iter() {
+                writeln!(
+                    w,
+                    "{}    /// Read from a form body by the async `dispatch`, and `None`",
+                    indent
+                )?;
+                writeln!(w, "{}    /// when the route is matched otherwise.", indent)?;
+                let location = source_map.param(path, Some(action.method), &param.name);
+                write_location(w, location, &field_indent)?;
                 writeln!(
                     w,
                     "{}    pub {}: Option<{}>,",
-                    indent, param.name, param.typ
+                    indent,
+                    param.name,
+                    param_type(param, options)
                 )?;
             }
 
@@ -127,13 +176,21 @@ where
 
         writeln!(w)?;
 
-        writeln!(w, "{}impl {} {{", indent, to_caps_case(&action.name))?;
         writeln!(
             w,
-            "{}    /// Make a path to this route with the given parameters.",
-            indent
+            "{}impl {}ToPath for {}{} {{",
+            indent,
+            root,
+            to_caps_case(&action.name),
+            lifetime(borrows, "'_")
+        )?;
+        writeln!(
+            w,
+            "{}    fn write_path<W: {}::fmt::Write>(&self, w: &mut W) -> {}::fmt::Result {{",
+            indent,
+            options.core(),
+            options.core()
         )?;
-        writeln!(w, "{}    pub fn to_path(&self) -> String {{", indent)?;
 
         if !action.route_parameters.is_empty() || !action.query_parameters.is_empty() {
             write!(
@@ -144,47 +201,71 @@ where
             )?;
 
             for param in action.route_parameters.iter() {
-                write!(w, "ref {}, ", param.name)?;
+                write!(w, "{}: ref {}, ", param.name, local(param))?;
             }
 
             for param in action.query_parameters.iter() {
-                write!(w, "ref {}, ", param.name)?;
+                write!(w, "{}: ref {}, ", param.name, local(param))?;
+            }
+
+            // body parameters aren't part of the path
+            if !action.body_parameters.is_empty() {
+                write!(w, ".. ")?;
             }
 
             writeln!(w, "}} = self;")?;
         }
 
-        write!(w, "{}        format!(\"/", indent)?;
-
+        // static text is written as is, dynamic segments are encoded
+        let mut text = String::from("/");
         let mut path = action.path.iter().peekable();
-        loop {
-            let ch = match path.next() {
-                None => break,
-                Some(c) => c.clone(),
-            };
+        while let Some(ch) = path.next() {
             match ch {
-                Charlike::Static(s) => {
-                    write!(w, "{}", s)?;
-                }
-                Charlike::Dynamic(_) => {
-                    write!(w, "{{}}")?;
+                Charlike::Static(c) => text.push(c),
+                Charlike::Dynamic(_, ref p) => {
+                    if !text.is_empty() {
+                        writeln!(w, "{}        w.write_str(\"{}\")?;", indent, text)?;
+                        text.clear();
+                    }
+                    if p.raw {
+                        writeln!(w, "{}        write!(w, \"{{}}\", {})?;", indent, local(p))?;
+                    } else {
+                        writeln!(
+                            w,
+                            "{}        {}write_encoded(w, {})?;",
+                            indent,
+                            root,
+                            local(p)
+                        )?;
+                    }
                 }
-                Charlike::Separator => match path.peek() {
-                    None => {}
-                    Some(_) => {
-                        write!(w, "/")?;
+                Charlike::Separator => {
+                    if path.peek().is_some() {
+                        text.push('/');
                     }
-                },
+                }
             }
         }
+        if !text.is_empty() {
+            writeln!(w, "{}        w.write_str(\"{}\")?;", indent, text)?;
+        }
 
-        write!(w, "\"")?;
-
-        for param in action.route_parameters.iter() {
-            write!(w, ", {}", param.name)?;
+        if !action.query_parameters.is_empty() {
+            writeln!(w, "{}        let mut first = true;", indent)?;
+            for param in action.query_parameters.iter() {
+                writeln!(
+                    w,
+                    "{}        {}write_query(w, &mut first, \"{}\", {}, {})?;",
+                    indent,
+                    root,
+                    param.name,
+                    local(param),
+                    param.raw
+                )?;
+            }
         }
 
-        writeln!(w, ")")?;
+        writeln!(w, "{}        Ok(())", indent)?;
         writeln!(w, "{}    }}", indent)?;
         writeln!(w, "{}}}", indent)?;
 
@@ -194,7 +275,14 @@ where
     for module in module.modules.iter() {
         writeln!(w, "{}pub mod {} {{", indent, to_snake_case(&module.name))?;
 
-        codegen_module(w, &module, &headers, &format!("{}    ", indent))?;
+        codegen_module(
+            w,
+            module,
+            headers,
+            source_map,
+            options,
+            &format!("{}    ", indent),
+        )?;
 
         writeln!(w, "{}}}", indent)?;
         writeln!(w)?;
@@ -214,26 +302,29 @@ where
             to_snake_case(&module.name)
         )?;
     }
+    let borrows = module_borrows(module, options);
     writeln!(w, "{}#[derive(Debug, PartialEq, Eq)]", indent)?;
-    writeln!(w, "{}pub enum Route {{", indent)?;
+    writeln!(w, "{}pub enum Route{} {{", indent, lifetime(borrows, "'a"))?;
 
     for action in module.actions.iter() {
         writeln!(
             w,
-            "{}    {}({}),",
+            "{}    {}({}{}),",
             indent,
             to_caps_case(&action.name),
-            to_caps_case(&action.name)
+            to_caps_case(&action.name),
+            lifetime(action_borrows(action, options), "'a")
         )?;
     }
 
     for module in module.modules.iter() {
         writeln!(
             w,
-            "{}    {}({}::Route),",
+            "{}    {}({}::Route{}),",
             indent,
             to_caps_case(&module.name),
-            to_snake_case(&module.name)
+            to_snake_case(&module.name),
+            lifetime(module_borrows(module, options), "'a")
         )?;
     }
 
@@ -241,30 +332,39 @@ where
 
     writeln!(w)?;
 
-    writeln!(w, "{}impl Route {{", indent)?;
     writeln!(
         w,
-        "{}    /// Make a path to this route with the given parameters.",
-        indent
+        "{}impl {}ToPath for Route{} {{",
+        indent,
+        root,
+        lifetime(borrows, "'_")
+    )?;
+    writeln!(
+        w,
+        "{}    fn write_path<W: {}::fmt::Write>(&self, w: &mut W) -> {}::fmt::Result {{",
+        indent,
+        options.core(),
+        options.core()
     )?;
-    writeln!(w, "{}    pub fn to_path(&self) -> String {{", indent)?;
     writeln!(w, "{}        match self {{", indent)?;
 
     for action in module.actions.iter() {
         writeln!(
             w,
-            "{}            Route::{}(ref route) => route.to_path(),",
+            "{}            Route::{}(ref route) => {}ToPath::write_path(route, w),",
             indent,
-            to_caps_case(&action.name)
+            to_caps_case(&action.name),
+            root
         )?;
     }
 
     for module in module.modules.iter() {
         writeln!(
             w,
-            "{}            Route::{}(ref route) => route.to_path(),",
+            "{}            Route::{}(ref route) => {}ToPath::write_path(route, w),",
             indent,
-            to_caps_case(&module.name)
+            to_caps_case(&module.name),
+            root
         )?;
     }
 
@@ -272,19 +372,214 @@ where
     writeln!(w, "{}    }}", indent)?;
     writeln!(w, "{}}}", indent)?;
 
+    for action in module.actions.iter() {
+        let name = to_caps_case(&action.name);
+        let typ = format!("{}{}", name, lifetime(action_borrows(action, options), "'a"));
+        write_from(w, &typ, &format!("Route::{}(route)", name), borrows, indent)?;
+    }
+
+    for child in module.modules.iter() {
+        let path = to_snake_case(&child.name);
+        let variant = to_caps_case(&child.name);
+
+        let route = format!(
+            "{}::Route{}",
+            path,
+            lifetime(module_borrows(child, options), "'a")
+        );
+        let expr = format!("Route::{}(route)", variant);
+        write_from(w, &route, &expr, borrows, indent)?;
+
+        for typ in nested_types(child, options) {
+            let typ = format!("{}::{}", path, typ);
+            let expr = format!("Route::{}(route.into())", variant);
+            write_from(w, &typ, &expr, borrows, indent)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The type of a parameter's field, which is `Cow<'a, str>` if it
+/// borrows from the input.
+fn param_type(param: &Param, options: &CodegenOptions) -> String {
+    if options.borrows(param) {
+        format!("{}::borrow::Cow<'a, str>", options.alloc())
+    } else {
+        param.typ.clone()
+    }
+}
+
+/// Whether an action's struct borrows from the input.
+pub fn action_borrows(action: &FlattenedAction, options: &CodegenOptions) -> bool {
+    action
+        .route_parameters
+        .iter()
+        .chain(action.query_parameters.iter())
+        .chain(action.body_parameters.iter())
+        .any(|param| options.borrows(param))
+}
+
+/// Whether a module's `Route` borrows from the input, which it does if
+/// any action within it does.
+pub fn module_borrows(module: &FlattenedModule, options: &CodegenOptions) -> bool {
+    module
+        .actions
+        .iter()
+        .any(|action| action_borrows(action, options))
+        || module
+            .modules
+            .iter()
+            .any(|child| module_borrows(child, options))
+}
+
+/// The lifetime argument for a type that borrows, such as `<'a>`, or
+/// nothing if it doesn't.
+pub fn lifetime(borrows: bool, lifetime: &str) -> String {
+    if borrows {
+        format!("<{}>", lifetime)
+    } else {
+        String::new()
+    }
+}
+
+/// All the types that can be converted into this module's `Route`,
+/// relative to the module.
+fn nested_types(module: &FlattenedModule, options: &CodegenOptions) -> Vec<String> {
+    let mut types = module
+        .actions
+        .iter()
+        .map(|action| {
+            let borrows = action_borrows(action, options);
+            format!("{}{}", to_caps_case(&action.name), lifetime(borrows, "'a"))
+        })
+        .collect::<Vec<_>>();
+
+    for child in module.modules.iter() {
+        let path = to_snake_case(&child.name);
+        let borrows = module_borrows(child, options);
+        types.push(format!("{}::Route{}", path, lifetime(borrows, "'a")));
+        for typ in nested_types(child, options) {
+            types.push(format!("{}::{}", path, typ));
+        }
+    }
+
+    types
+}
+
+/// Write the conversion from a type into `Route`, which is generic over
+/// the lifetime if the `Route` borrows.
+fn write_from<W>(w: &mut W, typ: &str, expr: &str, borrows: bool, indent: &str) -> io::Result<()>
+where
+    W: Write,
+{
+    let lifetime = lifetime(borrows, "'a");
+    writeln!(w)?;
+    writeln!(
+        w,
+        "{}impl{} From<{}> for Route{} {{",
+        indent, lifetime, typ, lifetime
+    )?;
+    writeln!(
+        w,
+        "{}    fn from(route: {}) -> Route{} {{",
+        indent, typ, lifetime
+    )?;
+    writeln!(w, "{}        {}", indent, expr)?;
+    writeln!(w, "{}    }}", indent)?;
+    writeln!(w, "{}}}", indent)?;
+
+    Ok(())
+}
+
+/// Note where in the route file the following item was declared, so
+/// that compile errors in the generated code can be traced back.
+fn write_location<W>(w: &mut W, location: Option<&Location>, indent: &str) -> io::Result<()>
+where
+    W: Write,
+{
+    if let Some(location) = location {
+        writeln!(w, "{}///", indent)?;
+        writeln!(w, "{}/// Declared at `{}`.", indent, location)?;
+    }
+
     Ok(())
 }
 
 /// Generate a server-side route matching module for the given
 /// [`RouteConfig`](../struct.RouteConfig.html).
+///
+/// Fails with `InvalidInput` if the config has any validation errors.
+/// See [`RouteConfig::validate`](../struct.RouteConfig.html#method.validate).
 pub fn codegen<W>(w: &mut W, route_config: &RouteConfig) -> io::Result<()>
 where
     W: Write,
 {
-    let flattened = FlattenedRoutes::from(&route_config.routes);
-    let modules = FlattenedModules::from(&route_config.routes);
+    codegen_with_options(w, route_config, &CodegenOptions::default())
+}
 
-    writeln!(w, "pub mod routes {{")?;
+/// Generate a route matching module for the given
+/// [`RouteConfig`](../struct.RouteConfig.html), customized by the
+/// [`CodegenOptions`](struct.CodegenOptions.html).
+///
+/// Fails with `InvalidInput` if the config has any validation errors
+/// or the options are invalid.
+pub fn codegen_with_options<W>(
+    w: &mut W,
+    route_config: &RouteConfig,
+    options: &CodegenOptions,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    codegen_with_source_map(w, route_config, options, &SourceMap::default())
+}
+
+/// Like [`codegen_with_options`](fn.codegen_with_options.html), but
+/// each request struct and its fields are documented with where they
+/// were declared in the route file, according to the
+/// [`SourceMap`](../struct.SourceMap.html).
+///
+/// When rustc reports an error in the generated code, such as a
+/// parameter type that isn't in scope, the route file location is
+/// right above the offending line.
+pub fn codegen_with_source_map<W>(
+    w: &mut W,
+    route_config: &RouteConfig,
+    options: &CodegenOptions,
+    source_map: &SourceMap,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut diagnostics = route_config.validate_with_source_map(source_map);
+    diagnostics.extend(check_handler_names(
+        &route_config.routes,
+        options,
+        source_map,
+    ));
+    if let Some(error) = diagnostics.into_iter().find(|d| d.is_error()) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, error.to_string()));
+    }
+    options
+        .validate()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let flattened = FlattenedRoutes::new(&route_config.routes, options.trailing_slash);
+    let trie = flattened
+        .to_trie(options.ignore_case)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let modules = FlattenedModules::new(&route_config.routes)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let errors = ErrorVariants::from(&route_config.routes);
+    let borrows = module_borrows(&modules.root, options);
+    let route_lifetime = lifetime(borrows, "'_");
+
+    if options.visibility.is_empty() {
+        writeln!(w, "mod {} {{", options.module_name)?;
+    } else {
+        writeln!(w, "{} mod {} {{", options.visibility, options.module_name)?;
+    }
     writeln!(w)?;
     writeln!(w, "    //! Application route configuration.")?;
     writeln!(w, "    //!")?;
@@ -294,6 +589,45 @@ where
     )?;
     writeln!(w, "    //! specific to each named resource.")?;
     writeln!(w, "    //!")?;
+    writeln!(
+        w,
+        "    //! Parameters are percent-decoded before they're parsed, and `+` in"
+    )?;
+    writeln!(
+        w,
+        "    //! a query string is a space.  Parameters declared `raw` are parsed"
+    )?;
+    writeln!(
+        w,
+        "    //! from the text exactly as it appears in the request instead."
+    )?;
+    writeln!(
+        w,
+        "    //! Static path segments are matched literally, without decoding, so"
+    )?;
+    writeln!(
+        w,
+        "    //! `/peop%6Ce` doesn't match `/people`, and `%2F` is never a"
+    )?;
+    writeln!(
+        w,
+        "    //! separator.  Links are encoded the same way: parameters are"
+    )?;
+    writeln!(w, "    //! percent-encoded unless they're `raw`.")?;
+    writeln!(w, "    //!")?;
+    writeln!(
+        w,
+        "    //! A path parameter that isn't valid UTF-8 once it's decoded doesn't"
+    )?;
+    writeln!(
+        w,
+        "    //! match, since a sibling route might.  A query parameter that isn't"
+    )?;
+    writeln!(
+        w,
+        "    //! is reported as `Error::NotUtf8`, as the path has already matched."
+    )?;
+    writeln!(w, "    //!")?;
     writeln!(w, "    //! Route configuration:")?;
     writeln!(w, "    //!")?;
     writeln!(w, "    //! ```ignore")?;
@@ -313,87 +647,90 @@ where
     writeln!(w, "    #![allow(unused_variables)]")?;
     writeln!(w)?;
 
-    codegen_module(w, &modules.root, &route_config.headers, "    ")?;
+    codegen_module(
+        w,
+        &modules.root,
+        &route_config.headers,
+        source_map,
+        options,
+        "    ",
+    )?;
     writeln!(w)?;
 
-    writeln!(w, "    #[derive(PartialEq, Eq)]")?;
-    writeln!(w, "    pub enum Match<T> {{")?;
-    writeln!(w, "        NotFound,")?;
-    writeln!(w, "        NotAllowed,")?;
-    writeln!(w, "        Route(T),")?;
-    writeln!(w, "        Redirect(T),")?;
-    writeln!(w, "    }}")?;
-    writeln!(w)?;
-    writeln!(w, "    use std::fmt;")?;
-    writeln!(w, "    impl<T: fmt::Debug> fmt::Debug for Match<T> {{")?;
     writeln!(
         w,
-        "        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{"
+        "    /// Routes and request structs that can be rendered as a path."
     )?;
-    writeln!(w, "            match self {{")?;
+    writeln!(w, "    pub trait ToPath {{")?;
     writeln!(
         w,
-        "                Match::NotFound => write!(f, \"Match::NotFound\"),"
+        "        /// Write the path to this route, with its parameters filled in."
     )?;
     writeln!(
         w,
-        "                Match::NotAllowed => write!(f, \"Match::NotAllowed\"),"
+        "        fn write_path<W: fmt::Write>(&self, w: &mut W) -> fmt::Result;"
     )?;
+    writeln!(w)?;
     writeln!(
         w,
-        "                Match::Route(t) => write!(f, \"Match::Route({{:?}})\", t),"
+        "        /// Make a path to this route with the given parameters."
     )?;
+    writeln!(w, "        fn to_path(&self) -> String {{")?;
+    writeln!(w, "            let mut path = String::new();")?;
+    writeln!(w, "            self.write_path(&mut path)")?;
     writeln!(
         w,
-        "                Match::Redirect(t) => write!(f, \"Match::Redirect({{:?}})\", t),"
+        "                .expect(\"writing to a String can't fail\");"
     )?;
-    writeln!(w, "            }}")?;
+    writeln!(w, "            path")?;
     writeln!(w, "        }}")?;
     writeln!(w, "    }}")?;
     writeln!(w)?;
-    writeln!(w, "    pub struct Error {{")?;
-    writeln!(w, "        param: String,")?;
-    writeln!(w, "        what: Box<dyn fmt::Debug>,")?;
-    writeln!(w, "    }}")?;
-    writeln!(w)?;
-    writeln!(w, "    impl fmt::Debug for Error {{")?;
+    write_urls(w)?;
+
+    writeln!(w, "    #[derive(PartialEq, Eq)]")?;
+    writeln!(w, "    pub enum Match<T> {{")?;
+    writeln!(w, "        NotFound,")?;
     writeln!(
         w,
-        "        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{"
+        "        /// The path matches, but not the method.  Holds the methods"
     )?;
-    writeln!(w, "            f.debug_struct(\"wayfinder::Error\")")?; // TODO: what here?
-    writeln!(w, "                .field(\"param\", &self.param)")?;
-    writeln!(w, "                .field(\"what\", &self.what)")?;
-    writeln!(w, "                .finish()")?;
-    writeln!(w, "        }}")?;
+    writeln!(w, "        /// the route does allow.")?;
+    writeln!(w, "        NotAllowed(&'static [&'static str]),")?;
+    writeln!(w, "        Route(T),")?;
+    writeln!(w, "        Redirect(T),")?;
     writeln!(w, "    }}")?;
     writeln!(w)?;
-    writeln!(w, "    impl fmt::Display for Error {{")?;
+    writeln!(w, "    use {}::fmt;", options.core())?;
+    writeln!(w, "    impl<T: fmt::Debug> fmt::Debug for Match<T> {{")?;
     writeln!(
         w,
         "        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{"
     )?;
-    writeln!(w, "            write!(")?;
-    writeln!(w, "                f,")?;
+    writeln!(w, "            match self {{")?;
     writeln!(
         w,
-        "                \"Error parsing '{{}}' parameter {{:?}}\","
+        "                Match::NotFound => write!(f, \"Match::NotFound\"),"
+    )?;
+    writeln!(
+        w,
+        "                Match::NotAllowed(m) => write!(f, \"Match::NotAllowed({{:?}})\", m),"
+    )?;
+    writeln!(
+        w,
+        "                Match::Route(t) => write!(f, \"Match::Route({{:?}})\", t),"
+    )?;
+    writeln!(
+        w,
+        "                Match::Redirect(t) => write!(f, \"Match::Redirect({{:?}})\", t),"
     )?;
-    writeln!(w, "                self.param, self.what")?;
-    writeln!(w, "            )")?;
-    writeln!(w, "        }}")?;
-    writeln!(w, "    }}")?;
-    writeln!(w)?;
-    writeln!(w, "    impl std::error::Error for Error {{}}")?;
-    writeln!(w)?;
-    writeln!(w, "    impl Error {{")?;
-    writeln!(w, "        pub fn fail<S: AsRef<str>, T: fmt::Debug + 'static>(param: S, what: T) -> Error {{")?;
-    writeln!(w, "            Error {{")?;
-    writeln!(w, "                param: param.as_ref().to_string(),")?;
-    writeln!(w, "                what: Box::new(what),")?;
     writeln!(w, "            }}")?;
     writeln!(w, "        }}")?;
     writeln!(w, "    }}")?;
+    writeln!(w)?;
+    errors.write(w, options)?;
+    write_url_helpers(w, &route_config.routes, options)?;
+    write_url_for(w, &modules, &errors, options)?;
 
     writeln!(w, "    /// Match a path and method against this router.")?;
     writeln!(w, "    ///")?;
@@ -433,27 +770,161 @@ where
         w,
         "    /// or `Match::NotAllowed` if no method matches (in which case a"
     )?;
-    writeln!(w, "    /// `405 Not Allowed` would be appropriate).")?;
+    writeln!(
+        w,
+        "    /// `405 Not Allowed` would be appropriate, with the methods it holds"
+    )?;
+    writeln!(w, "    /// in the `Allow` header).")?;
     writeln!(w, "    ///")?;
     writeln!(
         w,
-        "    /// If a route parameter fails to parse correctly, this will return"
+        "    /// Static path segments take precedence over a parameter in the"
     )?;
     writeln!(
         w,
-        "    /// `Err` with the underlying parsing error.  Usually you'll want"
+        "    /// same place.  If the rest of the path doesn't match after a static"
     )?;
-    writeln!(w, "    /// to send back a `400 Bad Request` for that.")?;
+    writeln!(
+        w,
+        "    /// segment, the parameter is tried instead.  A parameter matches a"
+    )?;
+    writeln!(
+        w,
+        "    /// whole segment, which can't be empty.  Parameters side by side are"
+    )?;
+    writeln!(
+        w,
+        "    /// tried in the order they're declared, and one that fails to parse"
+    )?;
+    writeln!(w, "    /// falls through to the next.")?;
     writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// Parameters are only parsed once the whole path matches, so a path"
+    )?;
+    writeln!(
+        w,
+        "    /// that doesn't match any route is `NotFound` even if a parameter in"
+    )?;
+    writeln!(
+        w,
+        "    /// it is invalid.  One that could fall through to the next is parsed"
+    )?;
+    writeln!(
+        w,
+        "    /// before the method is checked, and the rest after.  If the last"
+    )?;
+    writeln!(
+        w,
+        "    /// route parameter tried fails to parse, this will return `Err` with"
+    )?;
+    writeln!(
+        w,
+        "    /// the underlying parsing error.  Usually you'll want to send back a"
+    )?;
+    writeln!(w, "    /// `400 Bad Request` for that.")?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// Any bytes may be passed without panicking.  A path segment that"
+    )?;
+    writeln!(
+        w,
+        "    /// isn't valid UTF-8 once percent-decoded can't be a parameter, so"
+    )?;
+    writeln!(w, "    /// it's `Match::NotFound`.")?;
+    writeln!(w, "    ///")?;
+    if borrows {
+        writeln!(
+            w,
+            "    /// `String` parameters borrow from the path, so the route can't"
+        )?;
+        writeln!(
+            w,
+            "    /// outlive it.  They're only allocated if they're percent-decoded."
+        )?;
+        writeln!(w, "    ///")?;
+    }
     writeln!(w, "    /// [`Match`]: enum.Match.html")?;
     writeln!(w, "    /// [`Route`]: enum.Route.html")?;
 
-    writeln!(w, "    pub fn match_route<P: AsRef<[u8]>, M: AsRef<[u8]>>(")?;
-    writeln!(w, "        path: P,")?;
+    if borrows {
+        writeln!(
+            w,
+            "    pub fn match_route<P: AsRef<[u8]> + ?Sized, M: AsRef<[u8]>>("
+        )?;
+        writeln!(w, "        path: &P,")?;
+    } else {
+        writeln!(w, "    pub fn match_route<P: AsRef<[u8]>, M: AsRef<[u8]>>(")?;
+        writeln!(w, "        path: P,")?;
+    }
+    writeln!(w, "        method: M,")?;
+    writeln!(w, "    ) -> Result<Match<Route{}>, Error> {{", route_lifetime)?;
+    writeln!(w, "        match_path(path.as_ref(), b\"\", method.as_ref())")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    /// Match a request target and method against this router."
+    )?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// The request target is a path with an optional query string, such"
+    )?;
+    writeln!(
+        w,
+        "    /// as `/people?lang=en`.  This works like [`match_route`], but query"
+    )?;
+    writeln!(
+        w,
+        "    /// parameters are also decoded and parsed from the query string."
+    )?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`match_route`]: fn.match_route.html")?;
+    if borrows {
+        writeln!(
+            w,
+            "    pub fn match_request<T: AsRef<[u8]> + ?Sized, M: AsRef<[u8]>>("
+        )?;
+        writeln!(w, "        target: &T,")?;
+    } else {
+        writeln!(w, "    pub fn match_request<T: AsRef<[u8]>, M: AsRef<[u8]>>(")?;
+        writeln!(w, "        target: T,")?;
+    }
     writeln!(w, "        method: M,")?;
-    writeln!(w, "    ) -> Result<Match<Route>, Error> {{")?;
-    writeln!(w, "        let method = method.as_ref();")?;
-    writeln!(w, "        let path = path.as_ref();")?;
+    writeln!(w, "    ) -> Result<Match<Route{}>, Error> {{", route_lifetime)?;
+    writeln!(w, "        let target = target.as_ref();")?;
+    writeln!(w, "        match target.iter().position(|&b| b == b'?') {{")?;
+    writeln!(
+        w,
+        "            Some(i) => match_path(&target[..i], &target[i + 1..], method.as_ref()),"
+    )?;
+    writeln!(
+        w,
+        "            None => match_path(target, b\"\", method.as_ref()),"
+    )?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    if borrows {
+        writeln!(
+            w,
+            "    fn match_path<'a>(path: &'a [u8], query: &'a [u8], method: &[u8]) -> Result<Match<Route<'a>>, Error> {{"
+        )?;
+    } else {
+        writeln!(
+            w,
+            "    fn match_path(path: &[u8], query: &[u8], method: &[u8]) -> Result<Match<Route>, Error> {{"
+        )?;
+    }
+    if options.collapse_slashes || options.resolve_dot_segments {
+        writeln!(w, "        let path = normalize_path(path);")?;
+        writeln!(w, "        let path = &path[..];")?;
+    }
+    if options.ignore_case {
+        writeln!(w, "        let lower = path.to_ascii_lowercase();")?;
+    }
     writeln!(w, "        let len = path.len();")?;
     writeln!(
         w,
@@ -462,19 +933,101 @@ where
 
     writeln!(w)?;
 
-    codegen_trie(w, &flattened.to_trie(), 2)?;
+    let matcher = Matcher {
+        errors: &errors,
+        options,
+        statics: if options.ignore_case { "lower" } else { "path" },
+        fallback: None,
+        params: vec![],
+    };
+    codegen_trie(w, &trie, 2, &matcher)?;
 
     writeln!(w, "    }}")?;
     writeln!(w)?;
-    writeln!(w, "}} // mod routes")?;
+    if options.collapse_slashes || options.resolve_dot_segments {
+        write_normalize_path(w, options)?;
+    }
+    write_request_line(w, options, borrows)?;
+
+    if options.handlers {
+        write_handlers(w, &modules, options)?;
+    }
+
+    if options.async_handlers {
+        write_async_handlers(w, &modules, &errors, options)?;
+    }
+
+    if options.http || options.tower {
+        write_http(w, &route_lifetime)?;
+    }
+
+    if options.tower {
+        write_tower(w, &modules)?;
+    }
+    writeln!(w, "}} // mod {}", options.module_name)?;
 
     Ok(())
 }
 
-pub fn codegen_trie<W>(
+/// The state of the matcher at a node of the trie.
+#[derive(Clone)]
+struct Matcher<'a> {
+    errors: &'a ErrorVariants,
+    options: &'a CodegenOptions,
+    /// Static segments are matched against this, a lowercase copy of
+    /// the path when ignoring case.
+    statics: &'static str,
+    /// The innermost fallback block, if any.
+    fallback: Option<usize>,
+    /// The parameters matched so far, which are parsed once a resource
+    /// is selected.
+    params: Vec<MatchedParam<'a>>,
+}
+
+/// A parameter that's been matched, but not yet parsed.
+#[derive(Clone)]
+struct MatchedParam<'a> {
+    param: &'a Param,
+    /// The fallback block to give up to if it isn't valid UTF-8.
+    fallback: Option<usize>,
+    /// Whether failing to parse also gives up, so the next alternative
+    /// is tried, rather than being an error.
+    fall_through: bool,
+}
+
+impl<'a> Matcher<'a> {
+    /// The statement that gives up on the current branch: break to the
+    /// enclosing fallback block, or if there isn't one, it's not found.
+    fn fail(&self) -> String {
+        fail(self.fallback)
+    }
+
+    /// The matcher inside a new fallback block.
+    fn within(&self, label: usize) -> Matcher<'a> {
+        let mut matcher = self.clone();
+        matcher.fallback = Some(label);
+        matcher
+    }
+}
+
+fn fail(fallback: Option<usize>) -> String {
+    match fallback {
+        Some(label) => format!("break 'fallback{}", label),
+        None => "return Ok(Match::NotFound)".to_string(),
+    }
+}
+
+/// Write the matcher for a node of the trie.
+///
+/// Static segments take precedence over a parameter in the same
+/// position.  When the rest of the path doesn't match through them, the
+/// matcher backtracks and tries the parameter instead, so every branch
+/// either returns or gives up to the enclosing `fallback` block.
+fn codegen_trie<'a, W>(
     w: &mut W,
-    trie: &Trie<Charlike, FlattenedRoute>,
+    trie: &'a Trie<Charlike, FlattenedRoute>,
     indent: usize,
+    matcher: &Matcher<'a>,
 ) -> io::Result<()>
 where
     W: Write,
@@ -483,57 +1036,109 @@ where
     for _ in 0..indent {
         indent1.push_str("    ");
     }
-    let mut indent2 = indent1.clone();
-    indent2.push_str("    ");
 
     if let Some(ref route) = trie.data {
-        if route.resources.len() != 0 {
-            write_methods(w, route, indent)?;
+        // every route but the root is reached after a trailing slash
+        let trailing_slash = if route.path.segments().is_empty() {
+            TrailingSlash::Lenient
+        } else {
+            route.trailing_slash
+        };
+        match trailing_slash {
+            _ if route.resources.is_empty() => {}
+            TrailingSlash::Lenient => write_methods(w, route, indent, matcher, false)?,
+            TrailingSlash::Redirect => write_methods(w, route, indent, matcher, true)?,
+            TrailingSlash::Strict => {
+                writeln!(w, "{}if i == len {{", indent1)?;
+                writeln!(w, "{}    {};", indent1, matcher.fail())?;
+                writeln!(w, "{}}}", indent1)?;
+            }
         }
     }
 
-    if trie.children.len() == 0 {
-        writeln!(w, "{}return Ok(Match::NotFound);", indent1)?;
-        return Ok(());
+    let separator = trie.children.iter().find(|c| c.0 == Charlike::Separator);
+    let (dynamics, statics): (Vec<_>, Vec<_>) = trie
+        .children
+        .iter()
+        .partition(|c| matches!(c.0, Charlike::Dynamic(..)));
+
+    // check for an exact match, before the separator
+    if let Some((_, ref child)) = separator {
+        match child.data {
+            Some(ref route) if !route.resources.is_empty() => {
+                write_methods(w, route, indent, matcher, false)?;
+            }
+            _ => {
+                writeln!(w, "{}if i == len {{", indent1)?;
+                writeln!(w, "{}    {};", indent1, matcher.fail())?;
+                writeln!(w, "{}}}", indent1)?;
+            }
+        }
     }
 
-    if trie.children.len() == 1 {
-        let (ref segment, ref child) = trie.children[0];
-        match segment {
-            Charlike::Separator => {
-                // check for child match
-                // TODO: this seems backwards??
-                match child.data {
-                    Some(ref route) if route.resources.len() != 0 => {
-                        write_methods(w, route, indent)?;
-                    }
-                    _ => {
-                        writeln!(w, "{}if i == len {{", indent1)?;
-                        writeln!(w, "{}    return Ok(Match::NotFound);", indent1)?;
-                        writeln!(w, "{}}}", indent1)?;
-                    }
-                }
-
-                // check for separator
-                writeln!(w, "{}match &path[i..i+1] {{", indent1)?;
-                writeln!(w, "{}    b\"/\" => {{", indent1)?;
-                writeln!(w, "{}        i += 1;", indent1)?;
-                writeln!(w, "{}    }},", indent1)?;
-                writeln!(w, "{}    _ => return Ok(Match::NotFound),", indent1)?;
+    match dynamics.split_last() {
+        None if statics.is_empty() => {
+            writeln!(w, "{}{};", indent1, matcher.fail())?;
+        }
+        None => {
+            let checked = separator.is_some();
+            write_statics(w, &statics, indent, matcher, checked)?;
+        }
+        Some((last, rest)) => {
+            writeln!(w, "{}let start = i;", indent1)?;
+
+            // try each alternative in turn, starting over after each one
+            let label = matcher.fallback.map_or(0, |n| n + 1);
+            if !statics.is_empty() {
+                writeln!(w, "{}'fallback{}: {{", indent1, label)?;
+                write_statics(w, &statics, indent + 1, &matcher.within(label), false)?;
                 writeln!(w, "{}}}", indent1)?;
-
-                // continue with child
-                codegen_trie(w, child, indent)?;
+                writeln!(w, "{}i = start;", indent1)?;
+            }
+            for dynamic in rest {
+                writeln!(w, "{}'fallback{}: {{", indent1, label)?;
+                write_dynamic(w, dynamic, indent + 1, &matcher.within(label), true)?;
+                writeln!(w, "{}}}", indent1)?;
+                writeln!(w, "{}i = start;", indent1)?;
             }
+            write_dynamic(w, last, indent, matcher, false)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the match on the static children of a node, including the
+/// separator.  If `checked` is set, the path is known to continue.
+fn write_statics<'a, W>(
+    w: &mut W,
+    statics: &[&'a (Charlike, Trie<Charlike, FlattenedRoute>)],
+    indent: usize,
+    matcher: &Matcher<'a>,
+    checked: bool,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut indent1 = String::new();
+    for _ in 0..indent {
+        indent1.push_str("    ");
+    }
+    let mut indent2 = indent1.clone();
+    indent2.push_str("    ");
+
+    if statics.len() == 1 {
+        let (ref segment, ref child) = *statics[0];
+
+        // find unambiguous match
+        let mut unambiguous = String::new();
+        let mut child = child;
+        match segment {
             Charlike::Static(ch) => {
-                // find unambiguous match
-                let mut unambiguous = String::new();
                 unambiguous.push(*ch);
 
-                let mut child = child;
-
                 loop {
-                    if child.children.len() == 1 {
+                    if child.data.is_none() && child.children.len() == 1 {
                         if let Charlike::Static(ch) = child.children[0].0 {
                             unambiguous.push(ch);
                             child = &child.children[0].1;
@@ -542,166 +1147,84 @@ where
                     }
                     break;
                 }
-
-                let match_len = unambiguous.len();
-                if match_len == 1 {
-                    writeln!(w, "{}if i == len {{", indent1)?;
-                } else {
-                    writeln!(w, "{}if i + {} > len {{", indent1, match_len)?;
-                }
-                writeln!(w, "{}    return Ok(Match::NotFound);", indent1)?;
-                writeln!(w, "{}}}", indent1)?;
-
-                // check it
-                writeln!(w, "{}match &path[i..i+{}] {{", indent1, match_len)?;
-                writeln!(w, "{}    b\"{}\" => {{", indent1, unambiguous)?; // TODO quotes in paths - invalid, but??
-                writeln!(w, "{}        i += {};", indent1, match_len)?;
-                writeln!(w, "{}    }},", indent1)?;
-                writeln!(w, "{}    _ => return Ok(Match::NotFound),", indent1)?;
-                writeln!(w, "{}}}", indent1)?;
-
-                // continue after unambiguous
-                codegen_trie(w, child, indent)?;
-            }
-            Charlike::Dynamic(ref name) => {
-                writeln!(w, "{}let start = i;", indent1)?;
-                write_dynamic(w, &child, indent, name)?;
-            }
-        }
-
-        return Ok(());
-    }
-
-    // n.b. if we got here, trie.children.len() > 1
-
-    let dynamic = trie.children.iter().find(|c| match c.0 {
-        Charlike::Dynamic(_) => true,
-        _ => false,
-    });
-    let has_dynamic = match dynamic {
-        Some(_) => true,
-        None => false,
-    };
-
-    if has_dynamic {
-        writeln!(w)?;
-        writeln!(w, "{}let start = i;", indent1)?;
-        writeln!(w)?;
-    }
-
-    let (unambiguous, l, next) = {
-        let mut l = 0;
-        let mut s = String::new();
-        let mut t = trie;
-
-        loop {
-            let has_separator = t.children.iter().any(|c| match c.0 {
-                Charlike::Separator => true,
-                _ => false,
-            });
-            if has_separator {
-                break;
             }
-
-            l += 1;
-
-            let options = t
-                .children
-                .iter()
-                .filter_map(|c| match c.0 {
-                    Charlike::Dynamic(_) => None,
-                    Charlike::Separator => unreachable!(),
-                    Charlike::Static(ch) => Some((ch, &c.1)),
-                })
-                .collect::<Vec<_>>();
-
-            if options.len() != 1 {
-                break;
-            }
-
-            s.push(options[0].0);
-            t = options[0].1;
+            Charlike::Separator => unambiguous.push('/'),
+            Charlike::Dynamic(..) => unreachable!(),
         }
 
-        (s, l, t)
-    };
-
-    let match_len = if l == 0 { 1 } else { l };
-
-    if unambiguous != "" {
-        // n.b. if we got here, the next bit is unambiguous save a dynamic
-
-        // TODO: refactor code to make this clearer!
-        assert!(
-            has_dynamic,
-            "there must be a dynamic or we did something wrong"
-        );
-
-        if match_len == 1 {
-            writeln!(w, "{}if i <= len {{", indent1)?;
-        } else {
-            writeln!(w, "{}if i + {} <= len {{", indent1, match_len)?;
+        let match_len = unambiguous.len();
+        if match_len != 1 {
+            writeln!(w, "{}if i + {} > len {{", indent1, match_len)?;
+            writeln!(w, "{}    {};", indent1, matcher.fail())?;
+            writeln!(w, "{}}}", indent1)?;
+        } else if !checked {
+            writeln!(w, "{}if i == len {{", indent1)?;
+            writeln!(w, "{}    {};", indent1, matcher.fail())?;
+            writeln!(w, "{}}}", indent1)?;
         }
 
-        writeln!(w, "{}match &path[i..i+{}] {{", indent2, match_len)?;
-        writeln!(w, "{}    b\"{}\" => {{", indent2, unambiguous)?; // TODO: quotes in paths - invalid, but??
-        writeln!(w, "{}        i += {};", indent2, match_len)?;
-
-        codegen_trie(w, next, indent + 3)?;
-
-        writeln!(w, "{}    }},", indent2)?;
-
-        writeln!(w, "{}    _ => {{}},", indent2)?;
-        writeln!(w, "{}}}", indent2)?;
+        // check it
+        writeln!(
+            w,
+            "{}match &{}[i..i+{}] {{",
+            indent1, matcher.statics, match_len
+        )?;
+        writeln!(w, "{}    b\"{}\" => {{", indent1, unambiguous)?; // TODO quotes in paths - invalid, but??
+        writeln!(w, "{}        i += {};", indent1, match_len)?;
+        writeln!(w, "{}    }},", indent1)?;
+        writeln!(w, "{}    _ => {},", indent1, matcher.fail())?;
         writeln!(w, "{}}}", indent1)?;
 
-        if let Charlike::Dynamic(ref name) = dynamic.unwrap().0 {
-            write_dynamic(w, &dynamic.unwrap().1, indent, name)?;
-        } else {
-            unreachable!();
-        }
-
-        return Ok(());
+        // continue after unambiguous
+        return codegen_trie(w, child, indent, matcher);
     }
 
     // n.b. if we got here, the next character is ambiguous
-    writeln!(w, "{}if i == len {{", indent1)?;
-    writeln!(w, "{}    return Ok(Match::NotFound);", indent1)?;
-    writeln!(w, "{}}}", indent1)?;
+    if !checked {
+        writeln!(w, "{}if i == len {{", indent1)?;
+        writeln!(w, "{}    {};", indent1, matcher.fail())?;
+        writeln!(w, "{}}}", indent1)?;
+    }
 
-    writeln!(w, "{}match &path[i..i+1] {{", indent1)?;
+    writeln!(w, "{}match &{}[i..i+1] {{", indent1, matcher.statics)?;
 
-    for child in trie.children.iter() {
-        match child.0 {
-            Charlike::Static(c) => {
-                writeln!(w, "{}b\"{}\" => {{", indent2, c)?;
-                writeln!(w, "{}    i += 1;", indent2)?;
+    for (segment, child) in statics.iter().map(|c| (&c.0, &c.1)) {
+        let ch = match segment {
+            Charlike::Static(ch) => *ch,
+            Charlike::Separator => '/',
+            Charlike::Dynamic(..) => unreachable!(),
+        };
 
-                codegen_trie(w, &child.1, indent + 2)?;
+        writeln!(w, "{}b\"{}\" => {{", indent2, ch)?;
+        writeln!(w, "{}    i += 1;", indent2)?;
 
-                writeln!(w, "{}}},", indent2)?;
-            }
-            Charlike::Dynamic(ref p) => {
-                write_dynamic(w, &child.1, indent, p)?;
+        codegen_trie(w, child, indent + 2, matcher)?;
 
-                // No further routes will possibly match.
-                // TODO: is this true still?
-                break;
-            }
-            Charlike::Separator => {
-                writeln!(w, "{}Some('/') => {{}}", indent2)?;
-            }
-        }
+        writeln!(w, "{}}},", indent2)?;
     }
 
-    writeln!(w, "{}    _ => return Ok(Match::NotFound),", indent1)?;
-
+    writeln!(w, "{}    _ => {},", indent1, matcher.fail())?;
     writeln!(w, "{}}}", indent1)?;
 
     Ok(())
 }
 
-fn write_methods<W>(w: &mut W, route: &FlattenedRoute, indent: usize) -> io::Result<()>
+/// Write the match on the method for a route.  If `redirect` is set,
+/// `GET` resources are a redirect, to their canonical path.  Other
+/// methods are matched as usual, since clients turn a redirect of
+/// them into a `GET`.
+///
+/// The parameters in the path are only parsed once the whole path
+/// matches.  Those that can fall through to a sibling are parsed before
+/// the method, since the route only matches if they parse, and the
+/// rest once a resource is selected.
+fn write_methods<W>(
+    w: &mut W,
+    route: &FlattenedRoute,
+    indent: usize,
+    matcher: &Matcher,
+    redirect: bool,
+) -> io::Result<()>
 where
     W: Write,
 {
@@ -710,7 +1233,12 @@ where
         indent1.push_str("    ");
     }
 
+    let (early, late): (Vec<_>, Vec<_>) = matcher.params.iter().partition(|p| p.fall_through);
+
     writeln!(w, "{}if i == len {{", indent1)?;
+    for matched in early {
+        write_parse_path(w, matched, &format!("{}    ", indent1), matcher)?;
+    }
     writeln!(w, "{}    match method {{", indent1)?;
 
     for resource in route.resources.iter() {
@@ -726,12 +1254,24 @@ where
             (path, accum, parens)
         };
 
+        // the arm is a block if there are parameters to parse first
+        let indent2 = if late.is_empty() {
+            write!(w, "{}        {} => ", indent1, resource.method.byte_str())?;
+            format!("{}        ", indent1)
+        } else {
+            writeln!(w, "{}        {} => {{", indent1, resource.method.byte_str())?;
+            let indent2 = format!("{}            ", indent1);
+            for matched in late.iter() {
+                write_parse_path(w, matched, &indent2, matcher)?;
+            }
+            write!(w, "{}", indent2)?;
+            indent2
+        };
+
         writeln!(
             w,
-            "{0}        {1} => return Ok(Match::{2}({3}{4}Route::{5}({4}{5} {{",
-            indent1,
-            resource.method.byte_str(),
-            if resource.is_redirect {
+            "return Ok(Match::{}({}{}Route::{}({}{} {{",
+            if resource.is_redirect || (redirect && resource.method == Method::Get) {
                 "Redirect"
             } else {
                 "Route"
@@ -739,61 +1279,162 @@ where
             route_nest,
             path,
             to_caps_case(&resource.name),
+            path,
+            to_caps_case(&resource.name),
         )?;
 
         for param in route.path.dynamics() {
-            writeln!(w, "{}            {},", indent1, param.name)?;
+            writeln!(w, "{}    {}: {},", indent2, param.name, local(param))?;
         }
-        for param in route.query_parameters.iter() {
-            writeln!(w, "{}            {}: None,", indent1, param.name)?;
+        let query_parameters = route
+            .query_parameters
+            .iter()
+            .chain(resource.query_parameters.iter());
+        for param in query_parameters {
+            if matcher.options.borrows(param) {
+                writeln!(
+                    w,
+                    "{}    {}: borrow_query(query, \"{}\", ParamKind::Query, {})?,",
+                    indent2, param.name, param.name, param.raw
+                )?;
+                continue;
+            }
+            writeln!(
+                w,
+                "{}    {}: parse_query(query, \"{}\", ParamKind::Query, {}, |kind, text, error| Error::{} {{",
+                indent2,
+                param.name,
+                param.name,
+                param.raw,
+                matcher.errors.variant(param)
+            )?;
+            writeln!(w, "{}        kind,", indent2)?;
+            writeln!(w, "{}        text,", indent2)?;
+            writeln!(w, "{}        error,", indent2)?;
+            writeln!(w, "{}    }})?,", indent2)?;
         }
-        for param in resource.query_parameters.iter() {
-            writeln!(w, "{}            {}: None,", indent1, param.name)?;
+        for param in resource.body_parameters.iter() {
+            writeln!(w, "{}    {}: None,", indent2, param.name)?;
         }
 
-        writeln!(w, "{}        }}{}))),", indent1, close_parens)?;
+        if late.is_empty() {
+            writeln!(w, "{}}}{}))),", indent2, close_parens)?;
+        } else {
+            writeln!(w, "{}}}{})));", indent2, close_parens)?;
+            writeln!(w, "{}        }}", indent1)?;
+        }
     }
 
-    writeln!(w, "{}        _ => return Ok(Match::NotAllowed),", indent1)?;
+    let mut allowed = vec![];
+    for resource in route.resources.iter() {
+        let method = format!("\"{}\"", resource.method);
+        if !allowed.contains(&method) {
+            allowed.push(method);
+        }
+    }
+    writeln!(
+        w,
+        "{}        _ => return Ok(Match::NotAllowed(&[{}])),",
+        indent1,
+        allowed.join(", ")
+    )?;
     writeln!(w, "{}    }}", indent1)?;
     writeln!(w, "{}}}", indent1)?;
 
     Ok(())
 }
 
-// assumes that a None case has already been written
-fn write_dynamic<W>(
+/// Write the parsing of a matched path parameter, shadowing the text
+/// of the segment with the parsed value.
+fn write_parse_path<W>(
     w: &mut W,
-    trie: &Trie<Charlike, FlattenedRoute>,
+    matched: &MatchedParam,
+    indent: &str,
+    matcher: &Matcher,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let name = local(matched.param);
+    if matcher.options.borrows(matched.param) {
+        // borrowing can only fail if it isn't valid UTF-8
+        writeln!(
+            w,
+            "{}let {} = match borrow_path({}, {}) {{",
+            indent, name, name, matched.param.raw
+        )?;
+        writeln!(w, "{}    Some({1}) => {1},", indent, name)?;
+        writeln!(w, "{}    None => {},", indent, fail(matched.fallback))?;
+        writeln!(w, "{}}};", indent)?;
+        return Ok(());
+    }
+
+    writeln!(
+        w,
+        "{}let {} = match parse_path({}, {}) {{",
+        indent, name, name, matched.param.raw
+    )?;
+    writeln!(w, "{}    Some(Ok({1})) => {1},", indent, name)?;
+    if matched.fall_through {
+        writeln!(w, "{}    _ => {},", indent, fail(matched.fallback))?;
+    } else {
+        writeln!(
+            w,
+            "{}    Some(Err((text, error))) => return Err(Error::{} {{",
+            indent,
+            matcher.errors.variant(matched.param)
+        )?;
+        writeln!(w, "{}        kind: ParamKind::Path,", indent)?;
+        writeln!(w, "{}        text,", indent)?;
+        writeln!(w, "{}        error,", indent)?;
+        writeln!(w, "{}    }}),", indent)?;
+        writeln!(w, "{}    None => {},", indent, fail(matched.fallback))?;
+    }
+    writeln!(w, "{}}};", indent)?;
+
+    Ok(())
+}
+
+/// The name of the local holding a parameter, prefixed so that it can't
+/// shadow one of the generated code's own locals, like `path` or `query`
+/// in the matcher, or `w` and `first` in `write_path`.
+fn local(param: &Param) -> String {
+    format!("param_{}", param.name)
+}
+
+/// Write the match on a parameter, assuming `start` is where it starts.
+/// A parameter matches the rest of the segment, which can't be empty,
+/// and is parsed later, once a resource is selected.  If `fall_through`
+/// is set, a parameter that fails to parse gives up to the fallback, so
+/// the next alternative can be tried, instead of being an error.
+fn write_dynamic<'a, W>(
+    w: &mut W,
+    dynamic: &'a (Charlike, Trie<Charlike, FlattenedRoute>),
     indent: usize,
-    name: &str,
+    matcher: &Matcher<'a>,
+    fall_through: bool,
 ) -> io::Result<()>
 where
     W: Write,
 {
+    let (param, trie) = match dynamic {
+        (Charlike::Dynamic(_, ref param), ref trie) => (param, trie),
+        _ => unreachable!(),
+    };
+
     let mut indent1 = String::new();
     for _ in 0..indent {
         indent1.push_str("    ");
     }
-    let mut indent2 = indent1.clone();
-    indent2.push_str("    ");
 
     writeln!(w)?;
     writeln!(w, "{}while i < len && &path[i..i+1] != b\"/\" {{", indent1)?;
     writeln!(w, "{}    i += 1;", indent1)?;
     writeln!(w, "{}}}", indent1)?;
-    writeln!(w)?;
-    writeln!(
-        w,
-        "{}let text = std::str::from_utf8(&path[start..i]).unwrap();",
-        indent1
-    )?;
-    writeln!(w, "{}let {} = text.parse()", indent1, name)?;
-    writeln!(
-        w,
-        "{}    .map_err(|e| Error::fail(\"{}\", e))?;",
-        indent1, name
-    )?;
+    writeln!(w, "{}if i == start {{", indent1)?;
+    writeln!(w, "{}    {};", indent1, matcher.fail())?;
+    writeln!(w, "{}}}", indent1)?;
+    writeln!(w, "{}let {} = &path[start..i];", indent1, local(param))?;
     writeln!(w)?;
 
     // must be followed by a separator
@@ -804,7 +1445,13 @@ where
         return Err(io::ErrorKind::InvalidInput.into());
     }
 
-    codegen_trie(w, trie, indent)?;
+    let mut matcher = matcher.clone();
+    matcher.params.push(MatchedParam {
+        param,
+        fallback: matcher.fallback,
+        fall_through,
+    });
+    codegen_trie(w, trie, indent, &matcher)?;
 
     Ok(())
 }
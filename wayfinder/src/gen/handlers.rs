@@ -0,0 +1,523 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+
+use crate::core::{display_action, display_path, Diagnostic, PathSegment, Routes, SourceMap};
+use crate::gen::error::ErrorVariants;
+use crate::gen::flat::{FlattenedModule, FlattenedModules};
+use crate::gen::gen::{action_borrows, lifetime, module_borrows, to_caps_case, to_snake_case};
+use crate::gen::options::CodegenOptions;
+use crate::gen::url::action_type;
+
+/// The name of the handler method for an action, such as `people_show`.
+pub(crate) fn handler_name(modules: &[&str], action: &str) -> String {
+    modules
+        .iter()
+        .map(|module| to_snake_case(module))
+        .chain(std::iter::once(to_snake_case(action)))
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Check that every action gets a handler method of its own.  Names
+/// are only joined with `_`, so `People::ShowAll` and `PeopleShow::All`
+/// would both be `people_show_all`, and a root action can land on one
+/// of the names the generated code already uses, like `not_found`.
+/// Nothing is checked unless the options generate code named for the
+/// handlers.
+pub(crate) fn check_handler_names(
+    routes: &Routes,
+    options: &CodegenOptions,
+    source_map: &SourceMap,
+) -> Vec<Diagnostic> {
+    if !(options.handlers || options.async_handlers || options.tower) {
+        return vec![];
+    }
+
+    let mut reserved = HashMap::new();
+    if options.async_handlers {
+        for name in &["not_found", "not_allowed", "redirect", "bad_request"] {
+            reserved.insert(name.to_string(), "`AsyncHandlers`");
+        }
+    }
+    if options.tower {
+        for name in &["routes", "not_found", "not_allowed", "bad_request"] {
+            reserved
+                .entry(name.to_string())
+                .or_insert("the tower `Router`");
+        }
+    }
+
+    let mut checker = NameChecker {
+        source_map,
+        reserved,
+        seen: HashMap::new(),
+        diagnostics: vec![],
+    };
+    checker.check_routes(routes, &mut vec![]);
+
+    checker.diagnostics
+}
+
+struct NameChecker<'a> {
+    source_map: &'a SourceMap,
+    reserved: HashMap<String, &'static str>,
+    seen: HashMap<String, String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> NameChecker<'a> {
+    fn check_routes(&mut self, routes: &Routes, path: &mut Vec<PathSegment>) {
+        for resource in routes.resources.iter().filter(|r| !r.is_redirect) {
+            let modules = resource
+                .modules
+                .iter()
+                .map(|m| m.as_str())
+                .collect::<Vec<_>>();
+            let name = handler_name(&modules, &resource.name);
+            let action = display_action(resource);
+
+            let message = if let Some(owner) = self.reserved.get(&name) {
+                format!(
+                    "action `{}` has the handler name `{}`, which {} already uses",
+                    action, name, owner
+                )
+            } else {
+                match self.seen.get(&name) {
+                    // a duplicate action is reported by `validate`
+                    Some(other) if *other != action => format!(
+                        "action `{}` has the same handler name `{}` as `{}`",
+                        action, name, other
+                    ),
+                    _ => {
+                        self.seen.insert(name, action);
+                        continue;
+                    }
+                }
+            };
+
+            let location =
+                self.source_map
+                    .resource(path, resource.method, &display_action(resource));
+            self.diagnostics
+                .push(Diagnostic::error(display_path(path), message).at(location));
+        }
+
+        for child in routes.routes.iter() {
+            path.push(child.path_segment.clone());
+            self.check_routes(&child.routes, path);
+            path.pop();
+        }
+    }
+}
+
+/// Write the `Handlers` trait, with a method for every action, and
+/// `dispatch`, which calls the right one for a `Route`.
+pub fn write_handlers<W>(
+    w: &mut W,
+    modules: &FlattenedModules,
+    options: &CodegenOptions,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(w, "    /// Handlers for every action of this router.")?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// There is a method for each action, named for its module path and"
+    )?;
+    writeln!(
+        w,
+        "    /// action, such as `people_show`.  Pass an implementation to"
+    )?;
+    writeln!(
+        w,
+        "    /// [`dispatch`] to call the handler for a matched route.  Since every"
+    )?;
+    writeln!(
+        w,
+        "    /// method must be implemented, adding a route is a compile error"
+    )?;
+    writeln!(w, "    /// until it's handled.")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`dispatch`]: fn.dispatch.html")?;
+    writeln!(w, "    pub trait Handlers {{")?;
+    writeln!(
+        w,
+        "        /// What the handlers return, such as a response."
+    )?;
+    writeln!(w, "        type Output;")?;
+
+    write_handler_methods(w, modules, options, "Self::Output")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "    /// Call the handler for the route, returning its output."
+    )?;
+    writeln!(
+        w,
+        "    pub fn dispatch<H: Handlers>(handlers: &H, route: Route{}) -> H::Output {{",
+        lifetime(module_borrows(&modules.root, options), "'_")
+    )?;
+    write_dispatch(w, &modules.root, &mut vec![], "        ", None)?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+/// Write the `AsyncHandlers` trait, with an async method for every
+/// action and for each way a request can fail to match, and an async
+/// `dispatch`, which matches a `Request` and awaits the right one.
+pub fn write_async_handlers<W>(
+    w: &mut W,
+    modules: &FlattenedModules,
+    errors: &ErrorVariants,
+    options: &CodegenOptions,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let core = options.core();
+    writeln!(w, "    /// A request for [`dispatch`].")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`dispatch`]: fn.dispatch.html")?;
+    writeln!(w, "    #[derive(Debug, Clone, Copy, PartialEq, Eq)]")?;
+    writeln!(w, "    pub struct Request<'a> {{")?;
+    writeln!(w, "        /// The method, such as `b\"GET\"`.")?;
+    writeln!(w, "        pub method: &'a [u8],")?;
+    writeln!(
+        w,
+        "        /// The request target, a path with an optional query string."
+    )?;
+    writeln!(w, "        pub target: &'a [u8],")?;
+    writeln!(
+        w,
+        "        /// The body, if it's `application/x-www-form-urlencoded`.  Body"
+    )?;
+    writeln!(w, "        /// parameters are read from its fields.")?;
+    writeln!(w, "        pub form: Option<&'a [u8]>,")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(w, "    impl<'a> Request<'a> {{")?;
+    writeln!(w, "        /// A request without a form body.")?;
+    writeln!(
+        w,
+        "        pub fn new(method: &'a [u8], target: &'a [u8]) -> Request<'a> {{"
+    )?;
+    writeln!(w, "            Request {{")?;
+    writeln!(w, "                method,")?;
+    writeln!(w, "                target,")?;
+    writeln!(w, "                form: None,")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(w, "        /// Add a form body to the request.")?;
+    writeln!(
+        w,
+        "        pub fn form(self, form: &'a [u8]) -> Request<'a> {{"
+    )?;
+    writeln!(w, "            Request {{")?;
+    writeln!(w, "                form: Some(form),")?;
+    writeln!(w, "                ..self")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "    /// Async handlers for every action of this router, and for requests"
+    )?;
+    writeln!(w, "    /// that don't match one.")?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// The action methods are named like those of `Handlers`, such as"
+    )?;
+    writeln!(
+        w,
+        "    /// `people_show`, and may be implemented with `async fn`.  Pass an"
+    )?;
+    writeln!(
+        w,
+        "    /// implementation to [`dispatch`] to await the handler for a request."
+    )?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`dispatch`]: fn.dispatch.html")?;
+    writeln!(w, "    pub trait AsyncHandlers {{")?;
+    writeln!(
+        w,
+        "        /// What the handlers return, such as a response."
+    )?;
+    writeln!(w, "        type Output;")?;
+    writeln!(w)?;
+    writeln!(w, "        /// Handle a request that matches no route.")?;
+    writeln!(
+        w,
+        "        fn not_found(&self) -> impl {}::future::Future<Output = Self::Output>;",
+        core
+    )?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        /// Handle a request whose path matches but method doesn't, given"
+    )?;
+    writeln!(w, "        /// the methods the route allows.")?;
+    writeln!(
+        w,
+        "        fn not_allowed(&self, methods: &'static [&'static str]) -> impl {}::future::Future<Output = Self::Output>;", core
+    )?;
+    writeln!(w)?;
+    writeln!(w, "        /// Handle a request for a redirect route.")?;
+    writeln!(
+        w,
+        "        fn redirect(&self, route: Route{}) -> impl {}::future::Future<Output = Self::Output>;",
+        lifetime(module_borrows(&modules.root, options), "'_"), core
+    )?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        /// Handle a request with a parameter that failed to parse."
+    )?;
+    writeln!(
+        w,
+        "        fn bad_request(&self, error: Error) -> impl {}::future::Future<Output = Self::Output>;", core
+    )?;
+
+    let output = format!("impl {}::future::Future<Output = Self::Output>", core);
+    write_handler_methods(w, modules, options, &output)?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "    /// Match the request and await its handler, returning the output."
+    )?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// The action handlers get their parameters already parsed, from the"
+    )?;
+    writeln!(
+        w,
+        "    /// path, the query string and the form body.  A form field is only"
+    )?;
+    writeln!(
+        w,
+        "    /// read for a body parameter, and a query parameter only from the"
+    )?;
+    writeln!(
+        w,
+        "    /// query string.  If a body parameter fails to parse, the request"
+    )?;
+    writeln!(w, "    /// goes to `bad_request`.")?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// This doesn't depend on any async runtime, so the future may be run"
+    )?;
+    writeln!(w, "    /// on whichever executor the handlers need.")?;
+    writeln!(
+        w,
+        "    pub async fn dispatch<H: AsyncHandlers>(handlers: &H, request: Request<'_>) -> H::Output {{"
+    )?;
+    writeln!(w, "        let target = request.target;")?;
+    writeln!(
+        w,
+        "        let (path, query) = match target.iter().position(|&b| b == b'?') {{"
+    )?;
+    writeln!(
+        w,
+        "            Some(i) => (&target[..i], &target[i + 1..]),"
+    )?;
+    writeln!(w, "            None => (target, &b\"\"[..]),")?;
+    writeln!(w, "        }};")?;
+    let has_body = modules
+        .root
+        .all_actions()
+        .iter()
+        .any(|(_, action)| !action.body_parameters.is_empty());
+    if has_body {
+        writeln!(w, "        let form = request.form.unwrap_or(&[]);")?;
+    }
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        let route = match match_path(path, query, request.method) {{"
+    )?;
+    writeln!(w, "            Ok(Match::Route(route)) => route,")?;
+    writeln!(
+        w,
+        "            Ok(Match::Redirect(route)) => return handlers.redirect(route).await,"
+    )?;
+    writeln!(
+        w,
+        "            Ok(Match::NotFound) => return handlers.not_found().await,"
+    )?;
+    writeln!(
+        w,
+        "            Ok(Match::NotAllowed(methods)) => return handlers.not_allowed(methods).await,"
+    )?;
+    writeln!(
+        w,
+        "            Err(error) => return handlers.bad_request(error).await,"
+    )?;
+    writeln!(w, "        }};")?;
+    let body = Body { errors, options };
+    write_dispatch(w, &modules.root, &mut vec![], "        ", Some(&body))?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+/// Write a trait method for every action, grouped by module.
+fn write_handler_methods<W>(
+    w: &mut W,
+    modules: &FlattenedModules,
+    options: &CodegenOptions,
+    output: &str,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut last_modules = None;
+    for (path, action) in modules.root.all_actions() {
+        writeln!(w)?;
+        if !path.is_empty() && last_modules.as_ref() != Some(&path) {
+            let module = path
+                .iter()
+                .map(|module| to_snake_case(module))
+                .collect::<Vec<_>>()
+                .join("::");
+            writeln!(w, "        // {}", module)?;
+            writeln!(w)?;
+        }
+
+        writeln!(w, "        /// Handle `{} {}`.", action.method, action.path)?;
+        writeln!(
+            w,
+            "        fn {}(&self, action: {}{}) -> {};",
+            handler_name(&path, &action.name),
+            action_type(&path, action),
+            lifetime(action_borrows(action, options), "'_"),
+            output
+        )?;
+
+        last_modules = Some(path);
+    }
+
+    Ok(())
+}
+
+/// How the async `dispatch` reads body parameters from the form.
+struct Body<'a> {
+    errors: &'a ErrorVariants,
+    options: &'a CodegenOptions,
+}
+
+/// Write the match that calls the handler for each action.  With a
+/// `body`, the handlers are async, and body parameters are read from the
+/// form before the handler is awaited.
+fn write_dispatch<'a, W>(
+    w: &mut W,
+    module: &'a FlattenedModule,
+    path: &mut Vec<&'a str>,
+    indent: &str,
+    body: Option<&Body>,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let prefix = path
+        .iter()
+        .map(|module| format!("{}::", to_snake_case(module)))
+        .collect::<Vec<_>>()
+        .concat();
+    let route = if path.is_empty() { "route" } else { "inner" };
+    let call = if body.is_some() { ".await" } else { "" };
+
+    writeln!(w, "{}match {} {{", indent, route)?;
+    for action in module.actions.iter() {
+        let body = match body {
+            Some(body) if !action.body_parameters.is_empty() => body,
+            _ => {
+                writeln!(
+                    w,
+                    "{}    {}Route::{}(action) => handlers.{}(action){},",
+                    indent,
+                    prefix,
+                    to_caps_case(&action.name),
+                    handler_name(path, &action.name),
+                    call
+                )?;
+                continue;
+            }
+        };
+
+        writeln!(
+            w,
+            "{}    {}Route::{}(mut action) => {{",
+            indent,
+            prefix,
+            to_caps_case(&action.name)
+        )?;
+        for param in action.body_parameters.iter() {
+            if body.options.borrows(param) {
+                writeln!(
+                    w,
+                    "{}        action.{} = match borrow_query(form, \"{}\", ParamKind::Body, {}) {{",
+                    indent, param.name, param.name, param.raw
+                )?;
+            } else {
+                writeln!(
+                    w,
+                    "{}        action.{} = match parse_query(form, \"{}\", ParamKind::Body, {}, |kind, text, error| Error::{} {{",
+                    indent,
+                    param.name,
+                    param.name,
+                    param.raw,
+                    body.errors.variant(param)
+                )?;
+                writeln!(w, "{}            kind,", indent)?;
+                writeln!(w, "{}            text,", indent)?;
+                writeln!(w, "{}            error,", indent)?;
+                writeln!(w, "{}        }}) {{", indent)?;
+            }
+            writeln!(w, "{}            Ok(value) => value,", indent)?;
+            writeln!(
+                w,
+                "{}            Err(error) => return handlers.bad_request(error).await,",
+                indent
+            )?;
+            writeln!(w, "{}        }};", indent)?;
+        }
+        writeln!(
+            w,
+            "{}        handlers.{}(action).await",
+            indent,
+            handler_name(path, &action.name)
+        )?;
+        writeln!(w, "{}    }}", indent)?;
+    }
+    for child in module.modules.iter() {
+        writeln!(
+            w,
+            "{}    {}Route::{}(inner) => {{",
+            indent,
+            prefix,
+            to_caps_case(&child.name)
+        )?;
+        path.push(&child.name);
+        write_dispatch(w, child, path, &format!("{}        ", indent), body)?;
+        path.pop();
+        writeln!(w, "{}    }}", indent)?;
+    }
+    writeln!(w, "{}}}", indent)?;
+
+    Ok(())
+}
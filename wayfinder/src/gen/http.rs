@@ -0,0 +1,101 @@
+use std::io;
+use std::io::Write;
+
+/// Write the helpers for working with the `http` crate: matching an
+/// `http::Request`, and turning a `Match` into an `http::Response`.
+pub fn write_http<W>(w: &mut W, route_lifetime: &str) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(
+        w,
+        "    /// Match an `http::Request` against this router, using its method,"
+    )?;
+    writeln!(w, "    /// path and query string.")?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// See [`match_request`] for the details.  The request body is"
+    )?;
+    writeln!(w, "    /// not used.")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`match_request`]: fn.match_request.html")?;
+    writeln!(
+        w,
+        "    pub fn match_http_request<B>(request: &http::Request<B>) -> Result<Match<Route{}>, Error> {{",
+        route_lifetime
+    )?;
+    writeln!(w, "        let uri = request.uri();")?;
+    writeln!(w, "        match_path(")?;
+    writeln!(w, "            uri.path().as_bytes(),")?;
+    writeln!(w, "            uri.query().unwrap_or(\"\").as_bytes(),")?;
+    writeln!(w, "            request.method().as_str().as_bytes(),")?;
+    writeln!(w, "        )")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    /// Make the skeleton of a response to a match: `404 Not Found`,"
+    )?;
+    writeln!(
+        w,
+        "    /// `405 Method Not Allowed` with the allowed methods in the `Allow`"
+    )?;
+    writeln!(
+        w,
+        "    /// header, or `301 Moved Permanently` with the path of the redirect"
+    )?;
+    writeln!(
+        w,
+        "    /// in the `Location` header.  A matched route is `200 OK`, ready for"
+    )?;
+    writeln!(w, "    /// the handler to fill in.")?;
+    writeln!(
+        w,
+        "    impl<T: ToPath> From<Match<T>> for http::Response<()> {{"
+    )?;
+    writeln!(
+        w,
+        "        fn from(result: Match<T>) -> http::Response<()> {{"
+    )?;
+    writeln!(w, "            let response = http::Response::builder();")?;
+    writeln!(w, "            let response = match result {{")?;
+    writeln!(
+        w,
+        "                Match::NotFound => response.status(http::StatusCode::NOT_FOUND),"
+    )?;
+    writeln!(w, "                Match::NotAllowed(methods) => response")?;
+    writeln!(
+        w,
+        "                    .status(http::StatusCode::METHOD_NOT_ALLOWED)"
+    )?;
+    writeln!(
+        w,
+        "                    .header(http::header::ALLOW, methods.join(\", \")),"
+    )?;
+    writeln!(
+        w,
+        "                Match::Route(_) => response.status(http::StatusCode::OK),"
+    )?;
+    writeln!(w, "                Match::Redirect(route) => response")?;
+    writeln!(
+        w,
+        "                    .status(http::StatusCode::MOVED_PERMANENTLY)"
+    )?;
+    writeln!(
+        w,
+        "                    .header(http::header::LOCATION, route.to_path()),"
+    )?;
+    writeln!(w, "            }};")?;
+    writeln!(w, "            response")?;
+    writeln!(w, "                .body(())")?;
+    writeln!(
+        w,
+        "                .expect(\"generated paths are valid header values\")"
+    )?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
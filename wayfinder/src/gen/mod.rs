@@ -1,7 +1,17 @@
 //! Codegen utilities for producing route matchers.
 
+mod error;
 mod flat;
 mod gen;
+mod handlers;
+mod http;
+mod normalize;
+mod options;
+mod request_line;
+mod tower;
 mod trie;
+mod url;
 
-pub use gen::codegen;
+pub(crate) use handlers::check_handler_names;
+pub use gen::{codegen, codegen_with_options, codegen_with_source_map};
+pub use options::CodegenOptions;
@@ -0,0 +1,90 @@
+use std::io;
+use std::io::Write;
+
+use crate::gen::options::CodegenOptions;
+
+/// Write `normalize_path`, which rewrites a path before it's matched,
+/// according to the normalisation modes enabled in the options.
+pub fn write_normalize_path<W>(w: &mut W, options: &CodegenOptions) -> io::Result<()>
+where
+    W: Write,
+{
+    let alloc = options.alloc();
+    writeln!(w, "    /// Normalize a path before matching it.")?;
+    if options.collapse_slashes {
+        writeln!(
+            w,
+            "    /// Repeated slashes are collapsed, so `//people` is `/people`."
+        )?;
+    }
+    if options.resolve_dot_segments {
+        writeln!(
+            w,
+            "    /// Dot segments are resolved as in RFC 3986, so `/people/./new`"
+        )?;
+        writeln!(
+            w,
+            "    /// is `/people/new` and `/people/..` is `/`.  Like static segments,"
+        )?;
+        writeln!(w, "    /// a percent-encoded dot is not a dot.")?;
+    }
+    writeln!(
+        w,
+        "    fn normalize_path(path: &[u8]) -> {}::borrow::Cow<'_, [u8]> {{",
+        alloc
+    )?;
+    writeln!(w, "        let (prefix, rest) = match path.first() {{")?;
+    writeln!(w, "            Some(b'/') => (&path[..1], &path[1..]),")?;
+    writeln!(w, "            _ => (&path[..0], path),")?;
+    writeln!(w, "        }};")?;
+    writeln!(w, "        let count = rest.split(|&b| b == b'/').count();")?;
+    writeln!(
+        w,
+        "        let mut segments: Vec<&[u8]> = Vec::with_capacity(count);"
+    )?;
+    writeln!(w, "        let mut changed = false;")?;
+    writeln!(
+        w,
+        "        for (n, segment) in rest.split(|&b| b == b'/').enumerate() {{"
+    )?;
+    writeln!(w, "            let last = n + 1 == count;")?;
+    writeln!(w, "            match segment {{")?;
+    if options.collapse_slashes {
+        writeln!(w, "                b\"\" if !last => changed = true,")?;
+    }
+    if options.resolve_dot_segments {
+        writeln!(w, "                b\".\" => {{")?;
+        writeln!(w, "                    changed = true;")?;
+        writeln!(w, "                    if last {{")?;
+        writeln!(w, "                        segments.push(b\"\");")?;
+        writeln!(w, "                    }}")?;
+        writeln!(w, "                }}")?;
+        writeln!(w, "                b\"..\" => {{")?;
+        writeln!(w, "                    changed = true;")?;
+        writeln!(w, "                    segments.pop();")?;
+        writeln!(w, "                    if last {{")?;
+        writeln!(w, "                        segments.push(b\"\");")?;
+        writeln!(w, "                    }}")?;
+        writeln!(w, "                }}")?;
+    }
+    writeln!(w, "                _ => segments.push(segment),")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "        if !changed {{")?;
+    writeln!(
+        w,
+        "            return {}::borrow::Cow::Borrowed(path);",
+        alloc
+    )?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "        let mut normalized = prefix.to_vec();")?;
+    writeln!(
+        w,
+        "        normalized.extend_from_slice(&segments.join(&b'/'));"
+    )?;
+    writeln!(w, "        {}::borrow::Cow::Owned(normalized)", alloc)?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
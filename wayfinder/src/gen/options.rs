@@ -0,0 +1,250 @@
+use crate::core::{Param, TrailingSlash};
+
+/// Options controlling the shape of the generated route matcher.
+///
+/// The defaults produce a module named `routes` with `pub` visibility,
+/// the same as [`codegen`](fn.codegen.html).
+///
+/// ```
+/// # use wayfinder::gen::CodegenOptions;
+/// let options = CodegenOptions::new()
+///     .module_name("admin_routes")
+///     .visibility("pub(crate)");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodegenOptions {
+    pub(crate) module_name: String,
+    pub(crate) visibility: String,
+    pub(crate) http: bool,
+    pub(crate) handlers: bool,
+    pub(crate) async_handlers: bool,
+    pub(crate) tower: bool,
+    pub(crate) trailing_slash: TrailingSlash,
+    pub(crate) collapse_slashes: bool,
+    pub(crate) resolve_dot_segments: bool,
+    pub(crate) ignore_case: bool,
+    pub(crate) borrowed: bool,
+    pub(crate) no_std: bool,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> CodegenOptions {
+        CodegenOptions {
+            module_name: "routes".to_string(),
+            visibility: "pub".to_string(),
+            http: false,
+            handlers: false,
+            async_handlers: false,
+            tower: false,
+            trailing_slash: TrailingSlash::Lenient,
+            collapse_slashes: false,
+            resolve_dot_segments: false,
+            ignore_case: false,
+            borrowed: false,
+            no_std: false,
+        }
+    }
+}
+
+impl CodegenOptions {
+    /// Create the default options.
+    pub fn new() -> CodegenOptions {
+        CodegenOptions::default()
+    }
+
+    /// Set the name of the generated module.  Defaults to `routes`.
+    pub fn module_name<S: AsRef<str>>(mut self, name: S) -> CodegenOptions {
+        self.module_name = name.as_ref().to_string();
+        self
+    }
+
+    /// Set the visibility of the generated module, for instance
+    /// `pub(crate)`.  Use an empty string for a private module.
+    /// Defaults to `pub`.
+    pub fn visibility<S: AsRef<str>>(mut self, visibility: S) -> CodegenOptions {
+        self.visibility = visibility.as_ref().to_string();
+        self
+    }
+
+    /// Set how paths with a trailing slash are matched, for routes that
+    /// don't set it in the route file.  Defaults to
+    /// `TrailingSlash::Lenient`.
+    pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> CodegenOptions {
+        self.trailing_slash = trailing_slash;
+        self
+    }
+
+    /// Collapse repeated slashes in a path before matching it, so
+    /// `//people` matches `/people`.  Off by default.
+    pub fn collapse_slashes(mut self, collapse_slashes: bool) -> CodegenOptions {
+        self.collapse_slashes = collapse_slashes;
+        self
+    }
+
+    /// Resolve `.` and `..` segments in a path before matching it, as
+    /// described in RFC 3986, so `/people/./new` matches `/people/new`.
+    /// Off by default.
+    pub fn resolve_dot_segments(mut self, resolve_dot_segments: bool) -> CodegenOptions {
+        self.resolve_dot_segments = resolve_dot_segments;
+        self
+    }
+
+    /// Match static path segments ignoring ASCII case, so `/People`
+    /// matches `/people`.  Parameters are still parsed from the path as
+    /// given.  Off by default.
+    pub fn ignore_case(mut self, ignore_case: bool) -> CodegenOptions {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Borrow `String` parameters from the matched path and query
+    /// string rather than allocating them.  Their fields are
+    /// `Cow<'a, str>`, which is only owned when the parameter had to be
+    /// decoded, and the structs and `Route` enums holding them take a
+    /// lifetime, tied to the input by `match_route` and the other
+    /// matching functions.  Parameters of other types are still owned.
+    ///
+    /// Since the path must be matched as given, this can't be used with
+    /// [`collapse_slashes`](#method.collapse_slashes) or
+    /// [`resolve_dot_segments`](#method.resolve_dot_segments), nor with
+    /// [`tower`](#method.tower), whose inner service gets the route along
+    /// with the request it came from.  Off by default.
+    pub fn borrowed(mut self, borrowed: bool) -> CodegenOptions {
+        self.borrowed = borrowed;
+        self
+    }
+
+    /// Generate code that only uses `core` and `alloc`, for `no_std`
+    /// crates.  The crate must declare `extern crate alloc;`, and needs
+    /// Rust 1.81 or later, for `core::error::Error`.  Since the `http`
+    /// and `tower` crates need `std`, this can't be used with
+    /// [`http`](#method.http) or [`tower`](#method.tower).  Off by
+    /// default.
+    ///
+    /// There's no mode for targets without `alloc`, even for routers
+    /// whose parameters are all `Copy`.  Parse errors keep the
+    /// offending text as a `String`, percent-decoding writes to a
+    /// buffer, and the `ToPath` and `Urls` helpers build `String`s, so
+    /// each would need a different API first.
+    pub fn no_std(mut self, no_std: bool) -> CodegenOptions {
+        self.no_std = no_std;
+        self
+    }
+
+    /// The crate the generated code uses for items in `core`.
+    pub(crate) fn core(&self) -> &'static str {
+        if self.no_std {
+            "core"
+        } else {
+            "std"
+        }
+    }
+
+    /// The crate the generated code uses for items in `alloc`.
+    pub(crate) fn alloc(&self) -> &'static str {
+        if self.no_std {
+            "alloc"
+        } else {
+            "std"
+        }
+    }
+
+    /// Whether a parameter borrows from the input, rather than being
+    /// parsed into an owned value.
+    pub(crate) fn borrows(&self, param: &Param) -> bool {
+        self.borrowed && param.typ == "String"
+    }
+
+    /// Also generate a `Handlers` trait, with a method for every action,
+    /// and a `dispatch` function that calls the right one for a route.
+    /// The methods are named for the module path and action joined with
+    /// `_`, like `people_show`, so two actions that would get the same
+    /// name, like `People::ShowAll` and `PeopleShow::All`, are an error.
+    /// Off by default.
+    pub fn handlers(mut self, handlers: bool) -> CodegenOptions {
+        self.handlers = handlers;
+        self
+    }
+
+    /// Also generate an `AsyncHandlers` trait, with an async method for
+    /// every action and for unmatched requests, and an async `dispatch`
+    /// function that matches a request and awaits the right one.  It
+    /// reads body parameters, declared like `<name: String>`, from the
+    /// request's form body.  The action methods are named like those of
+    /// [`handlers`](#method.handlers), and a root action named like one
+    /// of the others, such as `NotFound`, is an error.  Since both
+    /// generate `dispatch`, this can't be used with `handlers`.  Off by
+    /// default.
+    pub fn async_handlers(mut self, async_handlers: bool) -> CodegenOptions {
+        self.async_handlers = async_handlers;
+        self
+    }
+
+    /// Also generate helpers for the `http` crate: `match_http_request`
+    /// to match an `http::Request`, and a conversion from `Match` to an
+    /// `http::Response`.  The crate using the generated code must
+    /// depend on `http` 1.x.  Off by default.
+    ///
+    /// Requires the `http` feature.
+    #[cfg(feature = "http")]
+    pub fn http(mut self, http: bool) -> CodegenOptions {
+        self.http = http;
+        self
+    }
+
+    /// Also generate `Router`, a `tower::Service` over `http::Request`s
+    /// that passes each matched route to the service for its action,
+    /// and requests that are not found, not allowed or bad to a service
+    /// for each.  The services for actions are set with methods named
+    /// like those of [`handlers`](#method.handlers), and a root action
+    /// named like one of the others, such as `Routes`, is an error.
+    /// Implies [`http`](#method.http).  The crate using the
+    /// generated code must depend on `http` 1.x and `tower` 0.5, with
+    /// its `util` feature.  Off by default.
+    ///
+    /// Requires the `tower` feature.
+    #[cfg(feature = "tower")]
+    pub fn tower(mut self, tower: bool) -> CodegenOptions {
+        self.tower = tower;
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let mut chars = self.module_name.chars();
+        let valid_name = match chars.next() {
+            Some(c) if c.is_alphabetic() || c == '_' => chars.all(crate::parse::is_identifier_char),
+            _ => false,
+        };
+        if !valid_name || self.module_name == "_" {
+            return Err(format!(
+                "Module name `{}` is not a valid identifier.",
+                self.module_name
+            ));
+        }
+
+        let visibility = self.visibility.replace(' ', "");
+        let valid_visibility = match visibility.as_str() {
+            "" | "pub" | "pub(crate)" | "pub(super)" | "pub(self)" => true,
+            v => v.starts_with("pub(in") && v.ends_with(')'),
+        };
+        if !valid_visibility {
+            return Err(format!("Visibility `{}` is not valid.", self.visibility));
+        }
+
+        if self.handlers && self.async_handlers {
+            return Err("Handlers and async handlers can't both be generated.".to_string());
+        }
+
+        if self.borrowed && (self.collapse_slashes || self.resolve_dot_segments) {
+            return Err("Borrowed parameters can't be used with path normalisation.".to_string());
+        }
+        if self.borrowed && self.tower {
+            return Err("Borrowed parameters can't be used with the tower router.".to_string());
+        }
+        if self.no_std && (self.http || self.tower) {
+            return Err("The http and tower helpers can't be used without std.".to_string());
+        }
+
+        Ok(())
+    }
+}
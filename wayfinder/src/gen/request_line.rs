@@ -0,0 +1,244 @@
+use std::io;
+use std::io::Write;
+
+use crate::gen::options::CodegenOptions;
+
+/// The longest request line that will be matched, in bytes.
+const MAX_REQUEST_LINE_LEN: usize = 8192;
+
+/// The longest method that will be matched, in bytes.
+const MAX_METHOD_LEN: usize = 16;
+
+/// Write `match_request_line`, which parses and routes the first line of
+/// an HTTP/1.x request, such as `GET /people?lang=en HTTP/1.1`.  If
+/// `borrows` is set, the route borrows from the line.
+pub fn write_request_line<W>(w: &mut W, options: &CodegenOptions, borrows: bool) -> io::Result<()>
+where
+    W: Write,
+{
+    let core = options.core();
+    writeln!(
+        w,
+        "    /// The longest request line `match_request_line` accepts, in bytes."
+    )?;
+    writeln!(
+        w,
+        "    pub const MAX_REQUEST_LINE_LEN: usize = {};",
+        MAX_REQUEST_LINE_LEN
+    )?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    /// The longest method `match_request_line` accepts, in bytes."
+    )?;
+    writeln!(
+        w,
+        "    pub const MAX_METHOD_LEN: usize = {};",
+        MAX_METHOD_LEN
+    )?;
+    writeln!(w)?;
+
+    writeln!(w, "    /// A request line that couldn't be matched.")?;
+    writeln!(w, "    #[derive(Debug)]")?;
+    writeln!(w, "    pub enum RequestLineError {{")?;
+    writeln!(
+        w,
+        "        /// The line is longer than `MAX_REQUEST_LINE_LEN`."
+    )?;
+    writeln!(w, "        TooLong,")?;
+    writeln!(
+        w,
+        "        /// The line isn't three parts separated by spaces."
+    )?;
+    writeln!(w, "        Malformed,")?;
+    writeln!(
+        w,
+        "        /// The method is empty, too long, or not a valid token."
+    )?;
+    writeln!(w, "        BadMethod,")?;
+    writeln!(
+        w,
+        "        /// The request target isn't a path with an optional query, or"
+    )?;
+    writeln!(w, "        /// has characters that aren't allowed.")?;
+    writeln!(w, "        BadTarget,")?;
+    writeln!(w, "        /// The version isn't `HTTP/1.0` or `HTTP/1.1`.")?;
+    writeln!(w, "        BadVersion,")?;
+    writeln!(w, "        /// A parameter failed to parse.")?;
+    writeln!(w, "        Param(Error),")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(w, "    impl fmt::Display for RequestLineError {{")?;
+    writeln!(
+        w,
+        "        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{"
+    )?;
+    writeln!(w, "            match *self {{")?;
+    writeln!(
+        w,
+        "                RequestLineError::TooLong => write!(f, \"Request line is longer than {{}} bytes\", MAX_REQUEST_LINE_LEN),"
+    )?;
+    writeln!(
+        w,
+        "                RequestLineError::Malformed => f.write_str(\"Malformed request line\"),"
+    )?;
+    writeln!(
+        w,
+        "                RequestLineError::BadMethod => f.write_str(\"Invalid method in request line\"),"
+    )?;
+    writeln!(
+        w,
+        "                RequestLineError::BadTarget => f.write_str(\"Invalid request target in request line\"),"
+    )?;
+    writeln!(
+        w,
+        "                RequestLineError::BadVersion => f.write_str(\"Unsupported HTTP version in request line\"),"
+    )?;
+    writeln!(
+        w,
+        "                RequestLineError::Param(ref error) => write!(f, \"{{}}\", error),"
+    )?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(w, "    impl {}::error::Error for RequestLineError {{", core)?;
+    writeln!(
+        w,
+        "        fn source(&self) -> Option<&(dyn {}::error::Error + 'static)> {{",
+        core
+    )?;
+    writeln!(w, "            match *self {{")?;
+    writeln!(
+        w,
+        "                RequestLineError::Param(ref error) => Some(error),"
+    )?;
+    writeln!(w, "                _ => None,")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(w, "    fn is_token_char(b: u8) -> bool {{")?;
+    writeln!(
+        w,
+        "        b.is_ascii_alphanumeric() || b\"!#$%&'*+-.^_`|~\".contains(&b)"
+    )?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "    /// Parse the first line of an HTTP/1.x request, such as"
+    )?;
+    writeln!(
+        w,
+        "    /// `GET /people?lang=en HTTP/1.1`, and match it against this router."
+    )?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// The line may end with `\\r\\n`.  The method must be a token of at"
+    )?;
+    writeln!(
+        w,
+        "    /// most `MAX_METHOD_LEN` bytes, the request target must be a path"
+    )?;
+    writeln!(
+        w,
+        "    /// starting with `/` of visible ASCII, and the whole line may be at"
+    )?;
+    writeln!(
+        w,
+        "    /// most `MAX_REQUEST_LINE_LEN` bytes.  Parsing the line doesn't"
+    )?;
+    writeln!(
+        w,
+        "    /// allocate, though parameters may when they're decoded and parsed."
+    )?;
+    if borrows {
+        writeln!(
+            w,
+            "    pub fn match_request_line<L: AsRef<[u8]> + ?Sized>(line: &L) -> Result<Match<Route<'_>>, RequestLineError> {{"
+        )?;
+    } else {
+        writeln!(
+            w,
+            "    pub fn match_request_line<L: AsRef<[u8]>>(line: L) -> Result<Match<Route>, RequestLineError> {{"
+        )?;
+    }
+    writeln!(w, "        let mut line = line.as_ref();")?;
+    writeln!(w, "        if line.ends_with(b\"\\r\\n\") {{")?;
+    writeln!(w, "            line = &line[..line.len() - 2];")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "        if line.len() > MAX_REQUEST_LINE_LEN {{")?;
+    writeln!(w, "            return Err(RequestLineError::TooLong);")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        let method_end = match line.iter().position(|&b| b == b' ') {{"
+    )?;
+    writeln!(w, "            Some(i) => i,")?;
+    writeln!(
+        w,
+        "            None => return Err(RequestLineError::Malformed),"
+    )?;
+    writeln!(w, "        }};")?;
+    writeln!(
+        w,
+        "        let target_end = match line.iter().rposition(|&b| b == b' ') {{"
+    )?;
+    writeln!(w, "            Some(i) if i > method_end => i,")?;
+    writeln!(
+        w,
+        "            _ => return Err(RequestLineError::Malformed),"
+    )?;
+    writeln!(w, "        }};")?;
+    writeln!(w, "        let method = &line[..method_end];")?;
+    writeln!(w, "        let target = &line[method_end + 1..target_end];")?;
+    writeln!(w, "        let version = &line[target_end + 1..];")?;
+    writeln!(w)?;
+    writeln!(w, "        if method.is_empty()")?;
+    writeln!(w, "            || method.len() > MAX_METHOD_LEN")?;
+    writeln!(
+        w,
+        "            || !method.iter().all(|&b| is_token_char(b))"
+    )?;
+    writeln!(w, "        {{")?;
+    writeln!(w, "            return Err(RequestLineError::BadMethod);")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "        if target.first() != Some(&b'/')")?;
+    writeln!(
+        w,
+        "            || !target.iter().all(|&b| b.is_ascii_graphic() && b != b'#')"
+    )?;
+    writeln!(w, "        {{")?;
+    writeln!(w, "            return Err(RequestLineError::BadTarget);")?;
+    writeln!(w, "        }}")?;
+    writeln!(
+        w,
+        "        if version != b\"HTTP/1.1\" && version != b\"HTTP/1.0\" {{"
+    )?;
+    writeln!(w, "            return Err(RequestLineError::BadVersion);")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        let (path, query) = match target.iter().position(|&b| b == b'?') {{"
+    )?;
+    writeln!(
+        w,
+        "            Some(i) => (&target[..i], &target[i + 1..]),"
+    )?;
+    writeln!(w, "            None => (target, &b\"\"[..]),")?;
+    writeln!(w, "        }};")?;
+    writeln!(
+        w,
+        "        match_path(path, query, method).map_err(RequestLineError::Param)"
+    )?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
@@ -0,0 +1,453 @@
+use std::io;
+use std::io::Write;
+
+use crate::gen::flat::FlattenedModules;
+use crate::gen::gen::{to_caps_case, to_snake_case};
+use crate::gen::handlers::handler_name;
+use crate::gen::url::action_type;
+
+/// Write `Router`, a `tower::Service` that matches requests and passes
+/// them along to the service for their action, or to the fallback
+/// service for why they didn't match.
+pub fn write_tower<W>(w: &mut W, modules: &FlattenedModules) -> io::Result<()>
+where
+    W: Write,
+{
+    let actions = modules.root.all_actions();
+
+    writeln!(
+        w,
+        "    /// A request that matched a route, along with the route: the struct"
+    )?;
+    writeln!(w, "    /// for its action, or a whole [`Route`].")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`Route`]: enum.Route.html")?;
+    writeln!(w, "    #[derive(Debug)]")?;
+    writeln!(w, "    pub struct Routed<T, B> {{")?;
+    writeln!(w, "        pub route: T,")?;
+    writeln!(w, "        pub request: http::Request<B>,")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    /// A request whose path matched a route, but not its method."
+    )?;
+    writeln!(w, "    #[derive(Debug)]")?;
+    writeln!(w, "    pub struct NotAllowed<B> {{")?;
+    writeln!(w, "        /// The methods the route does allow.")?;
+    writeln!(w, "        pub methods: &'static [&'static str],")?;
+    writeln!(w, "        pub request: http::Request<B>,")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    /// A request with a parameter that failed to parse."
+    )?;
+    writeln!(w, "    #[derive(Debug)]")?;
+    writeln!(w, "    pub struct BadRequest<B> {{")?;
+    writeln!(w, "        pub error: Error,")?;
+    writeln!(w, "        pub request: http::Request<B>,")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    /// A service of a [`Router`], boxed so that each can be any service."
+    )?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`Router`]: struct.Router.html")?;
+    writeln!(
+        w,
+        "    pub type BoxService<T, R> = tower::util::BoxCloneService<T, http::Response<R>, tower::BoxError>;"
+    )?;
+    writeln!(w)?;
+    writeln!(w, "    /// The response future of a [`Router`].")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`Router`]: struct.Router.html")?;
+    writeln!(
+        w,
+        "    pub type RouterFuture<R> = std::pin::Pin<Box<dyn std::future::Future<Output = Result<http::Response<R>, tower::BoxError>> + Send>>;"
+    )?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "    /// A `tower::Service` that matches requests against this router."
+    )?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// Each action can have a service of its own, set with the method"
+    )?;
+    writeln!(
+        w,
+        "    /// named for its module path and action, such as `people_show`,"
+    )?;
+    writeln!(
+        w,
+        "    /// which gets the action's struct in a [`Routed`].  Actions without"
+    )?;
+    writeln!(
+        w,
+        "    /// one go to the `routes` service with the whole [`Route`], or if"
+    )?;
+    writeln!(w, "    /// that isn't set either, they're not found.")?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// Redirects are answered with `301 Moved Permanently`.  Requests"
+    )?;
+    writeln!(
+        w,
+        "    /// that don't match go to the `not_found`, `not_allowed` and"
+    )?;
+    writeln!(
+        w,
+        "    /// `bad_request` services, which respond `404 Not Found`, `405 Method"
+    )?;
+    writeln!(
+        w,
+        "    /// Not Allowed` with the `Allow` header, and `400 Bad Request`, with"
+    )?;
+    writeln!(w, "    /// an empty body, unless they're replaced.")?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// The services are cloned for each request, so the router is"
+    )?;
+    writeln!(w, "    /// always ready.")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`Routed`]: struct.Routed.html")?;
+    writeln!(w, "    /// [`Route`]: enum.Route.html")?;
+    writeln!(w, "    pub struct Router<B, R> {{")?;
+    writeln!(
+        w,
+        "        routes: Option<BoxService<Routed<Route, B>, R>>,"
+    )?;
+    for (path, action) in actions.iter() {
+        writeln!(
+            w,
+            "        {}: Option<BoxService<Routed<{}, B>, R>>,",
+            handler_name(path, &action.name),
+            action_type(path, action)
+        )?;
+    }
+    writeln!(w, "        not_found: BoxService<http::Request<B>, R>,")?;
+    writeln!(w, "        not_allowed: BoxService<NotAllowed<B>, R>,")?;
+    writeln!(w, "        bad_request: BoxService<BadRequest<B>, R>,")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(w, "    impl<B, R> Clone for Router<B, R> {{")?;
+    writeln!(w, "        fn clone(&self) -> Router<B, R> {{")?;
+    writeln!(w, "            Router {{")?;
+    writeln!(w, "                routes: self.routes.clone(),")?;
+    for (path, action) in actions.iter() {
+        let name = handler_name(path, &action.name);
+        writeln!(w, "                {}: self.{}.clone(),", name, name)?;
+    }
+    writeln!(w, "                not_found: self.not_found.clone(),")?;
+    writeln!(w, "                not_allowed: self.not_allowed.clone(),")?;
+    writeln!(w, "                bad_request: self.bad_request.clone(),")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(w, "    impl<B, R> std::fmt::Debug for Router<B, R> {{")?;
+    writeln!(
+        w,
+        "        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {{"
+    )?;
+    writeln!(
+        w,
+        "            f.debug_struct(\"Router\").finish_non_exhaustive()"
+    )?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(w, "    impl<B, R> Default for Router<B, R>")?;
+    writeln!(w, "    where")?;
+    writeln!(w, "        B: Send + 'static,")?;
+    writeln!(w, "        R: Default + Send + 'static,")?;
+    writeln!(w, "    {{")?;
+    writeln!(
+        w,
+        "        /// A router without any services of its own, so every route is"
+    )?;
+    writeln!(w, "        /// not found.")?;
+    writeln!(w, "        fn default() -> Router<B, R> {{")?;
+    writeln!(w, "            Router {{")?;
+    writeln!(w, "                routes: None,")?;
+    for (path, action) in actions.iter() {
+        writeln!(
+            w,
+            "                {}: None,",
+            handler_name(path, &action.name)
+        )?;
+    }
+    writeln!(
+        w,
+        "                not_found: box_service(tower::service_fn(|_: http::Request<B>| {{"
+    )?;
+    writeln!(
+        w,
+        "                    respond(http::Response::from(Match::<Route>::NotFound))"
+    )?;
+    writeln!(w, "                }})),")?;
+    writeln!(
+        w,
+        "                not_allowed: box_service(tower::service_fn(|not_allowed: NotAllowed<B>| {{"
+    )?;
+    writeln!(
+        w,
+        "                    respond(http::Response::from(Match::<Route>::NotAllowed(not_allowed.methods)))"
+    )?;
+    writeln!(w, "                }})),")?;
+    writeln!(
+        w,
+        "                bad_request: box_service(tower::service_fn(|_: BadRequest<B>| {{"
+    )?;
+    writeln!(
+        w,
+        "                    let mut response = http::Response::new(());"
+    )?;
+    writeln!(
+        w,
+        "                    *response.status_mut() = http::StatusCode::BAD_REQUEST;"
+    )?;
+    writeln!(w, "                    respond(response)")?;
+    writeln!(w, "                }})),")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(w, "    impl<B, R> Router<B, R>")?;
+    writeln!(w, "    where")?;
+    writeln!(w, "        B: Send + 'static,")?;
+    writeln!(w, "        R: Default + Send + 'static,")?;
+    writeln!(w, "    {{")?;
+    let mut setters = vec![(
+        "routes".to_string(),
+        "Routed<Route, B>".to_string(),
+        "Pass matched routes without a service of their own to `service`.".to_string(),
+        true,
+    )];
+    for (path, action) in actions.iter() {
+        let typ = action_type(path, action);
+        setters.push((
+            handler_name(path, &action.name),
+            format!("Routed<{}, B>", typ),
+            format!("Pass requests for `{}` to `service`.", typ),
+            true,
+        ));
+    }
+    setters.push((
+        "not_found".to_string(),
+        "http::Request<B>".to_string(),
+        "Pass requests that don't match a route to `service`.".to_string(),
+        false,
+    ));
+    setters.push((
+        "not_allowed".to_string(),
+        "NotAllowed<B>".to_string(),
+        "Pass requests with a method the route doesn't allow to `service`.".to_string(),
+        false,
+    ));
+    setters.push((
+        "bad_request".to_string(),
+        "BadRequest<B>".to_string(),
+        "Pass requests with a parameter that fails to parse to `service`.".to_string(),
+        false,
+    ));
+    for (i, (name, request, doc, optional)) in setters.iter().enumerate() {
+        if i > 0 {
+            writeln!(w)?;
+        }
+        write_setter(w, name, request, doc, *optional)?;
+    }
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "    impl<B, R> tower::Service<http::Request<B>> for Router<B, R>"
+    )?;
+    writeln!(w, "    where")?;
+    writeln!(w, "        B: Send + 'static,")?;
+    writeln!(w, "        R: Default + Send + 'static,")?;
+    writeln!(w, "    {{")?;
+    writeln!(w, "        type Response = http::Response<R>;")?;
+    writeln!(w, "        type Error = tower::BoxError;")?;
+    writeln!(w, "        type Future = RouterFuture<R>;")?;
+    writeln!(w)?;
+    writeln!(w, "        fn poll_ready(")?;
+    writeln!(w, "            &mut self,")?;
+    writeln!(w, "            _: &mut std::task::Context<'_>,")?;
+    writeln!(
+        w,
+        "        ) -> std::task::Poll<Result<(), tower::BoxError>> {{"
+    )?;
+    writeln!(w, "            std::task::Poll::Ready(Ok(()))")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        fn call(&mut self, request: http::Request<B>) -> RouterFuture<R> {{"
+    )?;
+    writeln!(
+        w,
+        "            let route = match match_http_request(&request) {{"
+    )?;
+    writeln!(w, "                Ok(Match::Route(route)) => route,")?;
+    writeln!(w, "                Ok(Match::Redirect(route)) => {{")?;
+    writeln!(
+        w,
+        "                    return Box::pin(respond(http::Response::from(Match::Redirect(route))));"
+    )?;
+    writeln!(w, "                }}")?;
+    writeln!(
+        w,
+        "                Ok(Match::NotFound) => return oneshot(&self.not_found, request),"
+    )?;
+    writeln!(w, "                Ok(Match::NotAllowed(methods)) => {{")?;
+    writeln!(
+        w,
+        "                    return oneshot(&self.not_allowed, NotAllowed {{ methods, request }});"
+    )?;
+    writeln!(w, "                }}")?;
+    writeln!(w, "                Err(error) => {{")?;
+    writeln!(
+        w,
+        "                    return oneshot(&self.bad_request, BadRequest {{ error, request }});"
+    )?;
+    writeln!(w, "                }}")?;
+    writeln!(w, "            }};")?;
+    if !actions.is_empty() {
+        writeln!(w, "            let route = match route {{")?;
+        for (path, action) in actions.iter() {
+            let mut pattern = String::new();
+            let mut prefix = String::new();
+            let mut parens = String::new();
+            for module in path.iter() {
+                pattern.push_str(&format!("{}Route::{}(", prefix, to_caps_case(module)));
+                prefix.push_str(&format!("{}::", to_snake_case(module)));
+                parens.push(')');
+            }
+            writeln!(
+                w,
+                "                {}{}Route::{}(route){} => match self.{} {{",
+                pattern,
+                prefix,
+                to_caps_case(&action.name),
+                parens,
+                handler_name(path, &action.name)
+            )?;
+            writeln!(
+                w,
+                "                    Some(ref service) => return oneshot(service, Routed {{ route, request }}),"
+            )?;
+            writeln!(w, "                    None => Route::from(route),")?;
+            writeln!(w, "                }},")?;
+        }
+        writeln!(w, "            }};")?;
+    }
+    writeln!(w, "            match self.routes {{")?;
+    writeln!(
+        w,
+        "                Some(ref service) => oneshot(service, Routed {{ route, request }}),"
+    )?;
+    writeln!(
+        w,
+        "                None => oneshot(&self.not_found, request),"
+    )?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "    fn box_service<T, R, S>(service: S) -> BoxService<T, R>"
+    )?;
+    writeln!(w, "    where")?;
+    writeln!(
+        w,
+        "        S: tower::Service<T, Response = http::Response<R>> + Clone + Send + 'static,"
+    )?;
+    writeln!(w, "        S::Error: Into<tower::BoxError>,")?;
+    writeln!(w, "        S::Future: Send + 'static,")?;
+    writeln!(w, "    {{")?;
+    writeln!(
+        w,
+        "        let service = tower::ServiceExt::map_err(service, |error: S::Error| error.into());"
+    )?;
+    writeln!(w, "        tower::util::BoxCloneService::new(service)")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    fn oneshot<T, R>(service: &BoxService<T, R>, request: T) -> RouterFuture<R>"
+    )?;
+    writeln!(w, "    where")?;
+    writeln!(w, "        T: Send + 'static,")?;
+    writeln!(w, "        R: Send + 'static,")?;
+    writeln!(w, "    {{")?;
+    writeln!(
+        w,
+        "        Box::pin(tower::ServiceExt::oneshot(service.clone(), request))"
+    )?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    fn respond<R: Default>(response: http::Response<()>) -> std::future::Ready<Result<http::Response<R>, tower::BoxError>> {{"
+    )?;
+    writeln!(
+        w,
+        "        std::future::ready(Ok(response.map(|()| R::default())))"
+    )?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+/// Write a `Router` method setting one of its services, which is an
+/// `Option` if `optional` is set.
+fn write_setter<W>(
+    w: &mut W,
+    name: &str,
+    request: &str,
+    doc: &str,
+    optional: bool,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(w, "        /// {}", doc)?;
+    writeln!(
+        w,
+        "        pub fn {}<S>(mut self, service: S) -> Router<B, R>",
+        name
+    )?;
+    writeln!(w, "        where")?;
+    writeln!(
+        w,
+        "            S: tower::Service<{}, Response = http::Response<R>> + Clone + Send + 'static,",
+        request
+    )?;
+    writeln!(w, "            S::Error: Into<tower::BoxError>,")?;
+    writeln!(w, "            S::Future: Send + 'static,")?;
+    writeln!(w, "        {{")?;
+    if optional {
+        writeln!(w, "            self.{} = Some(box_service(service));", name)?;
+    } else {
+        writeln!(w, "            self.{} = box_service(service);", name)?;
+    }
+    writeln!(w, "            self")?;
+    writeln!(w, "        }}")?;
+
+    Ok(())
+}
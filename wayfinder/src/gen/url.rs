@@ -0,0 +1,1000 @@
+use std::io;
+use std::io::Write;
+
+use crate::core::{Param, PathSegment, Routes};
+use crate::gen::error::{ErrorVariants, NOT_UTF8};
+use crate::gen::flat::{FlattenedAction, FlattenedModules};
+use crate::gen::gen::{module_borrows, to_caps_case, to_snake_case};
+use crate::gen::options::CodegenOptions;
+
+/// Write the private helpers the generated code uses to percent-encode
+/// paths and query strings, and to decode them when matching.  Only the
+/// helpers these routes need are written.
+pub fn write_url_helpers<W>(w: &mut W, routes: &Routes, options: &CodegenOptions) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut path = vec![];
+    let mut query = vec![];
+    let mut body = vec![];
+    collect_params(routes, &mut path, &mut query, &mut body);
+    // Query and body parameters are read the same way, but body
+    // parameters are only read by the async `dispatch`.
+    let mut form = query.clone();
+    if options.async_handlers {
+        form.extend(body);
+    }
+    let parsed = |params: &[&Param]| params.iter().any(|param| !options.borrows(param));
+    let borrowed = |params: &[&Param]| params.iter().any(|param| options.borrows(param));
+
+    write_encode(w, options)?;
+    if !query.is_empty() {
+        write_query(w)?;
+    }
+    if !path.is_empty() || !form.is_empty() {
+        write_decode(w, options)?;
+    }
+    if parsed(&path) {
+        write_parse_path(w, options)?;
+    }
+    if borrowed(&path) {
+        write_borrow_path(w, options)?;
+    }
+    if !form.is_empty() {
+        write_not_utf8(w)?;
+    }
+    if parsed(&form) {
+        write_parse_query(w, options)?;
+    }
+    if borrowed(&form) {
+        write_borrow_query(w, options)?;
+    }
+
+    Ok(())
+}
+
+/// Write the `Urls` type, which makes links to routes, relative to the
+/// mount prefix of the app or absolute with its base URL.
+pub fn write_urls<W>(w: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(w, "    /// Makes links to routes.")?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// Configure it once with the base URL of the app and the prefix"
+    )?;
+    writeln!(
+        w,
+        "    /// it's mounted at, if any, then use it to link to any route."
+    )?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// ```ignore")?;
+    writeln!(w, "    /// let urls = Urls::new()")?;
+    writeln!(w, "    ///     .base(\"https://example.com\")")?;
+    writeln!(w, "    ///     .mount(\"/app\");")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// urls.link_to(&route, None); // /app/people")?;
+    writeln!(
+        w,
+        "    /// urls.url_for(&route, Some(\"top\")); // https://example.com/app/people#top"
+    )?;
+    writeln!(w, "    /// ```")?;
+    writeln!(w, "    #[derive(Debug, Clone, Default, PartialEq, Eq)]")?;
+    writeln!(w, "    pub struct Urls {{")?;
+    writeln!(w, "        base: String,")?;
+    writeln!(w, "        mount: String,")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(w, "    impl Urls {{")?;
+    writeln!(
+        w,
+        "        /// Links with no base URL or mount prefix, that is, just paths."
+    )?;
+    writeln!(w, "        pub fn new() -> Urls {{")?;
+    writeln!(w, "            Urls::default()")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        /// Set the scheme and host for absolute URLs, such as"
+    )?;
+    writeln!(w, "        /// `https://example.com`.")?;
+    writeln!(
+        w,
+        "        pub fn base<S: Into<String>>(mut self, base: S) -> Urls {{"
+    )?;
+    writeln!(w, "            let mut base = base.into();")?;
+    writeln!(w, "            while base.ends_with('/') {{")?;
+    writeln!(w, "                base.pop();")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "            self.base = base;")?;
+    writeln!(w, "            self")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        /// Set the prefix the app is mounted at, such as `/app`.  It's"
+    )?;
+    writeln!(w, "        /// added to the start of every link.")?;
+    writeln!(
+        w,
+        "        pub fn mount<S: AsRef<str>>(mut self, prefix: S) -> Urls {{"
+    )?;
+    writeln!(
+        w,
+        "            let prefix = prefix.as_ref().trim_matches('/');"
+    )?;
+    writeln!(w, "            self.mount = if prefix.is_empty() {{")?;
+    writeln!(w, "                String::new()")?;
+    writeln!(w, "            }} else {{")?;
+    writeln!(w, "                format!(\"/{{}}\", prefix)")?;
+    writeln!(w, "            }};")?;
+    writeln!(w, "            self")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        /// Write a link to the route, relative to the host, with an"
+    )?;
+    writeln!(w, "        /// optional fragment.")?;
+    writeln!(w, "        pub fn write_link<T: ToPath, W: fmt::Write>(")?;
+    writeln!(w, "            &self,")?;
+    writeln!(w, "            w: &mut W,")?;
+    writeln!(w, "            route: &T,")?;
+    writeln!(w, "            fragment: Option<&str>,")?;
+    writeln!(w, "        ) -> fmt::Result {{")?;
+    writeln!(w, "            w.write_str(&self.mount)?;")?;
+    writeln!(w, "            route.write_path(w)?;")?;
+    writeln!(w, "            if let Some(fragment) = fragment {{")?;
+    writeln!(w, "                w.write_char('#')?;")?;
+    writeln!(w, "                write_encoded(w, &fragment)?;")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "            Ok(())")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        /// Make a link to the route, relative to the host, such as"
+    )?;
+    writeln!(w, "        /// `/app/people/new`.")?;
+    writeln!(
+        w,
+        "        pub fn link_to<T: ToPath>(&self, route: &T, fragment: Option<&str>) -> String {{"
+    )?;
+    writeln!(w, "            let mut link = String::new();")?;
+    writeln!(w, "            self.write_link(&mut link, route, fragment)")?;
+    writeln!(
+        w,
+        "                .expect(\"writing to a String can't fail\");"
+    )?;
+    writeln!(w, "            link")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(w, "        /// Make an absolute URL for the route, such as")?;
+    writeln!(
+        w,
+        "        /// `https://example.com/app/people/new`.  Without a base URL"
+    )?;
+    writeln!(w, "        /// this is the same as `link_to`.")?;
+    writeln!(
+        w,
+        "        pub fn url_for<T: ToPath>(&self, route: &T, fragment: Option<&str>) -> String {{"
+    )?;
+    writeln!(w, "            let mut url = self.base.clone();")?;
+    writeln!(w, "            self.write_link(&mut url, route, fragment)")?;
+    writeln!(
+        w,
+        "                .expect(\"writing to a String can't fail\");"
+    )?;
+    writeln!(w, "            url")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+/// Collect every path and query parameter of these routes.
+fn collect_params<'a>(
+    routes: &'a Routes,
+    path: &mut Vec<&'a Param>,
+    query: &mut Vec<&'a Param>,
+    body: &mut Vec<&'a Param>,
+) {
+    query.extend(routes.query_parameters.iter());
+    for resource in routes.resources.iter() {
+        query.extend(resource.query_parameters.iter());
+        body.extend(resource.body_parameters.iter());
+    }
+    for child in routes.routes.iter() {
+        if let PathSegment::Dynamic(ref param) = child.path_segment {
+            path.push(param);
+        }
+        collect_params(&child.routes, path, query, body);
+    }
+}
+
+fn write_encode<W>(w: &mut W, options: &CodegenOptions) -> io::Result<()>
+where
+    W: Write,
+{
+    let core = options.core();
+    writeln!(
+        w,
+        "    /// Percent-encodes everything written to it except unreserved"
+    )?;
+    writeln!(
+        w,
+        "    /// characters, so it's safe in a path segment or query."
+    )?;
+    writeln!(w, "    struct Encode<'a, W: fmt::Write>(&'a mut W);")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    impl<'a, W: fmt::Write> fmt::Write for Encode<'a, W> {{"
+    )?;
+    writeln!(
+        w,
+        "        fn write_str(&mut self, s: &str) -> fmt::Result {{"
+    )?;
+    writeln!(w, "            for &b in s.as_bytes() {{")?;
+    writeln!(
+        w,
+        "                if b.is_ascii_alphanumeric() || b\"-._~\".contains(&b) {{"
+    )?;
+    writeln!(w, "                    self.0.write_char(b as char)?;")?;
+    writeln!(w, "                }} else {{")?;
+    writeln!(w, "                    write!(self.0, \"%{{:02X}}\", b)?;")?;
+    writeln!(w, "                }}")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "            Ok(())")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    fn write_encoded<W: fmt::Write, T: fmt::Display>(w: &mut W, value: &T) -> fmt::Result {{"
+    )?;
+    writeln!(w, "        use {}::fmt::Write;", core)?;
+    writeln!(w, "        write!(Encode(w), \"{{}}\", value)")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+fn write_query<W>(w: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(w, "    fn write_query<W: fmt::Write, T: fmt::Display>(")?;
+    writeln!(w, "        w: &mut W,")?;
+    writeln!(w, "        first: &mut bool,")?;
+    writeln!(w, "        name: &str,")?;
+    writeln!(w, "        value: &Option<T>,")?;
+    writeln!(w, "        raw: bool,")?;
+    writeln!(w, "    ) -> fmt::Result {{")?;
+    writeln!(w, "        if let Some(ref value) = *value {{")?;
+    writeln!(
+        w,
+        "            w.write_char(if *first {{ '?' }} else {{ '&' }})?;"
+    )?;
+    writeln!(w, "            *first = false;")?;
+    writeln!(w, "            w.write_str(name)?;")?;
+    writeln!(w, "            w.write_char('=')?;")?;
+    writeln!(w, "            if raw {{")?;
+    writeln!(w, "                write!(w, \"{{}}\", value)?;")?;
+    writeln!(w, "            }} else {{")?;
+    writeln!(w, "                write_encoded(w, value)?;")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "        Ok(())")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+fn write_decode<W>(w: &mut W, options: &CodegenOptions) -> io::Result<()>
+where
+    W: Write,
+{
+    let alloc = options.alloc();
+    writeln!(w, "    fn from_hex(b: u8) -> Option<u8> {{")?;
+    writeln!(w, "        match b {{")?;
+    writeln!(w, "            b'0'..=b'9' => Some(b - b'0'),")?;
+    writeln!(w, "            b'a'..=b'f' => Some(b - b'a' + 10),")?;
+    writeln!(w, "            b'A'..=b'F' => Some(b - b'A' + 10),")?;
+    writeln!(w, "            _ => None,")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "    /// Decode percent-escapes, and in a query string, `+` as a space."
+    )?;
+    writeln!(
+        w,
+        "    /// Invalid escapes are left as they are.  Only allocates if there"
+    )?;
+    writeln!(w, "    /// is something to decode.")?;
+    writeln!(
+        w,
+        "    fn decode(bytes: &[u8], is_query: bool) -> {}::borrow::Cow<'_, [u8]> {{",
+        alloc
+    )?;
+    writeln!(
+        w,
+        "        if !bytes.iter().any(|&b| b == b'%' || (is_query && b == b'+')) {{"
+    )?;
+    writeln!(
+        w,
+        "            return {}::borrow::Cow::Borrowed(bytes);",
+        alloc
+    )?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "        let mut decoded = Vec::with_capacity(bytes.len());"
+    )?;
+    writeln!(w, "        let mut i = 0;")?;
+    writeln!(w, "        while i < bytes.len() {{")?;
+    writeln!(w, "            match bytes[i] {{")?;
+    writeln!(w, "                b'%' if i + 2 < bytes.len() => {{")?;
+    writeln!(
+        w,
+        "                    match (from_hex(bytes[i + 1]), from_hex(bytes[i + 2])) {{"
+    )?;
+    writeln!(w, "                        (Some(hi), Some(lo)) => {{")?;
+    writeln!(w, "                            decoded.push(hi * 16 + lo);")?;
+    writeln!(w, "                            i += 3;")?;
+    writeln!(w, "                            continue;")?;
+    writeln!(w, "                        }}")?;
+    writeln!(w, "                        _ => decoded.push(b'%'),")?;
+    writeln!(w, "                    }}")?;
+    writeln!(w, "                }}")?;
+    writeln!(w, "                b'+' if is_query => decoded.push(b' '),")?;
+    writeln!(w, "                b => decoded.push(b),")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "            i += 1;")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "        {}::borrow::Cow::Owned(decoded)", alloc)?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+fn write_parse_path<W>(w: &mut W, options: &CodegenOptions) -> io::Result<()>
+where
+    W: Write,
+{
+    let core = options.core();
+    let alloc = options.alloc();
+    writeln!(
+        w,
+        "    /// Parse a path parameter, decoding it unless it's raw.  If it isn't"
+    )?;
+    writeln!(
+        w,
+        "    /// valid UTF-8 it's not a match, otherwise the error has the text."
+    )?;
+    writeln!(w, "    fn parse_path<T: {}::str::FromStr>(", core)?;
+    writeln!(w, "        text: &[u8],")?;
+    writeln!(w, "        raw: bool,")?;
+    writeln!(w, "    ) -> Option<Result<T, (String, T::Err)>> {{")?;
+    writeln!(w, "        let text = if raw {{")?;
+    writeln!(w, "            {}::borrow::Cow::Borrowed(text)", alloc)?;
+    writeln!(w, "        }} else {{")?;
+    writeln!(w, "            decode(text, false)")?;
+    writeln!(w, "        }};")?;
+    writeln!(
+        w,
+        "        let text = {}::str::from_utf8(&text).ok()?;",
+        core
+    )?;
+    writeln!(
+        w,
+        "        Some(text.parse().map_err(|e| (text.to_string(), e)))"
+    )?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+fn write_borrow_path<W>(w: &mut W, options: &CodegenOptions) -> io::Result<()>
+where
+    W: Write,
+{
+    let core = options.core();
+    let alloc = options.alloc();
+    writeln!(
+        w,
+        "    /// Borrow a path parameter, decoding it unless it's raw, in which"
+    )?;
+    writeln!(
+        w,
+        "    /// case it's only owned if it had to be.  If it isn't valid UTF-8"
+    )?;
+    writeln!(w, "    /// it's not a match.")?;
+    writeln!(
+        w,
+        "    fn borrow_path(text: &[u8], raw: bool) -> Option<{}::borrow::Cow<'_, str>> {{",
+        alloc
+    )?;
+    writeln!(w, "        let text = if raw {{")?;
+    writeln!(w, "            {}::borrow::Cow::Borrowed(text)", alloc)?;
+    writeln!(w, "        }} else {{")?;
+    writeln!(w, "            decode(text, false)")?;
+    writeln!(w, "        }};")?;
+    writeln!(w, "        match text {{")?;
+    writeln!(
+        w,
+        "            {}::borrow::Cow::Borrowed(text) => {}::str::from_utf8(text)",
+        alloc, core
+    )?;
+    writeln!(w, "                .ok()")?;
+    writeln!(w, "                .map({}::borrow::Cow::Borrowed),", alloc)?;
+    writeln!(
+        w,
+        "            {}::borrow::Cow::Owned(text) => String::from_utf8(text)",
+        alloc
+    )?;
+    writeln!(w, "                .ok()")?;
+    writeln!(w, "                .map({}::borrow::Cow::Owned),", alloc)?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+fn write_not_utf8<W>(w: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(
+        w,
+        "    /// The error for a query or body parameter that isn't valid UTF-8."
+    )?;
+    writeln!(
+        w,
+        "    fn not_utf8(param: &'static str, kind: ParamKind, text: &[u8]) -> Error {{"
+    )?;
+    writeln!(w, "        Error::{} {{", NOT_UTF8)?;
+    writeln!(w, "            param,")?;
+    writeln!(w, "            kind,")?;
+    writeln!(w, "            text: String::from_utf8_lossy(text).into_owned(),")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+fn write_borrow_query<W>(w: &mut W, options: &CodegenOptions) -> io::Result<()>
+where
+    W: Write,
+{
+    let core = options.core();
+    let alloc = options.alloc();
+    writeln!(
+        w,
+        "    /// Find and borrow the first value for the named query parameter,"
+    )?;
+    writeln!(
+        w,
+        "    /// decoding it unless it's raw, in which case it's only owned if it"
+    )?;
+    writeln!(
+        w,
+        "    /// had to be.  If it isn't valid UTF-8, the error has the text."
+    )?;
+    writeln!(w, "    fn borrow_query<'a>(")?;
+    writeln!(w, "        query: &'a [u8],")?;
+    writeln!(w, "        name: &'static str,")?;
+    writeln!(w, "        kind: ParamKind,")?;
+    writeln!(w, "        raw: bool,")?;
+    writeln!(
+        w,
+        "    ) -> Result<Option<{}::borrow::Cow<'a, str>>, Error> {{",
+        alloc
+    )?;
+    writeln!(w, "        for pair in query.split(|&b| b == b'&') {{")?;
+    writeln!(
+        w,
+        "            let (key, value) = match pair.iter().position(|&b| b == b'=') {{"
+    )?;
+    writeln!(
+        w,
+        "                Some(i) => (&pair[..i], &pair[i + 1..]),"
+    )?;
+    writeln!(w, "                None => (pair, &b\"\"[..]),")?;
+    writeln!(w, "            }};")?;
+    writeln!(
+        w,
+        "            if &*decode(key, true) != name.as_bytes() {{"
+    )?;
+    writeln!(w, "                continue;")?;
+    writeln!(w, "            }}")?;
+    writeln!(w)?;
+    writeln!(w, "            let value = if raw {{")?;
+    writeln!(w, "                {}::borrow::Cow::Borrowed(value)", alloc)?;
+    writeln!(w, "            }} else {{")?;
+    writeln!(w, "                decode(value, true)")?;
+    writeln!(w, "            }};")?;
+    writeln!(w, "            return match value {{")?;
+    writeln!(
+        w,
+        "                {}::borrow::Cow::Borrowed(value) => match {}::str::from_utf8(value) {{",
+        alloc, core
+    )?;
+    writeln!(
+        w,
+        "                    Ok(text) => Ok(Some({}::borrow::Cow::Borrowed(text))),",
+        alloc
+    )?;
+    writeln!(
+        w,
+        "                    Err(_) => Err(not_utf8(name, kind, value)),"
+    )?;
+    writeln!(w, "                }},")?;
+    writeln!(
+        w,
+        "                {}::borrow::Cow::Owned(value) => match String::from_utf8(value) {{",
+        alloc
+    )?;
+    writeln!(
+        w,
+        "                    Ok(text) => Ok(Some({}::borrow::Cow::Owned(text))),",
+        alloc
+    )?;
+    writeln!(
+        w,
+        "                    Err(error) => Err(not_utf8(name, kind, error.as_bytes())),"
+    )?;
+    writeln!(w, "                }},")?;
+    writeln!(w, "            }};")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(w, "        Ok(None)")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+fn write_parse_query<W>(w: &mut W, options: &CodegenOptions) -> io::Result<()>
+where
+    W: Write,
+{
+    let core = options.core();
+    let alloc = options.alloc();
+    writeln!(
+        w,
+        "    /// Find and parse the first value for the named query parameter,"
+    )?;
+    writeln!(
+        w,
+        "    /// decoding it unless it's raw.  If it isn't valid UTF-8, or doesn't"
+    )?;
+    writeln!(w, "    /// parse, the error has the text.")?;
+    writeln!(w, "    fn parse_query<T: {}::str::FromStr>(", core)?;
+    writeln!(w, "        query: &[u8],")?;
+    writeln!(w, "        name: &'static str,")?;
+    writeln!(w, "        kind: ParamKind,")?;
+    writeln!(w, "        raw: bool,")?;
+    writeln!(w, "        error: fn(ParamKind, String, T::Err) -> Error,")?;
+    writeln!(w, "    ) -> Result<Option<T>, Error> {{")?;
+    writeln!(w, "        if query.is_empty() {{")?;
+    writeln!(w, "            return Ok(None);")?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(w, "        for pair in query.split(|&b| b == b'&') {{")?;
+    writeln!(
+        w,
+        "            let (key, value) = match pair.iter().position(|&b| b == b'=') {{"
+    )?;
+    writeln!(
+        w,
+        "                Some(i) => (&pair[..i], &pair[i + 1..]),"
+    )?;
+    writeln!(w, "                None => (pair, &b\"\"[..]),")?;
+    writeln!(w, "            }};")?;
+    writeln!(
+        w,
+        "            if &*decode(key, true) != name.as_bytes() {{"
+    )?;
+    writeln!(w, "                continue;")?;
+    writeln!(w, "            }}")?;
+    writeln!(w)?;
+    writeln!(w, "            let value = if raw {{")?;
+    writeln!(w, "                {}::borrow::Cow::Borrowed(value)", alloc)?;
+    writeln!(w, "            }} else {{")?;
+    writeln!(w, "                decode(value, true)")?;
+    writeln!(w, "            }};")?;
+    writeln!(
+        w,
+        "            let text = match {}::str::from_utf8(&value) {{",
+        core
+    )?;
+    writeln!(w, "                Ok(text) => text,")?;
+    writeln!(
+        w,
+        "                Err(_) => return Err(not_utf8(name, kind, &value)),"
+    )?;
+    writeln!(w, "            }};")?;
+    writeln!(w, "            return text")?;
+    writeln!(w, "                .parse()")?;
+    writeln!(w, "                .map(Some)")?;
+    writeln!(
+        w,
+        "                .map_err(|e| error(kind, text.to_string(), e));"
+    )?;
+    writeln!(w, "        }}")?;
+    writeln!(w)?;
+    writeln!(w, "        Ok(None)")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+/// Write `route_for` and `url_for`, which find an action by a name such
+/// as `people.show` and fill it in from string parameters.
+pub fn write_url_for<W>(
+    w: &mut W,
+    modules: &FlattenedModules,
+    errors: &ErrorVariants,
+    options: &CodegenOptions,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let core = options.core();
+    let alloc = options.alloc();
+    let actions = modules
+        .root
+        .all_actions()
+        .into_iter()
+        .map(|(path, action)| {
+            (
+                action_name(&path, action),
+                action_type(&path, action),
+                action,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    writeln!(
+        w,
+        "    /// A problem finding a route by name or filling in its parameters."
+    )?;
+    writeln!(w, "    #[derive(Debug)]")?;
+    writeln!(w, "    pub enum UrlError {{")?;
+    writeln!(w, "        /// There is no route with this name.")?;
+    writeln!(w, "        UnknownRoute(String),")?;
+    writeln!(
+        w,
+        "        /// The route needs a parameter that wasn't given."
+    )?;
+    writeln!(w, "        MissingParam {{")?;
+    writeln!(w, "            route: &'static str,")?;
+    writeln!(w, "            param: &'static str,")?;
+    writeln!(w, "        }},")?;
+    writeln!(
+        w,
+        "        /// A parameter was given that the route doesn't take."
+    )?;
+    writeln!(w, "        UnknownParam {{")?;
+    writeln!(w, "            route: &'static str,")?;
+    writeln!(w, "            param: String,")?;
+    writeln!(w, "        }},")?;
+    writeln!(w, "        /// A parameter was given that failed to parse.")?;
+    writeln!(w, "        Param(Error),")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(w, "    impl fmt::Display for UrlError {{")?;
+    writeln!(
+        w,
+        "        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{"
+    )?;
+    writeln!(w, "            match *self {{")?;
+    writeln!(
+        w,
+        "                UrlError::UnknownRoute(ref name) => write!(f, \"Unknown route '{{}}'\", name),"
+    )?;
+    writeln!(
+        w,
+        "                UrlError::MissingParam {{ route, param }} => {{"
+    )?;
+    writeln!(
+        w,
+        "                    write!(f, \"Missing parameter '{{}}' for route '{{}}'\", param, route)"
+    )?;
+    writeln!(w, "                }}")?;
+    writeln!(
+        w,
+        "                UrlError::UnknownParam {{ route, ref param }} => {{"
+    )?;
+    writeln!(
+        w,
+        "                    write!(f, \"Unknown parameter '{{}}' for route '{{}}'\", param, route)"
+    )?;
+    writeln!(w, "                }}")?;
+    writeln!(
+        w,
+        "                UrlError::Param(ref error) => write!(f, \"{{}}\", error),"
+    )?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(w, "    impl {}::error::Error for UrlError {{", core)?;
+    writeln!(
+        w,
+        "        fn source(&self) -> Option<&(dyn {}::error::Error + 'static)> {{",
+        core
+    )?;
+    writeln!(w, "            match *self {{")?;
+    writeln!(
+        w,
+        "                UrlError::Param(ref error) => Some(error),"
+    )?;
+    writeln!(w, "                _ => None,")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "    /// Find the action named `name` and fill it in from `params`."
+    )?;
+    writeln!(w, "    ///")?;
+    writeln!(
+        w,
+        "    /// Actions are named by their module path and action name in"
+    )?;
+    writeln!(
+        w,
+        "    /// snake case, separated by dots, such as `people.show`.  Every"
+    )?;
+    writeln!(
+        w,
+        "    /// path parameter of the action must be given, query parameters are"
+    )?;
+    writeln!(w, "    /// optional, and anything else is an error.  Body parameters aren't")?;
+    writeln!(w, "    /// part of a URL, so they're left `None`.")?;
+    if module_borrows(&modules.root, options) {
+        writeln!(
+            w,
+            "    pub fn route_for<'a>(name: &str, params: &[(&str, &'a str)]) -> Result<Route<'a>, UrlError> {{"
+        )?;
+    } else {
+        writeln!(
+            w,
+            "    pub fn route_for(name: &str, params: &[(&str, &str)]) -> Result<Route, UrlError> {{"
+        )?;
+    }
+    writeln!(w, "        match name {{")?;
+    for &(ref name, ref typ, action) in actions.iter() {
+        let params = action
+            .route_parameters
+            .iter()
+            .chain(action.query_parameters.iter())
+            .map(|param| format!("\"{}\"", param.name))
+            .collect::<Vec<_>>();
+
+        writeln!(w, "            \"{}\" => {{", name)?;
+        writeln!(
+            w,
+            "                check_params(\"{}\", params, &[{}])?;",
+            name,
+            params.join(", ")
+        )?;
+        if params.is_empty() && action.body_parameters.is_empty() {
+            writeln!(w, "                Ok({}.into())", typ)?;
+        } else {
+            writeln!(w, "                let action = {} {{", typ)?;
+            for param in action.route_parameters.iter() {
+                if options.borrows(param) {
+                    writeln!(
+                        w,
+                        "                    {}: {}::borrow::Cow::Borrowed(required_text(\"{}\", params, \"{}\")?),",
+                        param.name, alloc, name, param.name
+                    )?;
+                    continue;
+                }
+                writeln!(
+                    w,
+                    "                    {}: required_param(\"{}\", params, \"{}\", |text, error| Error::{} {{",
+                    param.name,
+                    name,
+                    param.name,
+                    errors.variant(param)
+                )?;
+                writeln!(w, "                        kind: ParamKind::Path,")?;
+                writeln!(w, "                        text,")?;
+                writeln!(w, "                        error,")?;
+                writeln!(w, "                    }})?,")?;
+            }
+            for param in action.query_parameters.iter() {
+                if options.borrows(param) {
+                    writeln!(
+                        w,
+                        "                    {}: optional_text(params, \"{}\").map({}::borrow::Cow::Borrowed),",
+                        param.name, param.name, alloc
+                    )?;
+                    continue;
+                }
+                writeln!(
+                    w,
+                    "                    {}: optional_param(params, \"{}\", |text, error| Error::{} {{",
+                    param.name,
+                    param.name,
+                    errors.variant(param)
+                )?;
+                writeln!(w, "                        kind: ParamKind::Query,")?;
+                writeln!(w, "                        text,")?;
+                writeln!(w, "                        error,")?;
+                writeln!(w, "                    }})?,")?;
+            }
+            for param in action.body_parameters.iter() {
+                writeln!(w, "                    {}: None,", param.name)?;
+            }
+            writeln!(w, "                }};")?;
+            writeln!(w, "                Ok(action.into())")?;
+        }
+        writeln!(w, "            }}")?;
+    }
+    writeln!(
+        w,
+        "            _ => Err(UrlError::UnknownRoute(name.to_string())),"
+    )?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "    /// Make a path to the action named `name` with the given parameters,"
+    )?;
+    writeln!(
+        w,
+        "    /// for when the route isn't known until runtime, such as in a"
+    )?;
+    writeln!(w, "    /// template.  See [`route_for`] for the details.")?;
+    writeln!(w, "    ///")?;
+    writeln!(w, "    /// [`route_for`]: fn.route_for.html")?;
+    writeln!(
+        w,
+        "    pub fn url_for(name: &str, params: &[(&str, &str)]) -> Result<String, UrlError> {{"
+    )?;
+    writeln!(
+        w,
+        "        route_for(name, params).map(|route| route.to_path())"
+    )?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    if actions.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(w, "    fn check_params(")?;
+    writeln!(w, "        route: &'static str,")?;
+    writeln!(w, "        params: &[(&str, &str)],")?;
+    writeln!(w, "        known: &[&str],")?;
+    writeln!(w, "    ) -> Result<(), UrlError> {{")?;
+    writeln!(w, "        for &(param, _) in params {{")?;
+    writeln!(w, "            if !known.contains(&param) {{")?;
+    writeln!(w, "                return Err(UrlError::UnknownParam {{")?;
+    writeln!(w, "                    route,")?;
+    writeln!(w, "                    param: param.to_string(),")?;
+    writeln!(w, "                }});")?;
+    writeln!(w, "            }}")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "        Ok(())")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(w, "    fn optional_param<T: {}::str::FromStr>(", core)?;
+    writeln!(w, "        params: &[(&str, &str)],")?;
+    writeln!(w, "        name: &str,")?;
+    writeln!(w, "        error: fn(String, T::Err) -> Error,")?;
+    writeln!(w, "    ) -> Result<Option<T>, UrlError> {{")?;
+    writeln!(
+        w,
+        "        match params.iter().find(|&&(param, _)| param == name) {{"
+    )?;
+    writeln!(w, "            None => Ok(None),")?;
+    writeln!(w, "            Some(&(_, text)) => text")?;
+    writeln!(w, "                .parse()")?;
+    writeln!(w, "                .map(Some)")?;
+    writeln!(
+        w,
+        "                .map_err(|e| error(text.to_string(), e))"
+    )?;
+    writeln!(w, "                .map_err(UrlError::Param),")?;
+    writeln!(w, "        }}")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+    writeln!(w, "    fn required_param<T: {}::str::FromStr>(", core)?;
+    writeln!(w, "        route: &'static str,")?;
+    writeln!(w, "        params: &[(&str, &str)],")?;
+    writeln!(w, "        name: &'static str,")?;
+    writeln!(w, "        error: fn(String, T::Err) -> Error,")?;
+    writeln!(w, "    ) -> Result<T, UrlError> {{")?;
+    writeln!(
+        w,
+        "        optional_param(params, name, error)?.ok_or(UrlError::MissingParam {{"
+    )?;
+    writeln!(w, "            route,")?;
+    writeln!(w, "            param: name,")?;
+    writeln!(w, "        }})")?;
+    writeln!(w, "    }}")?;
+    writeln!(w)?;
+
+    let borrows = |params: &[Param]| params.iter().any(|param| options.borrows(param));
+    let borrowed_path = actions
+        .iter()
+        .any(|(_, _, action)| borrows(&action.route_parameters));
+    let borrowed_query = actions
+        .iter()
+        .any(|(_, _, action)| borrows(&action.query_parameters));
+    if borrowed_path || borrowed_query {
+        writeln!(w, "    fn optional_text<'a>(")?;
+        writeln!(w, "        params: &[(&str, &'a str)],")?;
+        writeln!(w, "        name: &str,")?;
+        writeln!(w, "    ) -> Option<&'a str> {{")?;
+        writeln!(w, "        params")?;
+        writeln!(w, "            .iter()")?;
+        writeln!(w, "            .find(|&&(param, _)| param == name)")?;
+        writeln!(w, "            .map(|&(_, text)| text)")?;
+        writeln!(w, "    }}")?;
+        writeln!(w)?;
+    }
+    if borrowed_path {
+        writeln!(w, "    fn required_text<'a>(")?;
+        writeln!(w, "        route: &'static str,")?;
+        writeln!(w, "        params: &[(&str, &'a str)],")?;
+        writeln!(w, "        name: &'static str,")?;
+        writeln!(w, "    ) -> Result<&'a str, UrlError> {{")?;
+        writeln!(
+            w,
+            "        optional_text(params, name).ok_or(UrlError::MissingParam {{"
+        )?;
+        writeln!(w, "            route,")?;
+        writeln!(w, "            param: name,")?;
+        writeln!(w, "        }})")?;
+        writeln!(w, "    }}")?;
+        writeln!(w)?;
+    }
+
+    Ok(())
+}
+
+/// The name of an action for `route_for`, such as `people.show`.
+pub fn action_name(modules: &[&str], action: &FlattenedAction) -> String {
+    modules
+        .iter()
+        .map(|module| to_snake_case(module))
+        .chain(std::iter::once(to_snake_case(&action.name)))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// The path to an action's type from the root module, such as
+/// `people::Show`.
+pub fn action_type(modules: &[&str], action: &FlattenedAction) -> String {
+    modules
+        .iter()
+        .map(|module| to_snake_case(module))
+        .chain(std::iter::once(to_caps_case(&action.name)))
+        .collect::<Vec<_>>()
+        .join("::")
+}
@@ -48,6 +48,14 @@
 //! }
 //! ```
 //!
+//! To have the compiler check that every route is handled, turn on
+//! [`handlers`](build/struct.Builder.html#method.handlers) in the build
+//! script, implement the generated `Handlers` trait, and pass matched
+//! routes to `routes::dispatch`.  For async apps, turn on
+//! [`async_handlers`](build/struct.Builder.html#method.async_handlers)
+//! instead: `routes::dispatch` then matches a request itself and awaits
+//! the `AsyncHandlers` method for it.
+//!
 //! See the documentation for the generated module for more information,
 //! or the examples for a complete application.
 //!
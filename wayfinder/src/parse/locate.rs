@@ -0,0 +1,106 @@
+use crate::core::PathSegment;
+use crate::core::{display_action, Location, RouteConfig, Routes, SourceMap};
+
+/// Find where each part of a route config was declared in the route
+/// file.  The config should be the one parsed from this input.
+///
+/// ```
+/// # use wayfinder::{parse, Method};
+/// let input = "/\n  people\n    GET People::Index\n      [page: usize]\n";
+/// let config = parse::route_config(input).unwrap().1;
+/// let source_map = parse::source_map(input, &config);
+///
+/// let people = vec!["people".into()];
+/// let location = source_map.resource(&people, Method::Get, "People::Index").unwrap();
+/// assert_eq!((location.line, location.column), (3, 5));
+/// let location = source_map.param(&people, Some(Method::Get), "page").unwrap();
+/// assert_eq!((location.line, location.column), (4, 14));
+/// ```
+pub fn source_map(input: &str, config: &RouteConfig) -> SourceMap {
+    let mut source_map = SourceMap::default();
+
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .skip_while(|(_, line)| line.trim_end() != "/");
+    if let Some((n, line)) = lines.next() {
+        source_map.insert_route(&[], segment_location(n, line));
+    }
+
+    let mut lines = lines.filter(|(_, line)| !line.trim().is_empty());
+    locate_routes(&config.routes, &mut vec![], &mut lines, &mut source_map);
+
+    source_map
+}
+
+fn locate_routes<'a, I: Iterator<Item = (usize, &'a str)>>(
+    routes: &Routes,
+    path: &mut Vec<PathSegment>,
+    lines: &mut I,
+    source_map: &mut SourceMap,
+) {
+    for param in routes.query_parameters.iter() {
+        if let Some((n, line)) = lines.next() {
+            source_map.insert_param(path, None, &param.name, type_location(n, line));
+        }
+    }
+
+    for resource in routes.resources.iter() {
+        if let Some((n, line)) = lines.next() {
+            let name = display_action(resource);
+            source_map.insert_resource(path, resource.method, &name, segment_location(n, line));
+        }
+        let params = resource
+            .query_parameters
+            .iter()
+            .chain(resource.body_parameters.iter());
+        for param in params {
+            if let Some((n, line)) = lines.next() {
+                let location = type_location(n, line);
+                source_map.insert_param(path, Some(resource.method), &param.name, location);
+            }
+        }
+    }
+
+    for child in routes.routes.iter() {
+        path.push(child.path_segment.clone());
+        if let Some((n, line)) = lines.next() {
+            source_map.insert_route(path, segment_location(n, line));
+            if let PathSegment::Dynamic(ref param) = child.path_segment {
+                source_map.insert_param(path, None, &param.name, type_location(n, line));
+            }
+        }
+        locate_routes(&child.routes, path, lines, source_map);
+        path.pop();
+    }
+}
+
+/// The location of the first thing on the line.
+fn segment_location(n: usize, line: &str) -> Location {
+    Location {
+        file: None,
+        line: n + 1,
+        column: line.chars().take_while(|c| c.is_whitespace()).count() + 1,
+    }
+}
+
+/// The location of the type in a parameter line, like `{id: Uuid}` or
+/// `{path: raw String}`.
+fn type_location(n: usize, line: &str) -> Location {
+    let column = match line.find(':') {
+        Some(colon) => {
+            let mut typ = line[colon + 1..].trim_start();
+            if typ.starts_with("raw ") {
+                typ = typ[3..].trim_start();
+            }
+            line[..line.len() - typ.len()].chars().count() + 1
+        }
+        None => segment_location(n, line).column,
+    };
+
+    Location {
+        file: None,
+        line: n + 1,
+        column,
+    }
+}
@@ -7,8 +7,8 @@ use lazy_static::lazy_static;
 use nom::types::CompleteStr;
 use nom::{
     alt_complete, apply, char, complete, count, delimited, do_parse, eof, line_ending, many0,
-    many1, named, not, not_line_ending, pair, preceded, recognize, return_error, tag, tag_no_case,
-    take_while1, terminated, value, ws, IResult,
+    many1, named, not, not_line_ending, opt, pair, preceded, recognize, return_error, tag,
+    tag_no_case, take_while, take_while1, terminated, value, ws, IResult,
 };
 
 use crate::core::*;
@@ -16,6 +16,9 @@ use crate::core::*;
 #[macro_use]
 pub mod errors;
 
+mod locate;
+pub use locate::source_map;
+
 macro_rules! indented {
     (
         $name:ident < $ty:ident > , $($content:tt)*
@@ -34,6 +37,10 @@ pub fn is_identifier_char(ch: char) -> bool {
     ch.is_alphanumeric() || ch == '_'
 }
 
+fn is_space(ch: char) -> bool {
+    ch == ' '
+}
+
 named!(pub method<CompleteStr, Method>,
     alt_complete!(
         value!(Method::Get, tag_no_case!("get")) |
@@ -53,8 +60,9 @@ named!(pub param<CompleteStr, Param>,
     do_parse!(
         name: take_while1!(is_identifier_char) >>
         ws!(colon) >>
+        raw: opt!(terminated!(tag!("raw"), take_while1!(is_space))) >>
         typ: take_while1!(is_identifier_char) >>
-        (Param::new(name, typ))
+        (if raw.is_some() { Param::new_raw(name, typ) } else { Param::new(name, typ) })
     )
 );
 
@@ -82,6 +90,15 @@ indented!(
     )
 );
 
+indented!(
+    body_parameter<Param>,
+    delimited!(
+        char!('<'),
+        ws!(param),
+        return_error!(err_str!("Expected a \">\""), char!('>'))
+    )
+);
+
 named!(target<CompleteStr, (bool, Vec<String>, String)>,
     alt_complete!(
         do_parse!(
@@ -104,6 +121,27 @@ named!(target<CompleteStr, (bool, Vec<String>, String)>,
     )
 );
 
+named!(pub trailing_slash<CompleteStr, TrailingSlash>,
+    preceded!(
+        take_while1!(is_space),
+        delimited!(
+            tag!("(trailing slash:"),
+            preceded!(
+                take_while!(is_space),
+                return_error!(
+                    err_str!("Expected \"lenient\", \"strict\" or \"redirect\""),
+                    alt_complete!(
+                        value!(TrailingSlash::Lenient, tag!("lenient")) |
+                        value!(TrailingSlash::Strict, tag!("strict")) |
+                        value!(TrailingSlash::Redirect, tag!("redirect"))
+                    )
+                )
+            ),
+            return_error!(err_str!("Expected a \")\""), char!(')'))
+        )
+    )
+);
+
 named!(require_newline<CompleteStr, ()>,
     return_error!(
         err_str!("Expected a newline"),
@@ -125,12 +163,18 @@ pub fn resource(input: CompleteStr, level: usize) -> IResult<CompleteStr, Resour
                         apply!(query_parameter, level + 1),
                         require_newline
                     ))
+                >> body_parameters:
+                    many0!(terminated!(
+                        apply!(body_parameter, level + 1),
+                        require_newline
+                    ))
                 >> (Resource {
                     method,
                     modules: target.1,
                     name: target.2,
                     is_redirect: target.0,
                     query_parameters,
+                    body_parameters,
                 })
         )
     )
@@ -178,6 +222,7 @@ pub fn nested_routes(input: CompleteStr, level: usize) -> IResult<CompleteStr, N
     do_parse!(
         input,
         path_segment: apply!(path_segment, level)
+            >> trailing_slash: opt!(trailing_slash)
             >> many1!(line_ending)
             >> routes:
                 return_error!(
@@ -186,7 +231,8 @@ pub fn nested_routes(input: CompleteStr, level: usize) -> IResult<CompleteStr, N
                 )
             >> (NestedRoutes {
                 path_segment,
-                routes
+                routes,
+                trailing_slash,
             })
     )
 }